@@ -0,0 +1,77 @@
+use quote::quote;
+use syn::Lit;
+
+use crate::gen::{tag_kind, type_reference};
+use crate::parse::{Choices, SchemaNode};
+
+/// Builds the `dfu_structures::describe::Node` of `node`, which is registered next to its walker
+pub(crate) fn describe(node: &SchemaNode) -> proc_macro2::TokenStream {
+    match node {
+        SchemaNode::Register(_, nodes) | SchemaNode::All(nodes) => {
+            let nodes = nodes.iter().map(describe);
+            quote! { dfu_structures::describe::Node::All(std::vec![#(#nodes),*]) }
+        }
+        SchemaNode::Opt(key, inner) => {
+            let inner = describe(inner);
+            quote! { dfu_structures::describe::Node::Opt { key: #key, node: std::boxed::Box::new(#inner) } }
+        }
+        SchemaNode::Req(key, inner) => {
+            let inner = describe(inner);
+            quote! { dfu_structures::describe::Node::Req { key: #key, node: std::boxed::Box::new(#inner) } }
+        }
+        SchemaNode::Reference(ident) => {
+            let type_ref = type_reference(ident);
+            quote! { dfu_structures::describe::Node::Refer(#type_ref) }
+        }
+        SchemaNode::List(inner) => {
+            let inner = describe(inner);
+            quote! { dfu_structures::describe::Node::List(std::boxed::Box::new(#inner)) }
+        }
+        SchemaNode::MapValues(inner) => {
+            let inner = describe(inner);
+            quote! { dfu_structures::describe::Node::MapValues(std::boxed::Box::new(#inner)) }
+        }
+        SchemaNode::TaggedChoice(key, choices) => {
+            let (arms, default) = describe_choices(choices);
+            quote! { dfu_structures::describe::Node::TaggedChoice { key: #key, arms: #arms, default: #default } }
+        }
+        SchemaNode::MatchValue(choices) => {
+            let (arms, default) = describe_choices(choices);
+            quote! { dfu_structures::describe::Node::MatchValue { arms: #arms, default: #default } }
+        }
+        SchemaNode::Rename(old, new) => quote! { dfu_structures::describe::Node::Rename { old: #old, new: #new } },
+        SchemaNode::Remove(key) => quote! { dfu_structures::describe::Node::Remove { key: #key } },
+        SchemaNode::Default(key, literal) => {
+            let value = quote!(#literal).to_string();
+            quote! { dfu_structures::describe::Node::Default { key: #key, value: #value } }
+        }
+        SchemaNode::ConvertType(key, from, to) => {
+            let (from, to) = (tag_kind(*from), tag_kind(*to));
+            quote! { dfu_structures::describe::Node::ConvertType { key: #key, from: #from, to: #to } }
+        }
+        SchemaNode::Custom(_) => quote! { dfu_structures::describe::Node::Custom },
+    }
+}
+
+fn describe_choices(choices: &Choices) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let arms = choices.arms.iter().map(|arm| {
+        let patterns = arm.patterns.iter().map(|pattern| match pattern {
+            Lit::Str(string) => quote! { dfu_structures::describe::Pattern::String(#string) },
+            Lit::Int(int) => {
+                let number: i64 = int.base10_parse().expect("patterns are checked when parsing");
+                quote! { dfu_structures::describe::Pattern::Int(#number) }
+            }
+            _ => unreachable!("patterns are checked when parsing"),
+        });
+        let node = describe(&arm.node);
+        quote! { dfu_structures::describe::Arm { patterns: std::vec![#(#patterns),*], node: #node } }
+    });
+    let default = match &choices.default {
+        Some(node) => {
+            let node = describe(node);
+            quote! { std::option::Option::Some(std::boxed::Box::new(#node)) }
+        }
+        None => quote! { std::option::Option::None },
+    };
+    (quote! { std::vec![#(#arms),*] }, default)
+}
@@ -1,79 +1,322 @@
 use quote::quote;
 
-use crate::parse::SchemaNode;
+use crate::describe::describe;
+use crate::parse::{Choices, SchemaNode, TagKind};
+use syn::Lit;
 
-pub(crate) fn generate_output(info: &syn::Ident, node: &SchemaNode) -> proc_macro2::TokenStream {
+/// The `TypeReference` variant for a type name, which [`crate::validate::validate`] has checked
+pub(crate) fn type_reference(ident: &syn::Ident) -> proc_macro2::TokenStream {
+    let type_ref = crate::validate::type_reference(ident).expect("type names are validated before generating");
+    let variant = syn::Ident::new(&format!("{type_ref:?}"), ident.span());
+    quote! { dfu_structures::types::TypeReference::#variant }
+}
+
+/// Generates the walker for `node`. `path` names the node for test coverage, as the registered type followed by the keys
+/// leading to the node.
+pub(crate) fn generate_output(info: &syn::Ident, node: &SchemaNode, path: &str) -> proc_macro2::TokenStream {
     match node {
         SchemaNode::Register(ident, nodes) => {
+            let path = ident.to_string();
             let mut nodes_tokens = proc_macro2::TokenStream::new();
+            let mut node_paths = Vec::new();
             for node in nodes {
-                let inner = generate_output(info, node);
+                let inner = generate_output(info, node, &path);
                 nodes_tokens.extend(inner);
+                collect_paths(node, &path, &mut node_paths);
             }
 
+            let type_ref = type_reference(ident);
+            let description = describe(node);
+            let types = if nodes.iter().any(has_reference) {
+                quote! { types }
+            } else {
+                quote! { _ }
+            };
             quote! {
-                #info.references.#ident.insert(#info.version,
-                    std::boxed::Box::leak(std::boxed::Box::new(|value: &mut quartz_nbt::NbtTag, from, to|{
-                        #nodes_tokens
-                        Ok(())
-                    })));
+                {
+                    let version: usize = #info.version;
+                    #[cfg(test)]
+                    dfu_structures::coverage::declare(&[#(#node_paths),*]);
+                    #info.references.describe(#type_ref, version, #description);
+                    #info.references.get_mut(#type_ref).insert(version,
+                        std::boxed::Box::new(move |#types: &dfu_structures::types::Types, value: &mut quartz_nbt::NbtTag, from: usize, to: usize| {
+                            #nodes_tokens
+                            Ok(())
+                        }));
+                }
             }
         }
         SchemaNode::All(nodes) => {
             let mut nodes_tokens = proc_macro2::TokenStream::new();
             for node in nodes {
-                let inner = generate_output(info, node);
+                let inner = generate_output(info, node, path);
                 nodes_tokens.extend(inner);
             }
             nodes_tokens
         }
         SchemaNode::Opt(val, node) => {
-            let inner = generate_output(info, node);
+            let path = format!("{path}/{}", val.value());
+            let inner = generate_output(info, node, &path);
             quote! {
-                let opt_value: &mut quartz_nbt::NbtCompound = value.try_into()?;
-                if let std::option::Option::Some(value) = opt_value.inner_mut().get_mut(#val) {
-                    #inner
+                {
+                    let opt_value: &mut quartz_nbt::NbtCompound = value.try_into()?;
+                    if let std::option::Option::Some(value) = opt_value.inner_mut().get_mut(#val) {
+                        #[cfg(test)]
+                        dfu_structures::coverage::matched(#path);
+                        #inner
+                    }
                 }
             }
         }
         SchemaNode::Req(val, node) => {
-            let inner = generate_output(info, node);
+            let path = format!("{path}/{}", val.value());
+            let inner = generate_output(info, node, &path);
             quote! {
-                let value: &mut quartz_nbt::NbtCompound = value.try_into()?;
-                let value: &mut quartz_nbt::NbtTag = value.get_mut(#val)?;
-                #inner
+                {
+                    let value: &mut quartz_nbt::NbtCompound = value.try_into()?;
+                    let value: &mut quartz_nbt::NbtTag = value.get_mut(#val)?;
+                    #[cfg(test)]
+                    dfu_structures::coverage::matched(#path);
+                    #inner
+                }
             }
         }
         SchemaNode::Reference(ident) => {
+            // the referenced value is passed on as it is, types such as item_name aren't compounds. It is upgraded by the
+            // registry the walker was called from, so registries with different functions stay independent.
+            let type_ref = type_reference(ident);
             let tokens = quote! {
-                dfu_structures::types::convert(&crate::TYPES.#ident, <&mut quartz_nbt::NbtCompound>::try_from(value)?, from, to);
+                dfu_structures::types::convert(types, #type_ref, value, from, to)?;
             };
             tokens
         }
         SchemaNode::List(node) => {
-            let inner = generate_output(info, node);
+            let inner = generate_output(info, node, path);
             let tokens = quote! {
-                let list: &mut quartz_nbt::NbtList = value.try_into()?;
-                for value in list.iter_mut() {
-                    #inner
+                {
+                    let list: &mut quartz_nbt::NbtList = value.try_into()?;
+                    for value in list.iter_mut() {
+                        #inner
+                    }
                 }
             };
             tokens
         }
         SchemaNode::MapValues(node) => {
-            let inner = generate_output(info, node);
+            let inner = generate_output(info, node, path);
             let tokens = quote! {
-                let compound: &mut quartz_nbt::NbtCompound = value.try_into()?;
-                for (key, value) in compound.inner_mut().iter_mut() {
-                    #inner
+                {
+                    let compound: &mut quartz_nbt::NbtCompound = value.try_into()?;
+                    for value in compound.inner_mut().values_mut() {
+                        #inner
+                    }
                 }
             };
             tokens
         }
+        SchemaNode::TaggedChoice(key, choices) => {
+            let path = format!("{path}/{}", key.value());
+            let select = generate_select(choices);
+            let dispatch = generate_dispatch(info, choices, &path);
+            quote! {
+                {
+                    let arm: std::option::Option<usize> = match &*value {
+                        quartz_nbt::NbtTag::Compound(compound) => match compound.inner().get(#key) {
+                            std::option::Option::Some(quartz_nbt::NbtTag::String(tag)) => match tag.as_str() { #select },
+                            _ => std::option::Option::None,
+                        },
+                        _ => std::option::Option::None,
+                    };
+                    #dispatch
+                }
+            }
+        }
+        SchemaNode::MatchValue(choices) => {
+            let select = generate_select(choices);
+            let dispatch = generate_dispatch(info, choices, path);
+            let arm = if choices.matches_strings() {
+                quote! {
+                    match &*value {
+                        quartz_nbt::NbtTag::String(tag) => match tag.as_str() { #select },
+                        _ => std::option::Option::None,
+                    }
+                }
+            } else {
+                quote! {
+                    match &*value {
+                        quartz_nbt::NbtTag::Byte(number) => std::option::Option::Some(*number as i64),
+                        quartz_nbt::NbtTag::Short(number) => std::option::Option::Some(*number as i64),
+                        quartz_nbt::NbtTag::Int(number) => std::option::Option::Some(*number as i64),
+                        quartz_nbt::NbtTag::Long(number) => std::option::Option::Some(*number),
+                        _ => std::option::Option::None,
+                    }
+                    .and_then(|number| match number { #select })
+                }
+            };
+            quote! {
+                {
+                    let arm: std::option::Option<usize> = #arm;
+                    #dispatch
+                }
+            }
+        }
+        SchemaNode::Rename(old, new) => {
+            let path = format!("{path}/{}", old.value());
+            generate_fix(&path, quote! { dfu_structures::fixes::rename(compound, #old, #new) })
+        }
+        SchemaNode::Remove(key) => {
+            let path = format!("{path}/{}", key.value());
+            generate_fix(&path, quote! { dfu_structures::fixes::remove(compound, #key) })
+        }
+        SchemaNode::Default(key, literal) => {
+            let path = format!("{path}/{}", key.value());
+            // a default matches every compound it runs on, whether or not it had to insert the value
+            generate_fix(&path, quote! { { dfu_structures::fixes::default(compound, #key, #literal); true } })
+        }
+        SchemaNode::ConvertType(key, from, to) => {
+            let path = format!("{path}/{}", key.value());
+            let (from, to) = (tag_kind(*from), tag_kind(*to));
+            generate_fix(&path, quote! { dfu_structures::fixes::convert_type(compound, #key, #from, #to) })
+        }
         SchemaNode::Custom(closure) => {
+            // fixes only run when upgrading across the version they are registered for
             quote! {
-                dfu_structures::call_closure_with(#closure, value, from, to)?;
+                if from < version && version <= to {
+                    dfu_structures::call_closure_with(#closure, value, from, to)?;
+                }
+            }
+        }
+    }
+}
+
+/// Runs a declarative fix on the current compound. Like custom fixes, it only runs when upgrading across the version
+/// of its registration.
+fn generate_fix(path: &str, fix: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        if from < version && version <= to {
+            let compound: &mut quartz_nbt::NbtCompound = value.try_into()?;
+            if #fix {
+                #[cfg(test)]
+                dfu_structures::coverage::matched(#path);
             }
         }
     }
 }
+
+pub(crate) fn tag_kind(kind: TagKind) -> proc_macro2::TokenStream {
+    match kind {
+        TagKind::Byte => quote! { dfu_structures::fixes::TagKind::Byte },
+        TagKind::Short => quote! { dfu_structures::fixes::TagKind::Short },
+        TagKind::Int => quote! { dfu_structures::fixes::TagKind::Int },
+        TagKind::Long => quote! { dfu_structures::fixes::TagKind::Long },
+        TagKind::Float => quote! { dfu_structures::fixes::TagKind::Float },
+        TagKind::Double => quote! { dfu_structures::fixes::TagKind::Double },
+        TagKind::String => quote! { dfu_structures::fixes::TagKind::String },
+    }
+}
+
+/// Match arms mapping the patterns of each arm to its index. The arm is picked before walking it, so the matched
+/// value doesn't stay borrowed while the arm changes it.
+fn generate_select(choices: &Choices) -> proc_macro2::TokenStream {
+    let arms = choices.arms.iter().enumerate().map(|(idx, arm)| {
+        let patterns = arm.patterns.iter().map(|pattern| match pattern {
+            // integers are matched as i64, whatever suffix they were written with
+            Lit::Int(int) => Lit::Int(syn::LitInt::new(int.base10_digits(), int.span())),
+            pattern => pattern.clone(),
+        });
+        quote! { #(#patterns)|* => std::option::Option::Some(#idx), }
+    });
+    quote! {
+        #(#arms)*
+        _ => std::option::Option::None,
+    }
+}
+
+/// Walks the arm picked by [`generate_select`], or the `_` arm when none matched
+fn generate_dispatch(info: &syn::Ident, choices: &Choices, path: &str) -> proc_macro2::TokenStream {
+    let arms = choices.arms.iter().enumerate().map(|(idx, arm)| {
+        let path = arm_path(path, &arm.patterns);
+        let inner = generate_output(info, &arm.node, &path);
+        quote! {
+            std::option::Option::Some(#idx) => {
+                #[cfg(test)]
+                dfu_structures::coverage::matched(#path);
+                #inner
+            }
+        }
+    });
+    let default = choices
+        .default
+        .as_ref()
+        .map(|node| generate_output(info, node, &format!("{path}=_")));
+    quote! {
+        match arm {
+            #(#arms)*
+            _ => { #default }
+        }
+    }
+}
+
+/// Names an arm by the value it matches, like `item_stack/id=minecraft:potion`
+fn arm_path(path: &str, patterns: &[Lit]) -> String {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| match pattern {
+            Lit::Str(string) => string.value(),
+            Lit::Int(int) => int.base10_digits().to_string(),
+            _ => unreachable!("patterns are checked when parsing"),
+        })
+        .collect::<Vec<_>>();
+    format!("{path}={}", patterns.join("|"))
+}
+
+/// The paths of every keyed node in `node`, in the form [`generate_output`] names them
+fn collect_paths(node: &SchemaNode, path: &str, paths: &mut Vec<String>) {
+    match node {
+        SchemaNode::Opt(key, inner) | SchemaNode::Req(key, inner) => {
+            let path = format!("{path}/{}", key.value());
+            collect_paths(inner, &path, paths);
+            paths.push(path);
+        }
+        SchemaNode::All(nodes) => nodes.iter().for_each(|node| collect_paths(node, path, paths)),
+        SchemaNode::List(inner) | SchemaNode::MapValues(inner) => collect_paths(inner, path, paths),
+        SchemaNode::TaggedChoice(key, choices) => collect_choice_paths(choices, &format!("{path}/{}", key.value()), paths),
+        SchemaNode::MatchValue(choices) => collect_choice_paths(choices, path, paths),
+        SchemaNode::Rename(key, _) | SchemaNode::Remove(key) | SchemaNode::Default(key, _) | SchemaNode::ConvertType(key, ..) => {
+            paths.push(format!("{path}/{}", key.value()))
+        }
+        SchemaNode::Register(..) | SchemaNode::Reference(_) | SchemaNode::Custom(_) => {}
+    }
+}
+
+/// Whether the walker of `node` upgrades referenced types, which needs the registry
+fn has_reference(node: &SchemaNode) -> bool {
+    match node {
+        SchemaNode::Reference(_) => true,
+        SchemaNode::Opt(_, inner) | SchemaNode::Req(_, inner) | SchemaNode::List(inner) | SchemaNode::MapValues(inner) => {
+            has_reference(inner)
+        }
+        SchemaNode::All(nodes) | SchemaNode::Register(_, nodes) => nodes.iter().any(has_reference),
+        SchemaNode::TaggedChoice(_, choices) | SchemaNode::MatchValue(choices) => choices
+            .arms
+            .iter()
+            .map(|arm| &arm.node)
+            .chain(choices.default.as_deref())
+            .any(has_reference),
+        SchemaNode::Rename(..) | SchemaNode::Remove(_) | SchemaNode::Default(..) | SchemaNode::ConvertType(..) | SchemaNode::Custom(_) => {
+            false
+        }
+    }
+}
+
+/// Every arm is a node of its own, which has to match some data. The `_` arm only declares the nodes inside of it.
+fn collect_choice_paths(choices: &Choices, path: &str, paths: &mut Vec<String>) {
+    for arm in &choices.arms {
+        let path = arm_path(path, &arm.patterns);
+        collect_paths(&arm.node, &path, paths);
+        paths.push(path);
+    }
+    if let Some(default) = &choices.default {
+        collect_paths(default, &format!("{path}=_"), paths);
+    }
+}
@@ -1,13 +1,13 @@
-#![feature(proc_macro_quote)]
-#![feature(extend_one)]
-
+pub(crate) mod describe;
 pub(crate) mod gen;
 pub(crate) mod parse;
+pub(crate) mod validate;
 
 use gen::generate_output;
 use parse::SchemaDefinition;
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
+use validate::validate;
 
 #[proc_macro]
 pub fn define_schema(input: TokenStream) -> TokenStream {
@@ -15,7 +15,10 @@ pub fn define_schema(input: TokenStream) -> TokenStream {
 
     let mut nodes_tokens = proc_macro2::TokenStream::new();
     for node in &root.nodes {
-        let inner = generate_output(&info, node);
+        if let Err(err) = validate(node) {
+            return err.to_compile_error().into();
+        }
+        let inner = generate_output(&info, node, "");
         nodes_tokens.extend(inner);
     }
 
@@ -49,12 +49,122 @@ impl Parse for SchemaTree {
 pub(crate) enum SchemaNode {
     Register(syn::Ident, Vec<SchemaNode>),
     All(Vec<SchemaNode>),
-    Opt(String, Box<SchemaNode>),
-    Req(String, Box<SchemaNode>),
+    Opt(syn::LitStr, Box<SchemaNode>),
+    Req(syn::LitStr, Box<SchemaNode>),
     Reference(syn::Ident),
     List(Box<SchemaNode>),
     MapValues(Box<SchemaNode>),
     Custom(ExprClosure),
+    /// Walks the arm matching the string at a key of the current compound
+    TaggedChoice(syn::LitStr, Choices),
+    /// Walks the arm matching the current value, a string or a number
+    MatchValue(Choices),
+    Rename(syn::LitStr, syn::LitStr),
+    Remove(syn::LitStr),
+    /// Inserts a literal, which converts into a tag, where the key is missing
+    Default(syn::LitStr, Lit),
+    /// Converts the value at the key between two tag types, like `int -> string`
+    ConvertType(syn::LitStr, TagKind, TagKind),
+}
+
+/// Tag types named in `convert_type`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TagKind {
+    Byte,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    String,
+}
+
+impl Parse for TagKind {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: syn::Ident = input.parse()?;
+        match ident.to_string().as_str() {
+            "byte" => Ok(Self::Byte),
+            "short" => Ok(Self::Short),
+            "int" => Ok(Self::Int),
+            "long" => Ok(Self::Long),
+            "float" => Ok(Self::Float),
+            "double" => Ok(Self::Double),
+            "string" => Ok(Self::String),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "expected one of: byte, short, int, long, float, double, string",
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Choices {
+    pub arms: Vec<ChoiceArm>,
+    /// The `_` arm, walked when no other arm matches
+    pub default: Option<Box<SchemaNode>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ChoiceArm {
+    pub patterns: Vec<Lit>,
+    pub node: SchemaNode,
+}
+
+impl Parse for Choices {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut arms: Vec<ChoiceArm> = Vec::new();
+        let mut default = None;
+        while !content.is_empty() {
+            if content.peek(Token![_]) {
+                let underscore = content.parse::<Token![_]>()?;
+                if default.is_some() {
+                    return Err(syn::Error::new(underscore.span(), "duplicate `_` arm"));
+                }
+                content.parse::<Token![=>]>()?;
+                default = Some(Box::new(content.parse()?));
+            } else {
+                let mut patterns = vec![content.parse::<Lit>()?];
+                while content.peek(Token![|]) {
+                    content.parse::<Token![|]>()?;
+                    patterns.push(content.parse()?);
+                }
+                // every pattern of a node has the kind of the first one
+                let first = arms.first().map_or(&patterns[0], |arm| &arm.patterns[0]);
+                let strings = matches!(first, Lit::Str(_));
+                for pattern in &patterns {
+                    match pattern {
+                        Lit::Str(_) | Lit::Int(_) if matches!(pattern, Lit::Str(_)) == strings => {}
+                        Lit::Str(_) | Lit::Int(_) => return Err(syn::Error::new(pattern.span(), "patterns mix strings and integers")),
+                        _ => return Err(syn::Error::new(pattern.span(), "expected string or integer literal")),
+                    }
+                }
+                content.parse::<Token![=>]>()?;
+                arms.push(ChoiceArm {
+                    patterns,
+                    node: content.parse()?,
+                });
+            }
+
+            // arms are separated by commas, a trailing comma is allowed
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<Token![,]>()?;
+        }
+
+        Ok(Self { arms, default })
+    }
+}
+
+impl Choices {
+    /// Whether the arms match strings rather than numbers
+    pub(crate) fn matches_strings(&self) -> bool {
+        self.arms.first().is_none_or(|arm| matches!(arm.patterns[0], Lit::Str(_)))
+    }
 }
 
 impl Parse for SchemaNode {
@@ -110,7 +220,7 @@ impl Parse for SchemaNode {
                 let val: ExprLit = content.parse()?;
                 content.parse::<Token![,]>()?;
                 if let Lit::Str(s) = val.lit {
-                    Ok(SchemaNode::Req(s.value(), Box::new(content.parse()?)))
+                    Ok(SchemaNode::Req(s, Box::new(content.parse()?)))
                 } else {
                     Err(syn::Error::new(val.span(), "expected string literal"))
                 }
@@ -121,7 +231,7 @@ impl Parse for SchemaNode {
                 let val: ExprLit = content.parse()?;
                 content.parse::<Token![,]>()?;
                 if let Lit::Str(s) = val.lit {
-                    Ok(SchemaNode::Opt(s.value(), Box::new(content.parse()?)))
+                    Ok(SchemaNode::Opt(s, Box::new(content.parse()?)))
                 } else {
                     Err(syn::Error::new(val.span(), "expected string literal"))
                 }
@@ -144,6 +254,50 @@ impl Parse for SchemaNode {
                 let node: SchemaNode = content.parse()?;
                 Ok(SchemaNode::MapValues(Box::new(node)))
             }
+            "tagged_choice" => {
+                let content;
+                parenthesized!(content in input);
+                let key: syn::LitStr = content.parse()?;
+                content.parse::<Token![,]>()?;
+                let choices: Choices = content.parse()?;
+                if !choices.matches_strings() {
+                    return Err(syn::Error::new(key.span(), "tagged_choice matches string tags"));
+                }
+                Ok(SchemaNode::TaggedChoice(key, choices))
+            }
+            "match_value" => {
+                let content;
+                parenthesized!(content in input);
+                Ok(SchemaNode::MatchValue(content.parse()?))
+            }
+            "rename" => {
+                let content;
+                parenthesized!(content in input);
+                let old: syn::LitStr = content.parse()?;
+                content.parse::<Token![,]>()?;
+                Ok(SchemaNode::Rename(old, content.parse()?))
+            }
+            "remove" => {
+                let content;
+                parenthesized!(content in input);
+                Ok(SchemaNode::Remove(content.parse()?))
+            }
+            "default" => {
+                let content;
+                parenthesized!(content in input);
+                let key: syn::LitStr = content.parse()?;
+                content.parse::<Token![,]>()?;
+                Ok(SchemaNode::Default(key, content.parse()?))
+            }
+            "convert_type" => {
+                let content;
+                parenthesized!(content in input);
+                let key: syn::LitStr = content.parse()?;
+                content.parse::<Token![,]>()?;
+                let from: TagKind = content.parse()?;
+                content.parse::<Token![->]>()?;
+                Ok(SchemaNode::ConvertType(key, from, content.parse()?))
+            }
             "custom" => {
                 let content;
                 parenthesized!(content in input);
@@ -154,3 +308,64 @@ impl Parse for SchemaNode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{SchemaNode, TagKind};
+
+    fn parse(node: &str) -> Result<SchemaNode, String> {
+        syn::parse_str(node).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn choices() {
+        match parse(
+            r#"tagged_choice("id", { "minecraft:potion" | "minecraft:splash_potion" => refer(item_name), _ => all(refer(entity)), })"#,
+        ) {
+            Ok(SchemaNode::TaggedChoice(key, choices)) => {
+                assert_eq!(key.value(), "id");
+                assert_eq!(choices.arms.len(), 1);
+                assert_eq!(choices.arms[0].patterns.len(), 2);
+                assert!(choices.default.is_some());
+            }
+            other => panic!("{other:?}"),
+        }
+        assert!(matches!(
+            parse("match_value({ 1 | 2 => refer(item_name) })"),
+            Ok(SchemaNode::MatchValue(_))
+        ));
+    }
+
+    #[test]
+    fn fixes() {
+        assert!(matches!(parse(r#"rename("TileID", "Block")"#), Ok(SchemaNode::Rename(..))));
+        assert!(matches!(parse(r#"default("Color", 10i8)"#), Ok(SchemaNode::Default(..))));
+        match parse(r#"convert_type("id", int -> string)"#) {
+            Ok(SchemaNode::ConvertType(key, from, to)) => {
+                assert_eq!(key.value(), "id");
+                assert_eq!((from, to), (TagKind::Int, TagKind::String));
+            }
+            other => panic!("{other:?}"),
+        }
+        assert_eq!(
+            parse(r#"convert_type("id", integer -> string)"#).unwrap_err(),
+            "expected one of: byte, short, int, long, float, double, string"
+        );
+    }
+
+    #[test]
+    fn invalid_choices() {
+        assert_eq!(
+            parse(r#"match_value({ "minecraft:stone" => refer(item_name), 1 => refer(item_name) })"#).unwrap_err(),
+            "patterns mix strings and integers"
+        );
+        assert_eq!(
+            parse("tagged_choice(\"id\", { 1 => refer(item_name) })").unwrap_err(),
+            "tagged_choice matches string tags"
+        );
+        assert_eq!(
+            parse("match_value({ _ => refer(item_name), _ => refer(item_name) })").unwrap_err(),
+            "duplicate `_` arm"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use crate::parse::{Choices, SchemaNode};
+use dfu_structures::types::TypeReference;
+
+/// Every key a registration of a type may walk, at any depth. Types which aren't listed are walked without keys.
+const KNOWN_FIELDS: [(&str, &[&str]); 8] = [
+    (
+        "player",
+        &[
+            "Inventory",
+            "EnderItems",
+            "RootVehicle",
+            "Entity",
+            "ShoulderEntityLeft",
+            "ShoulderEntityRight",
+        ],
+    ),
+    (
+        "entity",
+        &[
+            "id",
+            "Item",
+            "Items",
+            "Equipment",
+            "HandItems",
+            "ArmorItems",
+            "SaddleItem",
+            "ArmorItem",
+            "DecorItem",
+            "Inventory",
+            "Offers",
+            "Recipes",
+            "buy",
+            "buyB",
+            "sell",
+            "Riding",
+            "Passengers",
+            "SpawnData",
+            "SpawnPotentials",
+            "Entity",
+            "BlockState",
+            "carriedBlockState",
+            "DisplayState",
+            "Color",
+        ],
+    ),
+    (
+        "tile_entity",
+        &[
+            "id",
+            "Items",
+            "RecordItem",
+            "SpawnData",
+            "SpawnPotentials",
+            "Entity",
+            "blockState",
+            "Color",
+        ],
+    ),
+    ("item_stack", &["id", "tag", "EntityTag", "BlockEntityTag"]),
+    (
+        "chunk",
+        &["Level", "Entities", "TileEntities", "TileTicks", "i", "Sections", "Palette"],
+    ),
+    ("entity_chunk", &["Entities"]),
+    ("saved_data", &["data", "Features", "Objectives", "Teams"]),
+    ("block_state", &["Name"]),
+];
+
+/// Checks the registered and referenced types against [`TypeReference`], and the keys of a registration against the
+/// known fields of its type if they are listed, so a misspelled name fails to compile instead of silently never matching
+pub(crate) fn validate(node: &SchemaNode) -> syn::Result<()> {
+    if let SchemaNode::Register(registry, nodes) = node {
+        type_reference(registry)?;
+        let registry_name = registry.to_string();
+        // types without a list of known fields can have any keys
+        if let Some((_, fields)) = KNOWN_FIELDS.iter().find(|(name, _)| *name == registry_name) {
+            for node in nodes {
+                validate_keys(&registry_name, fields, node)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_keys(registry: &str, fields: &[&str], node: &SchemaNode) -> syn::Result<()> {
+    match node {
+        SchemaNode::Opt(key, inner) | SchemaNode::Req(key, inner) => {
+            validate_key(registry, fields, key)?;
+            validate_keys(registry, fields, inner)
+        }
+        SchemaNode::All(nodes) => nodes.iter().try_for_each(|node| validate_keys(registry, fields, node)),
+        SchemaNode::List(inner) | SchemaNode::MapValues(inner) => validate_keys(registry, fields, inner),
+        SchemaNode::TaggedChoice(key, choices) => {
+            validate_key(registry, fields, key)?;
+            validate_choices(registry, fields, choices)
+        }
+        SchemaNode::MatchValue(choices) => validate_choices(registry, fields, choices),
+        SchemaNode::Rename(old, new) => [old, new].into_iter().try_for_each(|key| validate_key(registry, fields, key)),
+        SchemaNode::Remove(key) | SchemaNode::Default(key, _) | SchemaNode::ConvertType(key, ..) => validate_key(registry, fields, key),
+        SchemaNode::Reference(ident) => type_reference(ident).map(|_| ()),
+        SchemaNode::Register(..) | SchemaNode::Custom(_) => Ok(()),
+    }
+}
+
+fn validate_choices(registry: &str, fields: &[&str], choices: &Choices) -> syn::Result<()> {
+    let arms = choices.arms.iter().map(|arm| &arm.node);
+    arms.chain(choices.default.as_deref())
+        .try_for_each(|node| validate_keys(registry, fields, node))
+}
+
+fn validate_key(registry: &str, fields: &[&str], key: &syn::LitStr) -> syn::Result<()> {
+    if fields.contains(&key.value().as_str()) {
+        return Ok(());
+    }
+    Err(syn::Error::new(
+        key.span(),
+        format!(
+            "unknown field `{}` for {registry}, expected one of: {}",
+            key.value(),
+            fields.join(", ")
+        ),
+    ))
+}
+
+/// Looks up the type named by `ident`, which is the name of its field in `Types`
+pub(crate) fn type_reference(ident: &syn::Ident) -> syn::Result<TypeReference> {
+    let name = ident.to_string();
+    if let Some(type_ref) = TypeReference::from_name(&name) {
+        return Ok(type_ref);
+    }
+    let names = TypeReference::ALL.map(TypeReference::name);
+    let closest = names.iter().min_by_key(|candidate| edit_distance(&name, candidate));
+    let message = match closest.filter(|candidate| edit_distance(&name, candidate) <= 2) {
+        Some(candidate) => format!("unknown type `{name}`, did you mean `{candidate}`?"),
+        None => format!("unknown type `{name}`, expected one of: {}", names.join(", ")),
+    };
+    Err(syn::Error::new(ident.span(), message))
+}
+
+/// Levenshtein distance between two names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, validate};
+    use crate::parse::SchemaNode;
+
+    fn check(register: &str) -> Result<(), String> {
+        let node: SchemaNode = syn::parse_str(register).unwrap();
+        validate(&node).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn known_fields() {
+        assert_eq!(
+            check(r#"register chunk { req("Level", all(opt("TileEntities", list(refer(tile_entity))))) }"#),
+            Ok(())
+        );
+        assert_eq!(check(r#"register item_name { custom(|value, _from, _to| Ok(())) }"#), Ok(()));
+    }
+
+    #[test]
+    fn types_without_known_fields_accept_any_key() {
+        assert_eq!(
+            check(r#"register options { opt("lang", custom(|value, _from, _to| Ok(()))) }"#),
+            Ok(())
+        );
+        assert_eq!(check(r#"register level { opt("Player", refer(player)) }"#), Ok(()));
+    }
+
+    #[test]
+    fn unknown_fields() {
+        let err = check(r#"register chunk { req("Level", opt("TileEntitites", list(refer(tile_entity)))) }"#).unwrap_err();
+        assert!(err.starts_with("unknown field `TileEntitites` for chunk"), "{err}");
+        let err = check(r#"register item_stack { opt("Tag", refer(entity)) }"#).unwrap_err();
+        assert!(err.starts_with("unknown field `Tag` for item_stack"), "{err}");
+        let err =
+            check(r#"register entity { tagged_choice("id", { "minecraft:horse" => opt("SadleItem", refer(item_stack)) }) }"#).unwrap_err();
+        assert!(err.starts_with("unknown field `SadleItem` for entity"), "{err}");
+    }
+
+    #[test]
+    fn unknown_types() {
+        let err = check(r#"register chunk { opt("Level", opt("Entities", list(refer(entty)))) }"#).unwrap_err();
+        assert_eq!(err, "unknown type `entty`, did you mean `entity`?");
+        let err = check(r#"register item_stak { opt("id", refer(item_name)) }"#).unwrap_err();
+        assert_eq!(err, "unknown type `item_stak`, did you mean `item_stack`?");
+        let err = check(r#"register entity { opt("Item", refer(thing)) }"#).unwrap_err();
+        assert!(
+            err.starts_with("unknown type `thing`, expected one of: level, player, chunk"),
+            "{err}"
+        );
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
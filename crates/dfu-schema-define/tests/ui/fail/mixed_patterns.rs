@@ -0,0 +1,16 @@
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+
+fn main() {
+    let mut types = Types::new();
+    let info = SchemaInfo::new(&mut types, 1451);
+    define_schema!(info, {
+        register item_name {
+            match_value({
+                "minecraft:totem" => remove("id"),
+                52 => remove("id")
+            })
+        }
+    });
+}
@@ -0,0 +1,13 @@
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+
+fn main() {
+    let mut types = Types::new();
+    let info = SchemaInfo::new(&mut types, 1451);
+    define_schema!(info, {
+        register chunk {
+            req("Level", opt("TileEntitites", list(refer(tile_entity))))
+        }
+    });
+}
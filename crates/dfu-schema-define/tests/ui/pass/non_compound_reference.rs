@@ -0,0 +1,34 @@
+use dfu_schema_define::define_schema;
+use dfu_structures::types::{convert, TypeReference, Types};
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{snbt, NbtTag};
+
+fn main() {
+    let mut types = Types::new();
+    let info = SchemaInfo::new(&mut types, 100);
+    define_schema!(info, {
+        register item_name {
+            match_value({
+                "minecraft:old" => custom(|value, _from, _to| {
+                    *value = NbtTag::String("minecraft:new".to_string());
+                    Ok(())
+                })
+            })
+        },
+        register item_stack {
+            opt("id", refer(item_name))
+        },
+        register player {
+            opt("Inventory", list(refer(item_stack)))
+        }
+    });
+
+    let mut name = NbtTag::String("minecraft:old".to_string());
+    convert(&types, TypeReference::ItemName, &mut name, 99, 100).unwrap();
+    assert_eq!(name, NbtTag::String("minecraft:new".to_string()));
+
+    let mut player = NbtTag::Compound(snbt::parse(r#"{Inventory: [{id: "minecraft:old"}, {id: "minecraft:stone"}]}"#).unwrap());
+    convert(&types, TypeReference::Player, &mut player, 99, 100).unwrap();
+    let expected = snbt::parse(r#"{Inventory: [{id: "minecraft:new"}, {id: "minecraft:stone"}]}"#).unwrap();
+    assert_eq!(player, NbtTag::Compound(expected));
+}
@@ -0,0 +1,36 @@
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+// Schemas record their keyed nodes here in test builds, so tests can find nodes which never match any data.
+// Nodes are named by their registered type followed by the keys leading to them, e.g. `chunk/Level/Entities`.
+
+static DECLARED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+static MATCHED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Records the nodes of a registration
+pub fn declare(nodes: &[&str]) {
+    let mut declared = DECLARED.lock().unwrap();
+    for node in nodes {
+        declared.insert(node.to_string());
+    }
+}
+
+/// Records that a node found its key in the converted data
+pub fn matched(node: &str) {
+    let mut matched = MATCHED.lock().unwrap();
+    if !matched.contains(node) {
+        matched.insert(node.to_string());
+    }
+}
+
+/// Every declared node which hasn't matched any data yet
+pub fn unmatched() -> Vec<String> {
+    let matched = MATCHED.lock().unwrap();
+    DECLARED
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|node| !matched.contains(*node))
+        .cloned()
+        .collect()
+}
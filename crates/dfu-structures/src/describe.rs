@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Serialize, Serializer};
+
+use crate::fixes::TagKind;
+use crate::types::{TypeReference, Types};
+
+// `define_schema!` describes every registration with these nodes, so what a registry walks and fixes can be inspected
+// at runtime, e.g. to find versions of a type nothing is registered for before trusting an upgrade.
+
+/// A node of a registration, as written in `define_schema!`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Node {
+    All(Vec<Node>),
+    Opt {
+        key: &'static str,
+        node: Box<Node>,
+    },
+    Req {
+        key: &'static str,
+        node: Box<Node>,
+    },
+    Refer(TypeReference),
+    List(Box<Node>),
+    MapValues(Box<Node>),
+    TaggedChoice {
+        key: &'static str,
+        arms: Vec<Arm>,
+        default: Option<Box<Node>>,
+    },
+    MatchValue {
+        arms: Vec<Arm>,
+        default: Option<Box<Node>>,
+    },
+    Rename {
+        old: &'static str,
+        new: &'static str,
+    },
+    Remove {
+        key: &'static str,
+    },
+    /// The default value as it was written, like `0i8`
+    Default {
+        key: &'static str,
+        value: &'static str,
+    },
+    ConvertType {
+        key: &'static str,
+        from: TagKind,
+        to: TagKind,
+    },
+    /// A closure, which can do anything
+    Custom,
+}
+
+/// An arm of a `tagged_choice` or `match_value`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Arm {
+    pub patterns: Vec<Pattern>,
+    pub node: Node,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Pattern {
+    String(&'static str),
+    Int(i64),
+}
+
+impl Serialize for TypeReference {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Every registration of a [`Types`] by type and version. Registrations without a description, which weren't made by
+/// `define_schema!`, are `None`.
+#[derive(Debug, Serialize)]
+pub struct Registry<'t> {
+    /// Every version any type is registered for
+    pub versions: BTreeSet<usize>,
+    pub types: BTreeMap<TypeReference, BTreeMap<usize, Option<&'t Node>>>,
+}
+
+impl Registry<'_> {
+    pub fn new(types: &Types) -> Registry<'_> {
+        let mut registry = Registry {
+            versions: BTreeSet::new(),
+            types: BTreeMap::new(),
+        };
+        for type_ref in TypeReference::ALL {
+            let registrations = types
+                .get(type_ref)
+                .keys()
+                .map(|version| (*version, types.description(type_ref, *version)))
+                .collect::<BTreeMap<_, _>>();
+            registry.versions.extend(registrations.keys());
+            registry.types.insert(type_ref, registrations);
+        }
+        registry
+    }
+
+    /// The registry as pretty printed JSON, with types named like in `define_schema!`
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("registries serialize to JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Arm, Node, Pattern, Registry};
+    use crate::types::{TypeReference, Types};
+
+    #[test]
+    fn registry_json() {
+        let mut types = Types::new();
+        types.get_mut(TypeReference::ItemStack).insert(102, Box::new(|_, _, _, _| Ok(())));
+        types.describe(
+            TypeReference::ItemStack,
+            102,
+            Node::All(vec![
+                Node::Opt {
+                    key: "tag",
+                    node: Box::new(Node::Refer(TypeReference::Entity)),
+                },
+                Node::TaggedChoice {
+                    key: "id",
+                    arms: vec![Arm {
+                        patterns: vec![Pattern::String("minecraft:potion")],
+                        node: Node::Custom,
+                    }],
+                    default: None,
+                },
+            ]),
+        );
+        // registered without define_schema!
+        types.get_mut(TypeReference::Chunk).insert(99, Box::new(|_, _, _, _| Ok(())));
+
+        let json: serde_json::Value = serde_json::from_str(&Registry::new(&types).to_json()).unwrap();
+        assert_eq!(json["versions"], json!([99, 102]));
+        assert_eq!(json["types"]["chunk"], json!({ "99": null }));
+        assert_eq!(json["types"]["level"], json!({}));
+        assert_eq!(
+            json["types"]["item_stack"]["102"],
+            json!({ "all": [
+                { "opt": { "key": "tag", "node": { "refer": "entity" } } },
+                { "tagged_choice": {
+                    "key": "id",
+                    "arms": [{ "patterns": ["minecraft:potion"], "node": "custom" }],
+                    "default": null
+                } }
+            ] })
+        );
+    }
+}
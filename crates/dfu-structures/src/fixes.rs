@@ -0,0 +1,142 @@
+use quartz_nbt::{NbtCompound, NbtTag};
+use serde::Serialize;
+
+// Fixes generated for the declarative nodes of `define_schema!`. Each returns whether it found its key.
+
+/// Tag types `convert_type` converts between
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagKind {
+    Byte,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    String,
+}
+
+enum Scalar {
+    Integer(i64),
+    Decimal(f64),
+    Text(String),
+}
+
+/// Moves the value at `old` to `new`, replacing any value at `new`
+pub fn rename(compound: &mut NbtCompound, old: &str, new: &str) -> bool {
+    match compound.inner_mut().remove(old) {
+        Some(value) => {
+            compound.insert(new, value);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn remove(compound: &mut NbtCompound, key: &str) -> bool {
+    compound.inner_mut().remove(key).is_some()
+}
+
+/// Inserts `value` at `key` if there is nothing there yet
+pub fn default(compound: &mut NbtCompound, key: &str, value: impl Into<NbtTag>) -> bool {
+    if compound.contains_key(key) {
+        return true;
+    }
+    compound.insert(key, value);
+    false
+}
+
+/// Converts the value at `key` if it is of type `from`. Values of other types, and strings which don't parse as the
+/// target number type, are left as they are.
+pub fn convert_type(compound: &mut NbtCompound, key: &str, from: TagKind, to: TagKind) -> bool {
+    let value = match compound.inner_mut().get_mut(key) {
+        Some(value) => value,
+        None => return false,
+    };
+    if let Some(converted) = convert(value, from, to) {
+        *value = converted;
+    }
+    true
+}
+
+fn convert(tag: &NbtTag, from: TagKind, to: TagKind) -> Option<NbtTag> {
+    let scalar = match (from, tag) {
+        (TagKind::Byte, NbtTag::Byte(value)) => Scalar::Integer(*value as i64),
+        (TagKind::Short, NbtTag::Short(value)) => Scalar::Integer(*value as i64),
+        (TagKind::Int, NbtTag::Int(value)) => Scalar::Integer(*value as i64),
+        (TagKind::Long, NbtTag::Long(value)) => Scalar::Integer(*value),
+        (TagKind::Float, NbtTag::Float(value)) => Scalar::Decimal(*value as f64),
+        (TagKind::Double, NbtTag::Double(value)) => Scalar::Decimal(*value),
+        (TagKind::String, NbtTag::String(value)) => Scalar::Text(value.clone()),
+        _ => return None,
+    };
+    Some(match (to, scalar) {
+        (TagKind::String, Scalar::Integer(value)) => NbtTag::String(value.to_string()),
+        (TagKind::String, Scalar::Decimal(value)) => NbtTag::String(value.to_string()),
+        (TagKind::String, Scalar::Text(value)) => NbtTag::String(value),
+        (to, Scalar::Text(value)) => match to {
+            TagKind::Float | TagKind::Double => number(to, Scalar::Decimal(value.trim().parse().ok()?)),
+            _ => number(to, Scalar::Integer(value.trim().parse().ok()?)),
+        },
+        (to, scalar) => number(to, scalar),
+    })
+}
+
+/// Casts a number the way the game's number tags cast between each other
+fn number(to: TagKind, scalar: Scalar) -> NbtTag {
+    let (integer, decimal) = match scalar {
+        Scalar::Integer(value) => (value, value as f64),
+        Scalar::Decimal(value) => (value as i64, value),
+        Scalar::Text(_) => unreachable!("text is parsed before casting"),
+    };
+    match to {
+        TagKind::Byte => NbtTag::Byte(integer as i8),
+        TagKind::Short => NbtTag::Short(integer as i16),
+        TagKind::Int => NbtTag::Int(integer as i32),
+        TagKind::Long => NbtTag::Long(integer),
+        TagKind::Float => NbtTag::Float(decimal as f32),
+        TagKind::Double => NbtTag::Double(decimal),
+        TagKind::String => unreachable!("strings are converted before casting"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert_type, default, rename, TagKind};
+    use quartz_nbt::{NbtCompound, NbtTag};
+
+    #[test]
+    fn rename_and_default() {
+        let mut compound = NbtCompound::new();
+        compound.insert("Old", 1);
+        assert!(rename(&mut compound, "Old", "New"));
+        assert!(!rename(&mut compound, "Old", "New"));
+        assert_eq!(compound.get::<_, i32>("New").unwrap(), 1);
+        assert!(!compound.contains_key("Old"));
+
+        assert!(!default(&mut compound, "Color", 10i8));
+        assert!(default(&mut compound, "Color", 3i8));
+        assert_eq!(compound.get::<_, i8>("Color").unwrap(), 10);
+    }
+
+    #[test]
+    fn convert_types() {
+        let mut compound = NbtCompound::new();
+        compound.insert("id", 52);
+        compound.insert("count", "12");
+        compound.insert("name", "twelve");
+        compound.insert("scale", 1.5f32);
+
+        convert_type(&mut compound, "id", TagKind::Int, TagKind::String);
+        convert_type(&mut compound, "count", TagKind::String, TagKind::Byte);
+        convert_type(&mut compound, "name", TagKind::String, TagKind::Int);
+        convert_type(&mut compound, "scale", TagKind::Float, TagKind::Int);
+        // only values of the `from` type are converted
+        convert_type(&mut compound, "id", TagKind::Long, TagKind::Short);
+
+        assert_eq!(compound.get::<_, &NbtTag>("id").unwrap(), &NbtTag::String("52".to_string()));
+        assert_eq!(compound.get::<_, &NbtTag>("count").unwrap(), &NbtTag::Byte(12));
+        assert_eq!(compound.get::<_, &NbtTag>("name").unwrap(), &NbtTag::String("twelve".to_string()));
+        assert_eq!(compound.get::<_, &NbtTag>("scale").unwrap(), &NbtTag::Int(1));
+    }
+}
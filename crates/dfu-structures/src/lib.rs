@@ -1,7 +1,10 @@
-use crate::types::{ConversionFunc, Types};
+use crate::types::Types;
 use quartz_nbt::NbtTag;
 use std::error::Error;
 
+pub mod coverage;
+pub mod describe;
+pub mod fixes;
 pub mod types;
 
 pub fn call_closure_with<'a, T, F: Fn(&'a mut NbtTag, usize, usize) -> Result<T, Box<dyn Error>>>(
@@ -14,12 +17,12 @@ pub fn call_closure_with<'a, T, F: Fn(&'a mut NbtTag, usize, usize) -> Result<T,
 }
 
 pub struct SchemaInfo<'d> {
-    pub references: &'d mut Types<&'static ConversionFunc>,
+    pub references: &'d mut Types,
     pub version: usize,
 }
 
 impl<'d> SchemaInfo<'d> {
-    pub fn new(references: &'d mut Types<&'static ConversionFunc>, version: usize) -> Self {
+    pub fn new(references: &'d mut Types, version: usize) -> Self {
         Self { references, version }
     }
 }
@@ -1,38 +1,139 @@
-use quartz_nbt::{NbtCompound, NbtTag};
+use quartz_nbt::NbtTag;
 use std::collections::HashMap;
 use std::error::Error;
 
-pub type ConversionFunc = dyn Fn(&mut NbtTag, usize, usize) -> Result<(), Box<dyn Error>> + Sync;
-
-pub struct Types<F> {
-    pub level: HashMap<usize, F>,
-    pub player: HashMap<usize, F>,
-    pub chunk: HashMap<usize, F>,
-    pub hotbar: HashMap<usize, F>,
-    pub options: HashMap<usize, F>,
-    pub structure: HashMap<usize, F>,
-    pub stats: HashMap<usize, F>,
-    pub saved_data: HashMap<usize, F>,
-    pub advancements: HashMap<usize, F>,
-    pub poi_chunk: HashMap<usize, F>,
-    pub entity_chunk: HashMap<usize, F>,
-    pub tile_entity: HashMap<usize, F>,
-    pub item_stack: HashMap<usize, F>,
-    pub block_state: HashMap<usize, F>,
-    pub entity_name: HashMap<usize, F>,
-    pub entity: HashMap<usize, F>,
-    pub block_name: HashMap<usize, F>,
-    pub item_name: HashMap<usize, F>,
-    pub untagged_spawner: HashMap<usize, F>,
-    pub structure_feature: HashMap<usize, F>,
-    pub objective: HashMap<usize, F>,
-    pub team: HashMap<usize, F>,
-    pub recipe: HashMap<usize, F>,
-    pub biome: HashMap<usize, F>,
-    pub world_gen_settings: HashMap<usize, F>,
+use crate::describe::Node;
+
+/// Upgrades one registered version of a type. It gets the registry it is part of, through which it upgrades the types it
+/// references.
+pub type ConversionFunc = dyn Fn(&Types, &mut NbtTag, usize, usize) -> Result<(), Box<dyn Error>> + Send + Sync;
+
+/// A kind of data registered in [`Types`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TypeReference {
+    Level,
+    Player,
+    Chunk,
+    Hotbar,
+    Options,
+    Structure,
+    Stats,
+    SavedData,
+    Advancements,
+    PoiChunk,
+    EntityChunk,
+    TileEntity,
+    ItemStack,
+    BlockState,
+    EntityName,
+    Entity,
+    BlockName,
+    ItemName,
+    UntaggedSpawner,
+    StructureFeature,
+    Objective,
+    Team,
+    Recipe,
+    Biome,
+    WorldGenSettings,
+}
+
+impl TypeReference {
+    pub const ALL: [TypeReference; 25] = [
+        Self::Level,
+        Self::Player,
+        Self::Chunk,
+        Self::Hotbar,
+        Self::Options,
+        Self::Structure,
+        Self::Stats,
+        Self::SavedData,
+        Self::Advancements,
+        Self::PoiChunk,
+        Self::EntityChunk,
+        Self::TileEntity,
+        Self::ItemStack,
+        Self::BlockState,
+        Self::EntityName,
+        Self::Entity,
+        Self::BlockName,
+        Self::ItemName,
+        Self::UntaggedSpawner,
+        Self::StructureFeature,
+        Self::Objective,
+        Self::Team,
+        Self::Recipe,
+        Self::Biome,
+        Self::WorldGenSettings,
+    ];
+
+    /// The name of the type's field in [`Types`], which is also how `define_schema!` refers to it
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Level => "level",
+            Self::Player => "player",
+            Self::Chunk => "chunk",
+            Self::Hotbar => "hotbar",
+            Self::Options => "options",
+            Self::Structure => "structure",
+            Self::Stats => "stats",
+            Self::SavedData => "saved_data",
+            Self::Advancements => "advancements",
+            Self::PoiChunk => "poi_chunk",
+            Self::EntityChunk => "entity_chunk",
+            Self::TileEntity => "tile_entity",
+            Self::ItemStack => "item_stack",
+            Self::BlockState => "block_state",
+            Self::EntityName => "entity_name",
+            Self::Entity => "entity",
+            Self::BlockName => "block_name",
+            Self::ItemName => "item_name",
+            Self::UntaggedSpawner => "untagged_spawner",
+            Self::StructureFeature => "structure_feature",
+            Self::Objective => "objective",
+            Self::Team => "team",
+            Self::Recipe => "recipe",
+            Self::Biome => "biome",
+            Self::WorldGenSettings => "world_gen_settings",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|type_ref| type_ref.name() == name)
+    }
+}
+
+/// The conversion functions of every type, by the version they are registered for
+pub struct Types {
+    pub level: HashMap<usize, Box<ConversionFunc>>,
+    pub player: HashMap<usize, Box<ConversionFunc>>,
+    pub chunk: HashMap<usize, Box<ConversionFunc>>,
+    pub hotbar: HashMap<usize, Box<ConversionFunc>>,
+    pub options: HashMap<usize, Box<ConversionFunc>>,
+    pub structure: HashMap<usize, Box<ConversionFunc>>,
+    pub stats: HashMap<usize, Box<ConversionFunc>>,
+    pub saved_data: HashMap<usize, Box<ConversionFunc>>,
+    pub advancements: HashMap<usize, Box<ConversionFunc>>,
+    pub poi_chunk: HashMap<usize, Box<ConversionFunc>>,
+    pub entity_chunk: HashMap<usize, Box<ConversionFunc>>,
+    pub tile_entity: HashMap<usize, Box<ConversionFunc>>,
+    pub item_stack: HashMap<usize, Box<ConversionFunc>>,
+    pub block_state: HashMap<usize, Box<ConversionFunc>>,
+    pub entity_name: HashMap<usize, Box<ConversionFunc>>,
+    pub entity: HashMap<usize, Box<ConversionFunc>>,
+    pub block_name: HashMap<usize, Box<ConversionFunc>>,
+    pub item_name: HashMap<usize, Box<ConversionFunc>>,
+    pub untagged_spawner: HashMap<usize, Box<ConversionFunc>>,
+    pub structure_feature: HashMap<usize, Box<ConversionFunc>>,
+    pub objective: HashMap<usize, Box<ConversionFunc>>,
+    pub team: HashMap<usize, Box<ConversionFunc>>,
+    pub recipe: HashMap<usize, Box<ConversionFunc>>,
+    pub biome: HashMap<usize, Box<ConversionFunc>>,
+    pub world_gen_settings: HashMap<usize, Box<ConversionFunc>>,
+    descriptions: HashMap<(TypeReference, usize), Node>,
 }
 
-impl<F> Types<F> {
+impl Types {
     pub fn new() -> Self {
         Self {
             level: HashMap::new(),
@@ -60,14 +161,180 @@ impl<F> Types<F> {
             recipe: HashMap::new(),
             biome: HashMap::new(),
             world_gen_settings: HashMap::new(),
+            descriptions: HashMap::new(),
         }
     }
+
+    pub fn get(&self, type_ref: TypeReference) -> &HashMap<usize, Box<ConversionFunc>> {
+        match type_ref {
+            TypeReference::Level => &self.level,
+            TypeReference::Player => &self.player,
+            TypeReference::Chunk => &self.chunk,
+            TypeReference::Hotbar => &self.hotbar,
+            TypeReference::Options => &self.options,
+            TypeReference::Structure => &self.structure,
+            TypeReference::Stats => &self.stats,
+            TypeReference::SavedData => &self.saved_data,
+            TypeReference::Advancements => &self.advancements,
+            TypeReference::PoiChunk => &self.poi_chunk,
+            TypeReference::EntityChunk => &self.entity_chunk,
+            TypeReference::TileEntity => &self.tile_entity,
+            TypeReference::ItemStack => &self.item_stack,
+            TypeReference::BlockState => &self.block_state,
+            TypeReference::EntityName => &self.entity_name,
+            TypeReference::Entity => &self.entity,
+            TypeReference::BlockName => &self.block_name,
+            TypeReference::ItemName => &self.item_name,
+            TypeReference::UntaggedSpawner => &self.untagged_spawner,
+            TypeReference::StructureFeature => &self.structure_feature,
+            TypeReference::Objective => &self.objective,
+            TypeReference::Team => &self.team,
+            TypeReference::Recipe => &self.recipe,
+            TypeReference::Biome => &self.biome,
+            TypeReference::WorldGenSettings => &self.world_gen_settings,
+        }
+    }
+
+    pub fn get_mut(&mut self, type_ref: TypeReference) -> &mut HashMap<usize, Box<ConversionFunc>> {
+        match type_ref {
+            TypeReference::Level => &mut self.level,
+            TypeReference::Player => &mut self.player,
+            TypeReference::Chunk => &mut self.chunk,
+            TypeReference::Hotbar => &mut self.hotbar,
+            TypeReference::Options => &mut self.options,
+            TypeReference::Structure => &mut self.structure,
+            TypeReference::Stats => &mut self.stats,
+            TypeReference::SavedData => &mut self.saved_data,
+            TypeReference::Advancements => &mut self.advancements,
+            TypeReference::PoiChunk => &mut self.poi_chunk,
+            TypeReference::EntityChunk => &mut self.entity_chunk,
+            TypeReference::TileEntity => &mut self.tile_entity,
+            TypeReference::ItemStack => &mut self.item_stack,
+            TypeReference::BlockState => &mut self.block_state,
+            TypeReference::EntityName => &mut self.entity_name,
+            TypeReference::Entity => &mut self.entity,
+            TypeReference::BlockName => &mut self.block_name,
+            TypeReference::ItemName => &mut self.item_name,
+            TypeReference::UntaggedSpawner => &mut self.untagged_spawner,
+            TypeReference::StructureFeature => &mut self.structure_feature,
+            TypeReference::Objective => &mut self.objective,
+            TypeReference::Team => &mut self.team,
+            TypeReference::Recipe => &mut self.recipe,
+            TypeReference::Biome => &mut self.biome,
+            TypeReference::WorldGenSettings => &mut self.world_gen_settings,
+        }
+    }
+
+    /// Describes the function registered for `type_ref` at `version`. `define_schema!` does this for every function it
+    /// registers, functions inserted with [`Self::get_mut`] keep the description they had.
+    pub fn describe(&mut self, type_ref: TypeReference, version: usize, node: Node) {
+        self.descriptions.insert((type_ref, version), node);
+    }
+
+    pub fn description(&self, type_ref: TypeReference, version: usize) -> Option<&Node> {
+        self.descriptions.get(&(type_ref, version))
+    }
 }
 
-pub fn convert<F: Fn(&mut NbtTag, usize, usize) -> Result<(), Box<dyn Error>>>(
-    _t: &HashMap<usize, F>,
-    _data: &mut NbtCompound,
-    _from: usize,
-    _to: usize,
-) {
+impl Default for Types {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Upgrades `data` of type `type_ref` from version `from` to version `to`, using the functions registered in `types`.
+///
+/// Registered functions run in version order, each one over the part of `(from, to]` until the next registered version, starting
+/// with the newest one at or below `from` which describes the data as it is. They apply their own fixes when their version is within
+/// the range they are given, and pass that range on to the types they reference.
+pub fn convert(types: &Types, type_ref: TypeReference, data: &mut NbtTag, from: usize, to: usize) -> Result<(), Box<dyn Error>> {
+    if from >= to {
+        return Ok(());
+    }
+    let t = types.get(type_ref);
+    let mut versions: Vec<usize> = t.keys().copied().filter(|version| *version <= to).collect();
+    versions.sort_unstable();
+
+    let start = versions.iter().rposition(|version| *version <= from).unwrap_or(0);
+    let mut current = from;
+    for (idx, version) in versions.iter().enumerate().skip(start) {
+        let next = versions.get(idx + 1).map_or(to, |next| next - 1);
+        if next <= current {
+            continue;
+        }
+        t[version](types, data, current, next)?;
+        current = next;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use quartz_nbt::NbtTag;
+
+    use super::{convert, TypeReference, Types};
+
+    /// Returns the `(version, from, to)` of every registered function called
+    fn run(from: usize, to: usize) -> Vec<(usize, usize, usize)> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut types = Types::new();
+        for version in [99, 102, 510] {
+            let calls = calls.clone();
+            types.get_mut(TypeReference::Chunk).insert(
+                version,
+                Box::new(move |_, _, from, to| {
+                    calls.lock().unwrap().push((version, from, to));
+                    Ok(())
+                }),
+            );
+        }
+        convert(&types, TypeReference::Chunk, &mut NbtTag::Int(0), from, to).unwrap();
+        let calls = calls.lock().unwrap().clone();
+        calls
+    }
+
+    #[test]
+    fn versions_in_order_over_their_ranges() {
+        assert_eq!(run(99, 1343), vec![(99, 99, 101), (102, 101, 509), (510, 509, 1343)]);
+        assert_eq!(run(100, 102), vec![(99, 100, 101), (102, 101, 102)]);
+        assert_eq!(run(50, 101), vec![(99, 50, 101)]);
+        assert_eq!(run(101, 101), vec![]);
+        assert_eq!(run(600, 1343), vec![(510, 600, 1343)]);
+    }
+
+    #[test]
+    fn references_resolve_in_their_own_registry() {
+        let registry = |name: &'static str| {
+            let mut types = Types::new();
+            types.get_mut(TypeReference::ItemName).insert(
+                100,
+                Box::new(move |_, value, _, _| {
+                    *value = NbtTag::String(name.to_string());
+                    Ok(())
+                }),
+            );
+            types.get_mut(TypeReference::ItemStack).insert(
+                100,
+                Box::new(|types, value, from, to| convert(types, TypeReference::ItemName, value, from, to)),
+            );
+            types
+        };
+        let (vanilla, modded) = (registry("minecraft:stone"), registry("mod:stone"));
+        for (types, expected) in [(&vanilla, "minecraft:stone"), (&modded, "mod:stone")] {
+            let mut value = NbtTag::Int(1);
+            convert(types, TypeReference::ItemStack, &mut value, 99, 100).unwrap();
+            assert_eq!(value, NbtTag::String(expected.to_string()));
+        }
+    }
+
+    #[test]
+    fn type_names_round_trip() {
+        for type_ref in TypeReference::ALL {
+            assert_eq!(TypeReference::from_name(type_ref.name()), Some(type_ref));
+        }
+        assert_eq!(TypeReference::ItemStack.name(), "item_stack");
+        assert_eq!(TypeReference::from_name("item_stak"), None);
+    }
 }
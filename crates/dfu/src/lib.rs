@@ -1,21 +1,246 @@
-#![feature(const_for)]
-
 mod schema;
 mod util;
 
-use crate::schema::v99;
+use std::error::Error;
+use std::sync::Arc;
+
+pub use dfu_structures::describe::{Node, Registry};
+pub use dfu_structures::types::TypeReference;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtCompound, NbtTag};
+pub use util::v102_item_name::ModdedItemIds;
+
+/// The target everything the fixer logs is logged under
+pub const LOG_TARGET: &str = "dfu";
+
+/// Upgrades game data between data versions. Each fixer owns its conversion functions, so fixers with different schemas
+/// can exist side by side.
+pub struct DataFixer {
+    types: Types,
+}
+
+impl DataFixer {
+    /// A fixer with every schema of the game
+    pub fn new() -> Self {
+        let mut types = Types::new();
+        schema::define_all(&mut types);
+        Self { types }
+    }
+
+    /// A fixer for a modded world, which converts numeric item ids saved before 1.8 with the world's own ids
+    pub fn with_modded_item_ids(ids: ModdedItemIds) -> Self {
+        let mut fixer = Self::new();
+        schema::v102::define_with_item_ids(&mut fixer.types, Arc::new(ids));
+        fixer
+    }
+
+    /// Registers more of the fixer's conversion functions with `define_schema!`, e.g. for mods. Registering a type at a
+    /// version it is already registered for replaces its function.
+    pub fn schema(&mut self, version: usize) -> SchemaInfo<'_> {
+        SchemaInfo::new(&mut self.types, version)
+    }
 
-use dfu_structures::types::{ConversionFunc, Types};
-use lazy_static::lazy_static;
+    /// The newest version any type is registered for
+    pub fn latest_version(&self) -> usize {
+        TypeReference::ALL
+            .iter()
+            .filter_map(|type_ref| self.types.get(*type_ref).keys().max())
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
 
-fn create_types() -> &'static Types<&'static ConversionFunc> {
-    let mut types = Types::new();
+    /// Every type and version the fixer has a function for, with what it walks and fixes. [`Registry::to_json`] dumps it
+    /// for auditing.
+    pub fn describe(&self) -> Registry<'_> {
+        Registry::new(&self.types)
+    }
 
-    v99::define(&mut types);
+    /// Upgrades `data` of type `type_ref` from `from_version` to `to_version`. On error, `data` may be partially upgraded.
+    pub fn update(
+        &self,
+        type_ref: TypeReference,
+        data: &mut NbtCompound,
+        from_version: usize,
+        to_version: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut tag = NbtTag::Compound(std::mem::replace(data, NbtCompound::new()));
+        let result = self.update_tag(type_ref, &mut tag, from_version, to_version);
+        if let NbtTag::Compound(compound) = tag {
+            *data = compound;
+        }
+        result
+    }
 
-    Box::leak(Box::new(types))
+    /// Like [`Self::update`], for types which aren't always compounds such as item or block names
+    pub fn update_tag(
+        &self,
+        type_ref: TypeReference,
+        data: &mut NbtTag,
+        from_version: usize,
+        to_version: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        dfu_structures::types::convert(&self.types, type_ref, data, from_version, to_version)
+    }
 }
 
-lazy_static! {
-    pub static ref TYPES: &'static Types<&'static ConversionFunc> = create_types();
+impl Default for DataFixer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dfu_schema_define::define_schema;
+    use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+    use crate::{DataFixer, ModdedItemIds, Node, TypeReference};
+
+    fn item(id: i16, damage: i16) -> NbtCompound {
+        let mut item = NbtCompound::new();
+        item.insert("id", id);
+        item.insert("Count", 1i8);
+        item.insert("Damage", damage);
+        item
+    }
+
+    #[test]
+    fn v99_chunk_item_stacks_upgrade_through_v102() {
+        let mut item_entity = NbtCompound::new();
+        item_entity.insert("id", "Item");
+        item_entity.insert("Item", item(1, 0));
+        let mut minecart = NbtCompound::new();
+        minecart.insert("id", "MinecartChest");
+        minecart.insert("Items", NbtList::from(vec![item(373, 16389), item(264, 0)]));
+        minecart.insert("Riding", item_entity.clone());
+
+        let mut level = NbtCompound::new();
+        level.insert("Entities", NbtList::from(vec![item_entity, minecart]));
+        let mut chunk = NbtCompound::new();
+        chunk.insert("Level", level);
+
+        DataFixer::new().update(TypeReference::Chunk, &mut chunk, 99, 102).unwrap();
+
+        let level: &NbtCompound = chunk.get("Level").unwrap();
+        let entities: &NbtList = level.get("Entities").unwrap();
+        let item_entity: &NbtCompound = entities.get(0).unwrap();
+        let stone: &NbtCompound = item_entity.get("Item").unwrap();
+        assert_eq!(stone.get::<_, &str>("id").unwrap(), "minecraft:stone");
+
+        let minecart: &NbtCompound = entities.get(1).unwrap();
+        let items: &NbtList = minecart.get("Items").unwrap();
+        let potion: &NbtCompound = items.get(0).unwrap();
+        assert_eq!(potion.get::<_, &str>("id").unwrap(), "minecraft:splash_potion");
+        assert_eq!(potion.get::<_, i16>("Damage").unwrap(), 0);
+        let tag: &NbtCompound = potion.get("tag").unwrap();
+        assert_eq!(tag.get::<_, &str>("Potion").unwrap(), "minecraft:healing");
+        let diamond: &NbtCompound = items.get(1).unwrap();
+        assert_eq!(diamond.get::<_, &str>("id").unwrap(), "minecraft:diamond");
+
+        let rider: &NbtCompound = minecart.get("Riding").unwrap();
+        let rider_item: &NbtCompound = rider.get("Item").unwrap();
+        assert_eq!(rider_item.get::<_, &str>("id").unwrap(), "minecraft:stone");
+    }
+
+    #[test]
+    fn already_upgraded_data_is_untouched() {
+        let mut stack = item(1, 0);
+        DataFixer::new().update(TypeReference::ItemStack, &mut stack, 102, 510).unwrap();
+        assert_eq!(stack.get::<_, i16>("id").unwrap(), 1);
+    }
+
+    #[test]
+    fn update_player_and_item_names() {
+        let fixer = DataFixer::new();
+        assert_eq!(fixer.latest_version(), 1451);
+
+        let mut player = NbtCompound::new();
+        player.insert("Inventory", NbtList::from(vec![item(264, 0)]));
+        player.insert("EnderItems", NbtList::from(vec![item(1, 0)]));
+        fixer
+            .update(TypeReference::Player, &mut player, 99, fixer.latest_version())
+            .unwrap();
+        let inventory: &NbtList = player.get("Inventory").unwrap();
+        assert_eq!(
+            inventory.get::<&NbtCompound>(0).unwrap().get::<_, &str>("id").unwrap(),
+            "minecraft:diamond"
+        );
+        let ender_items: &NbtList = player.get("EnderItems").unwrap();
+        assert_eq!(
+            ender_items.get::<&NbtCompound>(0).unwrap().get::<_, &str>("id").unwrap(),
+            "minecraft:stone"
+        );
+
+        let mut name = NbtTag::Int(264);
+        fixer.update_tag(TypeReference::ItemName, &mut name, 99, 102).unwrap();
+        assert_eq!(name, NbtTag::String("minecraft:diamond".to_string()));
+    }
+
+    #[test]
+    fn customised_fixers_are_independent() {
+        let vanilla = DataFixer::new();
+        let mut modded = DataFixer::new();
+        let info = modded.schema(1452);
+        define_schema!(info, {
+            register item_name {
+                custom(|value, _from, _to| {
+                    if *value == NbtTag::String("minecraft:stone".to_string()) {
+                        *value = NbtTag::String("mod:stone".to_string());
+                    }
+                    Ok(())
+                })
+            }
+        });
+        assert_eq!(modded.latest_version(), 1452);
+
+        let mut name = NbtTag::String("minecraft:stone".to_string());
+        modded.update_tag(TypeReference::ItemName, &mut name, 1451, 1452).unwrap();
+        assert_eq!(name, NbtTag::String("mod:stone".to_string()));
+        drop(modded);
+
+        let mut name = NbtTag::String("minecraft:stone".to_string());
+        vanilla.update_tag(TypeReference::ItemName, &mut name, 1451, 1452).unwrap();
+        assert_eq!(name, NbtTag::String("minecraft:stone".to_string()));
+    }
+
+    #[test]
+    fn modded_item_ids() {
+        let mut ids = ModdedItemIds::new();
+        ids.insert(4100, "ic2:wrench");
+        let modded = DataFixer::with_modded_item_ids(ids);
+
+        let mut stack = item(4100, 0);
+        modded.update(TypeReference::ItemStack, &mut stack, 99, 102).unwrap();
+        assert_eq!(stack.get::<_, &str>("id").unwrap(), "ic2:wrench");
+        let mut name = NbtTag::Short(264);
+        modded.update_tag(TypeReference::ItemName, &mut name, 99, 102).unwrap();
+        assert_eq!(name, NbtTag::String("minecraft:diamond".to_string()));
+
+        let mut stack = item(4100, 0);
+        DataFixer::new().update(TypeReference::ItemStack, &mut stack, 99, 102).unwrap();
+        assert_eq!(stack.get::<_, &str>("id").unwrap(), "minecraft:air");
+    }
+
+    #[test]
+    fn describe_registry() {
+        let fixer = DataFixer::new();
+        let registry = fixer.describe();
+        assert_eq!(registry.versions.last(), Some(&1451));
+        // every schema is defined with define_schema!, which describes what it registers
+        for (type_ref, registrations) in &registry.types {
+            for (version, description) in registrations {
+                assert!(description.is_some(), "{} at {version} has no description", type_ref.name());
+            }
+        }
+
+        let item_stack = registry.types[&TypeReference::ItemStack][&102].unwrap();
+        let Node::All(nodes) = item_stack else {
+            panic!("registrations describe all of their nodes");
+        };
+        assert_eq!(nodes.len(), 3);
+        assert!(matches!(nodes[1], Node::TaggedChoice { key: "id", .. }));
+        assert!(registry.to_json().contains(r#""refer": "entity""#));
+    }
 }
@@ -1,2 +1,169 @@
+use dfu_structures::types::Types;
+
+pub(crate) mod v100;
+pub(crate) mod v101;
 pub(crate) mod v102;
+pub(crate) mod v1022;
+pub(crate) mod v105;
+pub(crate) mod v106;
+pub(crate) mod v107;
+pub(crate) mod v108;
+pub(crate) mod v109;
+pub(crate) mod v110;
+pub(crate) mod v111;
+pub(crate) mod v1125;
+pub(crate) mod v113;
+pub(crate) mod v135;
+pub(crate) mod v143;
+pub(crate) mod v1451;
+pub(crate) mod v147;
+pub(crate) mod v165;
+pub(crate) mod v502;
+pub(crate) mod v505;
+pub(crate) mod v700;
+pub(crate) mod v701;
+pub(crate) mod v702;
+pub(crate) mod v703;
+pub(crate) mod v704;
+pub(crate) mod v705;
+pub(crate) mod v804;
+pub(crate) mod v806;
+pub(crate) mod v808;
+pub(crate) mod v813;
+pub(crate) mod v816;
+pub(crate) mod v820;
 pub(crate) mod v99;
+
+/// Every schema, in version order
+const SCHEMAS: [fn(&mut Types); 33] = [
+    v99::define,
+    v100::define,
+    v101::define,
+    v102::define,
+    v105::define,
+    v106::define,
+    v107::define,
+    v108::define,
+    v109::define,
+    v110::define,
+    v111::define,
+    v113::define,
+    v135::define,
+    v143::define,
+    v147::define,
+    v165::define,
+    v502::define,
+    v505::define,
+    v700::define,
+    v701::define,
+    v702::define,
+    v703::define,
+    v704::define,
+    v705::define,
+    v804::define,
+    v806::define,
+    v808::define,
+    v813::define,
+    v816::define,
+    v820::define,
+    v1022::define,
+    v1125::define,
+    v1451::define,
+];
+
+pub(crate) fn define_all(types: &mut Types) {
+    for define in SCHEMAS {
+        define(types);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{self, File},
+        path::Path,
+    };
+
+    use dfu_structures::coverage;
+    use quartz_nbt::{
+        io::{self, Flavor},
+        snbt, NbtCompound, NbtList, NbtTag,
+    };
+
+    use crate::{DataFixer, TypeReference};
+
+    fn test_resources(dir: &str) -> Vec<(String, std::path::PathBuf)> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/test").join(dir);
+        let entries = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                (path.file_name().unwrap().to_string_lossy().to_string(), path)
+            })
+            .collect::<Vec<_>>();
+        assert!(!entries.is_empty(), "no test resources found");
+        entries
+    }
+
+    /// Each fixture holds an `input` of `type`, which upgraded from version `from` to version `to` must equal `expected`
+    fn run_fixtures(fixer: &DataFixer) {
+        for (name, path) in test_resources("fixtures") {
+            let fixture = snbt::parse(&fs::read_to_string(&path).unwrap()).unwrap_or_else(|err| panic!("{name}: {err}"));
+
+            let type_name: &str = fixture.get("type").unwrap();
+            let type_ref = *TypeReference::ALL
+                .iter()
+                .find(|type_ref| format!("{type_ref:?}") == type_name)
+                .unwrap_or_else(|| panic!("{name}: unknown type {type_name}"));
+            let from: i32 = fixture.get("from").unwrap();
+            let to: i32 = fixture.get("to").unwrap();
+            let mut data: NbtTag = fixture.get::<_, &NbtTag>("input").unwrap().clone();
+            let expected: &NbtTag = fixture.get("expected").unwrap();
+
+            fixer
+                .update_tag(type_ref, &mut data, from as usize, to as usize)
+                .unwrap_or_else(|err| panic!("{name}: {err}"));
+            assert_eq!(&data, expected, "{name}");
+        }
+    }
+
+    /// Samples are chunks saved by the game, which have to upgrade to the latest version
+    fn run_samples(fixer: &DataFixer) {
+        for (name, path) in test_resources("samples") {
+            let (chunk, _) = io::read_nbt(&mut File::open(&path).unwrap(), Flavor::ZlibCompressed).unwrap();
+            let from = chunk.get::<_, i32>("DataVersion").unwrap() as usize;
+            let mut data = NbtTag::Compound(chunk);
+
+            fixer
+                .update_tag(TypeReference::Chunk, &mut data, from, fixer.latest_version())
+                .unwrap_or_else(|err| panic!("{name}: {err}"));
+
+            let chunk: NbtCompound = data.try_into().unwrap();
+            let level = chunk.get::<_, &NbtCompound>("Level").unwrap();
+            for section in level.get::<_, &NbtList>("Sections").unwrap().iter() {
+                let section: &NbtCompound = section.try_into().unwrap();
+                assert!(section.contains_key("Palette") && !section.contains_key("Blocks"), "{name}");
+            }
+        }
+    }
+
+    #[test]
+    fn fixtures() {
+        run_fixtures(&DataFixer::new());
+    }
+
+    #[test]
+    fn samples() {
+        run_samples(&DataFixer::new());
+    }
+
+    /// Every keyed node of every schema has to find its key in some fixture or sample, a node which never matches
+    /// is either misspelled or doesn't fit the data it is meant for
+    #[test]
+    fn every_node_matches_data() {
+        let fixer = DataFixer::new();
+        run_fixtures(&fixer);
+        run_samples(&fixer);
+        assert_eq!(coverage::unmatched(), Vec::<String>::new());
+    }
+}
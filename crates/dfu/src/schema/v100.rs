@@ -0,0 +1,60 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 100,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            custom(|value, _from, _to| {
+                if let Some(entity) = fix::compound(value) {
+                    split_equipment(entity);
+                }
+                Ok(())
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Riding", refer(entity)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Splits `Equipment` (held item, then armor from the feet up) into `HandItems` and `ArmorItems`, along with their drop chances
+fn split_equipment(entity: &mut NbtCompound) {
+    if let Some(NbtTag::List(equipment)) = fix::remove(entity, "Equipment") {
+        let item = |idx: usize| equipment.get::<&NbtTag>(idx).cloned().unwrap_or_else(|_| NbtCompound::new().into());
+        if !equipment.is_empty() && !entity.contains_key("HandItems") {
+            entity.insert("HandItems", NbtList::from(vec![item(0), NbtCompound::new().into()]));
+        }
+        if equipment.len() > 1 && !entity.contains_key("ArmorItems") {
+            entity.insert("ArmorItems", NbtList::from((1..5).map(item).collect::<Vec<_>>()));
+        }
+    }
+
+    if let Some(NbtTag::List(chances)) = fix::remove(entity, "DropChances") {
+        let chance = |idx: usize| chances.get::<f32>(idx).unwrap_or(0.0);
+        if !entity.contains_key("HandDropChances") {
+            entity.insert("HandDropChances", NbtList::from(vec![chance(0), 0.0]));
+        }
+        if !entity.contains_key("ArmorDropChances") {
+            entity.insert("ArmorDropChances", NbtList::from((1..5).map(chance).collect::<Vec<_>>()));
+        }
+    }
+}
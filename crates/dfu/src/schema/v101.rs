@@ -0,0 +1,31 @@
+use crate::util::fix;
+use crate::util::v101_text_component::strict_json;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 101,
+        references: types,
+    };
+    define_schema!(info, {
+        register tile_entity {
+            tagged_choice("id", {
+                "Sign" => custom(|value, _from, _to| {
+                    if let Some(sign) = fix::compound(value) {
+                        for key in ["Text1", "Text2", "Text3", "Text4"] {
+                            let text = sign.get::<_, &str>(key).unwrap_or("");
+                            sign.insert(key, strict_json(text));
+                        }
+                    }
+                    Ok(())
+                })
+            }),
+            opt("Items", list(refer(item_stack))),
+            opt("RecordItem", refer(item_stack)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
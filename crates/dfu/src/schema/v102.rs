@@ -1,10 +1,19 @@
-use crate::util::v102_item_name;
+use std::sync::Arc;
+
+use crate::util::fix;
+use crate::util::v102_item_name::{item_name, legacy_potion, ModdedItemIds};
 use dfu_schema_define::define_schema;
-use dfu_structures::types::{ConversionFunc, Types};
+use dfu_structures::types::Types;
 use dfu_structures::SchemaInfo;
 use quartz_nbt::{NbtCompound, NbtTag};
 
-pub fn define(types: &mut Types<&'static ConversionFunc>) {
+pub fn define(types: &mut Types) {
+    define_with_item_ids(types, Arc::new(ModdedItemIds::new()));
+}
+
+/// Registers the schema looking up numeric item ids in `modded_ids` before the game's own
+pub(crate) fn define_with_item_ids(types: &mut Types, modded_ids: Arc<ModdedItemIds>) {
+    let stack_ids = modded_ids.clone();
     let info = SchemaInfo {
         version: 102,
         references: types,
@@ -12,66 +21,58 @@ pub fn define(types: &mut Types<&'static ConversionFunc>) {
     define_schema!(info, {
         register item_name {
             custom(|value, _from, _to| {
-                let id = match value {
-                    NbtTag::Int(val) => val,
-                    _ => return Ok(None)
-                };
-                if let Some(name) = v102_item_name::ITEM_NAMES.get(id) {
-                    Ok(Some(name))
-                } else {
-                    println!("(v102) Unknown legacy id {}", id);
-                    return Ok(Some(v102_item_name::ITEM_NAMES.get(&0).unwrap()));
+                if let Some(id) = numeric_id(value) {
+                    *value = NbtTag::String(item_name(id, &modded_ids).to_string());
                 }
+                Ok(())
             })
         },
         register item_stack {
             custom(|value, _from, _to| {
                 let compound = match value {
                     NbtTag::Compound(compound) => compound,
-                    _ => return Ok(None::<()>)
-                };
-                let id: i32 = compound.get("id")?;
-
-                let name = if let Some(name) = v102_item_name::ITEM_NAMES.get(&id) {
-                    name
-                } else {
-                    println!("(v102) Unknown legacy id {}", id);
-                    v102_item_name::ITEM_NAMES.get(&0).unwrap()
+                    _ => return Ok(()),
                 };
-                compound.insert("id", NbtTag::String(name.to_string()));
-
-                Ok(None)
-            })
-        },
-        register item_stack { // FIXME: register only for potion items, not all items
-            custom(|value, _from, _to| {
-                let compound = match value {
-                    NbtTag::Compound(compound) => compound,
-                    _ => return Ok(None::<()>)
-                };
-                let damage: i16 = compound.get("Damage")?;
-                if damage != 0 {
-                    compound.insert("Damage", NbtTag::Short(damage));
+                if let Some(id) = compound.inner().get("id").and_then(numeric_id) {
+                    compound.insert("id", NbtTag::String(item_name(id, &stack_ids).to_string()));
                 }
+                Ok(())
+            }),
+            tagged_choice("id", {
+                "minecraft:potion" => custom(|value, _from, _to| {
+                    let compound: &mut NbtCompound = value.try_into()?;
+                    let damage = fix::get_int(compound, "Damage").unwrap_or(0);
+                    if damage != 0 {
+                        compound.insert("Damage", NbtTag::Short(0));
+                    }
 
-                let tag: Option<&mut NbtTag> = compound.inner_mut().get_mut("tag");
-                let tag_compound: &mut NbtCompound;
-                if tag.is_none() {
-                    compound.insert("tag", NbtCompound::new());
-                    tag_compound = compound.get_mut::<_, &mut NbtCompound>("tag")?;
-                } else {
-                    tag_compound = tag.unwrap().try_into()?
-                };
-
-                if tag_compound.get::<_, &String>("Potion").is_err() {
-                    let name = v102_item_name::POTION_NAMES[damage as usize];
-                    tag_compound.insert("Potion", name.unwrap_or("minecraft:water"));
-                    if damage & 16384 == 16384 {
-                        compound.insert("id", "minecraft:splash_potion");
+                    if !compound.contains_key("tag") {
+                        compound.insert("tag", NbtCompound::new());
                     }
-                }
-                Ok(None)
-            })
+                    let tag_compound = compound.get_mut::<_, &mut NbtCompound>("tag")?;
+                    if tag_compound.get::<_, &String>("Potion").is_err() {
+                        let potion = legacy_potion(damage);
+                        tag_compound.insert("Potion", potion.name);
+                        if potion.splash {
+                            compound.insert("id", "minecraft:splash_potion");
+                        }
+                    }
+                    Ok(())
+                })
+            }),
+            opt("tag", all(
+                opt("EntityTag", refer(entity)),
+                opt("BlockEntityTag", refer(tile_entity))
+            ))
         }
     });
 }
+
+fn numeric_id(tag: &NbtTag) -> Option<i32> {
+    match tag {
+        NbtTag::Byte(id) => Some(*id as i32),
+        NbtTag::Short(id) => Some(*id as i32),
+        NbtTag::Int(id) => Some(*id),
+        _ => None,
+    }
+}
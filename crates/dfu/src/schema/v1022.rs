@@ -0,0 +1,22 @@
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 1022,
+        references: types,
+    };
+    define_schema!(info, {
+        register player {
+            opt("RootVehicle", opt("Entity", refer(entity))),
+            opt("Inventory", list(refer(item_stack))),
+            opt("EnderItems", list(refer(item_stack))),
+            opt("ShoulderEntityLeft", refer(entity)),
+            opt("ShoulderEntityRight", refer(entity))
+        },
+        register hotbar {
+            map_values(list(refer(item_stack)))
+        }
+    });
+}
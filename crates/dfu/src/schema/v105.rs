@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use lazy_static::lazy_static;
+use quartz_nbt::NbtCompound;
+
+/// The entities spawn eggs spawned by their damage
+const SPAWN_EGG_ENTITIES: [(i32, &str); 67] = [
+    (1, "Item"),
+    (2, "XPOrb"),
+    (7, "ThrownEgg"),
+    (8, "LeashKnot"),
+    (9, "Painting"),
+    (10, "Arrow"),
+    (11, "Snowball"),
+    (12, "Fireball"),
+    (13, "SmallFireball"),
+    (14, "ThrownEnderpearl"),
+    (15, "EyeOfEnderSignal"),
+    (16, "ThrownPotion"),
+    (17, "ThrownExpBottle"),
+    (18, "ItemFrame"),
+    (19, "WitherSkull"),
+    (20, "PrimedTnt"),
+    (21, "FallingSand"),
+    (22, "FireworksRocketEntity"),
+    (23, "TippedArrow"),
+    (24, "SpectralArrow"),
+    (25, "ShulkerBullet"),
+    (26, "DragonFireball"),
+    (30, "ArmorStand"),
+    (40, "MinecartCommandBlock"),
+    (41, "Boat"),
+    (42, "MinecartRideable"),
+    (43, "MinecartChest"),
+    (44, "MinecartFurnace"),
+    (45, "MinecartTNT"),
+    (46, "MinecartHopper"),
+    (47, "MinecartSpawner"),
+    (48, "Mob"),
+    (49, "Monster"),
+    (50, "Creeper"),
+    (51, "Skeleton"),
+    (52, "Spider"),
+    (53, "Giant"),
+    (54, "Zombie"),
+    (55, "Slime"),
+    (56, "Ghast"),
+    (57, "PigZombie"),
+    (58, "Enderman"),
+    (59, "CaveSpider"),
+    (60, "Silverfish"),
+    (61, "Blaze"),
+    (62, "LavaSlime"),
+    (63, "EnderDragon"),
+    (64, "WitherBoss"),
+    (65, "Bat"),
+    (66, "Witch"),
+    (67, "Endermite"),
+    (68, "Guardian"),
+    (69, "Shulker"),
+    (90, "Pig"),
+    (91, "Sheep"),
+    (92, "Cow"),
+    (93, "Chicken"),
+    (94, "Squid"),
+    (95, "Wolf"),
+    (96, "MushroomCow"),
+    (97, "SnowMan"),
+    (98, "Ozelot"),
+    (99, "VillagerGolem"),
+    (100, "EntityHorse"),
+    (101, "Rabbit"),
+    (120, "Villager"),
+    (200, "EnderCrystal"),
+];
+
+lazy_static! {
+    static ref SPAWN_EGG_NAMES: HashMap<i32, &'static str> = SPAWN_EGG_ENTITIES.into_iter().collect();
+}
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 105,
+        references: types,
+    };
+    define_schema!(info, {
+        register item_stack {
+            tagged_choice("id", {
+                "minecraft:spawn_egg" => custom(|value, _from, _to| {
+                    if let Some(item) = fix::compound(value) {
+                        spawn_egg_entity(item);
+                    }
+                    Ok(())
+                })
+            }),
+            opt("tag", all(
+                opt("EntityTag", refer(entity)),
+                opt("BlockEntityTag", refer(tile_entity))
+            ))
+        }
+    });
+}
+
+/// Spawn eggs stored the entity they spawn as their damage, it moved to the id of their entity tag
+fn spawn_egg_entity(item: &mut NbtCompound) {
+    let damage = fix::get_int(item, "Damage").unwrap_or(0);
+    let has_id = fix::child(item, "tag")
+        .and_then(|tag| fix::child(tag, "EntityTag"))
+        .is_some_and(|entity| fix::id(entity).is_some());
+    if !has_id {
+        if let Some(entity_id) = SPAWN_EGG_NAMES.get(&(damage & 255)) {
+            if fix::child(item, "tag").is_none() {
+                item.insert("tag", NbtCompound::new());
+            }
+            let tag = fix::child(item, "tag").expect("inserted above");
+            if fix::child(tag, "EntityTag").is_none() {
+                tag.insert("EntityTag", NbtCompound::new());
+            }
+            fix::child(tag, "EntityTag").expect("inserted above").insert("id", *entity_id);
+        }
+    }
+    if damage != 0 {
+        item.insert("Damage", 0i16);
+    }
+}
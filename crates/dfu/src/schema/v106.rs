@@ -0,0 +1,44 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+
+/// Minecarts were one entity, with their kind in `Type`
+const MINECART_IDS: [&str; 3] = ["MinecartRideable", "MinecartChest", "MinecartFurnace"];
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 106,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            tagged_choice("id", {
+                "Minecart" => custom(|value, _from, _to| {
+                    if let Some(minecart) = fix::compound(value) {
+                        let kind = fix::get_int(minecart, "Type").unwrap_or(0);
+                        let id = MINECART_IDS.get(kind as usize).filter(|_| kind > 0).unwrap_or(&MINECART_IDS[0]);
+                        minecart.insert("id", *id);
+                        fix::remove(minecart, "Type");
+                    }
+                    Ok(())
+                })
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Riding", refer(entity)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
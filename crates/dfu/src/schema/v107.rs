@@ -0,0 +1,58 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 107,
+        references: types,
+    };
+    define_schema!(info, {
+        register tile_entity {
+            tagged_choice("id", {
+                "MobSpawner" => custom(|value, _from, _to| {
+                    if let Some(spawner) = fix::compound(value) {
+                        spawner_entities(spawner);
+                    }
+                    Ok(())
+                })
+            }),
+            opt("Items", list(refer(item_stack))),
+            opt("RecordItem", refer(item_stack)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Spawners named the entity they spawn in `EntityId`, and their potentials split it into `Type` and `Properties`. Both became
+/// entity compounds.
+fn spawner_entities(spawner: &mut NbtCompound) {
+    if let Some(NbtTag::String(entity_id)) = fix::remove(spawner, "EntityId") {
+        if fix::child(spawner, "SpawnData").is_none() {
+            spawner.insert("SpawnData", NbtCompound::new());
+        }
+        let entity_id = if entity_id.is_empty() { "Pig".to_string() } else { entity_id };
+        fix::child(spawner, "SpawnData").expect("inserted above").insert("id", entity_id);
+    }
+
+    if let Ok(potentials) = spawner.get_mut::<_, &mut NbtList>("SpawnPotentials") {
+        for potential in potentials.iter_mut() {
+            let potential = match fix::compound(potential) {
+                Some(potential) => potential,
+                None => continue,
+            };
+            if let Some(NbtTag::String(entity_id)) = potential.inner().get("Type").cloned() {
+                let mut entity = match fix::remove(potential, "Properties") {
+                    Some(NbtTag::Compound(properties)) => properties,
+                    _ => NbtCompound::new(),
+                };
+                entity.insert("id", entity_id);
+                potential.insert("Entity", entity);
+                fix::remove(potential, "Type");
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 108,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            custom(|value, _from, _to| {
+                if let Some(entity) = fix::compound(value) {
+                    if let Some((most, least)) = entity.get::<_, &str>("UUID").ok().and_then(parse_uuid) {
+                        entity.insert("UUIDMost", most);
+                        entity.insert("UUIDLeast", least);
+                    }
+                }
+                Ok(())
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Riding", refer(entity)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// The most and least significant halves of a UUID written like `123e4567-e89b-12d3-a456-426614174000`. Like the game,
+/// the groups may have fewer digits.
+fn parse_uuid(uuid: &str) -> Option<(i64, i64)> {
+    let groups = uuid
+        .split('-')
+        .map(|group| u64::from_str_radix(group, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+    let [a, b, c, d, e] = groups[..] else {
+        return None;
+    };
+    let most = (a & 0xffff_ffff) << 32 | (b & 0xffff) << 16 | c & 0xffff;
+    let least = (d & 0xffff) << 48 | e & 0xffff_ffff_ffff;
+    Some((most as i64, least as i64))
+}
@@ -0,0 +1,92 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtCompound;
+
+const LIVING_ENTITIES: [&str; 34] = [
+    "ArmorStand",
+    "Bat",
+    "Blaze",
+    "CaveSpider",
+    "Chicken",
+    "Cow",
+    "Creeper",
+    "EnderDragon",
+    "Enderman",
+    "Endermite",
+    "EntityHorse",
+    "Ghast",
+    "Giant",
+    "Guardian",
+    "LavaSlime",
+    "MushroomCow",
+    "Ozelot",
+    "Pig",
+    "PigZombie",
+    "Rabbit",
+    "Sheep",
+    "Shulker",
+    "Silverfish",
+    "Skeleton",
+    "Slime",
+    "SnowMan",
+    "Spider",
+    "Squid",
+    "Villager",
+    "VillagerGolem",
+    "Witch",
+    "WitherBoss",
+    "Wolf",
+    "Zombie",
+];
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 109,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            custom(|value, _from, _to| {
+                if let Some(entity) = fix::compound(value) {
+                    float_health(entity);
+                }
+                Ok(())
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Riding", refer(entity)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Health used to be a short, with the float in `HealF`
+fn float_health(entity: &mut NbtCompound) {
+    if !fix::id(entity).is_some_and(|id| LIVING_ENTITIES.contains(&id)) {
+        return;
+    }
+    let health = match fix::get_float(entity, "HealF") {
+        Some(health) => {
+            fix::remove(entity, "HealF");
+            health
+        }
+        None => match fix::get_float(entity, "Health") {
+            Some(health) => health,
+            None => return,
+        },
+    };
+    entity.insert("Health", health);
+}
@@ -0,0 +1,47 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtCompound;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 110,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            tagged_choice("id", {
+                "EntityHorse" => custom(|value, _from, _to| {
+                    if let Some(horse) = fix::compound(value) {
+                        let saddled = fix::get_int(horse, "Saddle").unwrap_or(0) != 0;
+                        if saddled && fix::child(horse, "SaddleItem").is_none() {
+                            let mut saddle = NbtCompound::new();
+                            saddle.insert("id", "minecraft:saddle");
+                            saddle.insert("Count", 1i8);
+                            saddle.insert("Damage", 0i16);
+                            horse.insert("SaddleItem", saddle);
+                            fix::remove(horse, "Saddle");
+                        }
+                    }
+                    Ok(())
+                })
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Riding", refer(entity)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
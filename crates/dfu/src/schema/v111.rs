@@ -0,0 +1,72 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtCompound;
+
+/// The block offsets of the horizontal facings, in their index order: south, west, north and east
+const FACING_OFFSETS: [[i32; 3]; 4] = [[0, 0, 1], [-1, 0, 0], [0, 0, -1], [1, 0, 0]];
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 111,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            tagged_choice("id", {
+                "Painting" | "ItemFrame" => custom(|value, _from, _to| {
+                    if let Some(entity) = fix::compound(value) {
+                        hanging_facing(entity);
+                    }
+                    Ok(())
+                })
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Riding", refer(entity)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Paintings and item frames stored their facing in `Direction`, or `Dir` before that, with positions relative to the block
+/// they hang on. `Direction` positions moved to the block in front of it.
+fn hanging_facing(entity: &mut NbtCompound) {
+    if fix::get_int(entity, "Facing").is_some() {
+        return;
+    }
+    let facing = match fix::get_int(entity, "Direction") {
+        Some(direction) => {
+            let facing = (direction as i8 % 4).unsigned_abs() as usize;
+            let [x, y, z] = FACING_OFFSETS[facing];
+            for (key, offset) in [("TileX", x), ("TileY", y), ("TileZ", z)] {
+                entity.insert(key, fix::get_int(entity, key).unwrap_or(0) + offset);
+            }
+            fix::remove(entity, "Direction");
+            if fix::id(entity) == Some("ItemFrame") {
+                if let Some(rotation) = fix::get_int(entity, "ItemRotation") {
+                    entity.insert("ItemRotation", (rotation as i8).wrapping_mul(2));
+                }
+            }
+            facing
+        }
+        None => {
+            let facing = (fix::get_int(entity, "Dir").unwrap_or(0) as i8 % 4).unsigned_abs() as usize;
+            fix::remove(entity, "Dir");
+            facing
+        }
+    };
+    entity.insert("Facing", facing as i8);
+}
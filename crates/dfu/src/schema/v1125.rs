@@ -0,0 +1,95 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+const BED_BLOCK_ID: i32 = 26;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 1125,
+        references: types,
+    };
+    define_schema!(info, {
+        register item_stack {
+            custom(|value, _from, _to| {
+                if let Some(item) = fix::compound(value) {
+                    if fix::id(item) == Some("minecraft:bed") && fix::get_int(item, "Damage").unwrap_or(0) == 0 {
+                        item.insert("Damage", 14i16);
+                    }
+                }
+                Ok(())
+            }),
+            opt("id", refer(item_name)),
+            opt("tag", all(
+                opt("EntityTag", refer(entity)),
+                opt("BlockEntityTag", refer(tile_entity))
+            ))
+        },
+        register chunk {
+            custom(|value, _from, _to| {
+                if let Some(level) = fix::compound(value).and_then(|chunk| fix::child(chunk, "Level")) {
+                    add_bed_tile_entities(level);
+                }
+                Ok(())
+            }),
+            req("Level", all(
+                opt("Entities", list(refer(entity))),
+                opt("TileEntities", list(refer(tile_entity))),
+                opt("TileTicks", list(req("i", refer(block_name))))
+            ))
+        }
+    });
+}
+
+/// Beds became tile entities storing their color, existing beds are red
+fn add_bed_tile_entities(level: &mut NbtCompound) {
+    let chunk_x = fix::get_int(level, "xPos").unwrap_or(0);
+    let chunk_z = fix::get_int(level, "zPos").unwrap_or(0);
+
+    let mut beds = Vec::new();
+    for section in level
+        .get::<_, &NbtList>("Sections")
+        .into_iter()
+        .flat_map(|sections| sections.iter())
+    {
+        let section = match section {
+            NbtTag::Compound(section) => section,
+            _ => continue,
+        };
+        let section_y = fix::get_int(section, "Y").unwrap_or(0);
+        let blocks = match section.get::<_, &[i8]>("Blocks") {
+            Ok(blocks) => blocks,
+            Err(_) => continue,
+        };
+        let add = section.get::<_, &[i8]>("Add").ok();
+
+        for (idx, block) in blocks.iter().enumerate() {
+            let add_bits = add
+                .and_then(|add| add.get(idx >> 1))
+                .map_or(0, |add| (*add as u8 >> ((idx & 1) * 4)) & 15);
+            if (*block as u8 as i32 | (add_bits as i32) << 8) != BED_BLOCK_ID {
+                continue;
+            }
+            let mut bed = NbtCompound::new();
+            bed.insert("id", "minecraft:bed");
+            bed.insert("x", (chunk_x << 4) + (idx & 15) as i32);
+            bed.insert("y", (section_y << 4) + (idx >> 8 & 15) as i32);
+            bed.insert("z", (chunk_z << 4) + (idx >> 4 & 15) as i32);
+            bed.insert("color", 14);
+            beds.push(NbtTag::Compound(bed));
+        }
+    }
+    if beds.is_empty() {
+        return;
+    }
+
+    if level.get::<_, &NbtList>("TileEntities").is_err() {
+        level.insert("TileEntities", NbtList::new());
+    }
+    let tile_entities = level.get_mut::<_, &mut NbtList>("TileEntities").expect("inserted above");
+    for bed in beds {
+        tile_entities.push(bed);
+    }
+}
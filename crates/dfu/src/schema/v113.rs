@@ -0,0 +1,48 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtCompound, NbtList};
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 113,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            custom(|value, _from, _to| {
+                if let Some(entity) = fix::compound(value) {
+                    remove_zero_chances(entity, "HandDropChances", 2);
+                    remove_zero_chances(entity, "ArmorDropChances", 4);
+                }
+                Ok(())
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Riding", refer(entity)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Drop chances which are all zero are the default, and no longer saved
+fn remove_zero_chances(entity: &mut NbtCompound, key: &str, len: usize) {
+    let redundant = entity
+        .get::<_, &NbtList>(key)
+        .is_ok_and(|chances| chances.len() == len && (0..len).all(|idx| chances.get::<f32>(idx).is_ok_and(|chance| chance == 0.0)));
+    if redundant {
+        fix::remove(entity, key);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtList, NbtTag};
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 135,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            custom(|value, _from, _to| {
+                riding_to_passengers(value);
+                Ok(())
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Passengers", list(refer(entity))),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Turns a chain of entities riding each other inside out: the bottom-most mount becomes the stored entity, carrying its riders
+/// in `Passengers`
+fn riding_to_passengers(value: &mut NbtTag) {
+    loop {
+        let mount = match fix::compound(value).and_then(|entity| fix::remove(entity, "Riding")) {
+            Some(mount @ NbtTag::Compound(_)) => mount,
+            _ => return,
+        };
+        let rider = std::mem::replace(value, mount);
+        if let Some(mount) = fix::compound(value) {
+            mount.insert("Passengers", NbtList::from(vec![rider]));
+        }
+    }
+}
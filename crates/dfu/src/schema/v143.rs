@@ -0,0 +1,39 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 143,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            tagged_choice("id", {
+                // tipped arrows became arrows with potion effects
+                "TippedArrow" => custom(|value, _from, _to| {
+                    if let Some(arrow) = fix::compound(value) {
+                        arrow.insert("id", "Arrow");
+                    }
+                    Ok(())
+                })
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Passengers", list(refer(entity))),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
@@ -0,0 +1,173 @@
+use crate::util::fix;
+use crate::util::v1451_block_states::{block_name, block_state, legacy_block_id};
+use crate::util::v1451_chunk::flatten_sections;
+use crate::util::v1451_item_names::{flattened_item_name, is_damageable, spawn_egg};
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtCompound, NbtTag};
+
+// Item ids are not walked through item_name here, legacy and flattened names overlap (`melon` became `melon_slice`,
+// `melon_block` became `melon`) and the flattened name depends on the damage of the stack.
+// Block states are created in their final form, so they aren't walked through block_name either.
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 1451,
+        references: types,
+    };
+    define_schema!(info, {
+        register block_name {
+            custom(|value, _from, _to| {
+                let id = match value {
+                    NbtTag::String(name) => legacy_block_id(name),
+                    NbtTag::Byte(id) => Some(*id as u8 as i32),
+                    NbtTag::Short(id) => Some(*id as i32),
+                    NbtTag::Int(id) => Some(*id),
+                    _ => None,
+                };
+                if let Some(id) = id {
+                    *value = NbtTag::String(block_name(id).to_string());
+                }
+                Ok(())
+            })
+        },
+        register item_name {
+            custom(|value, _from, _to| {
+                if let NbtTag::String(name) = value {
+                    if let Some(new_name) = flattened_item_name(name, 0) {
+                        *name = new_name;
+                    }
+                }
+                Ok(())
+            })
+        },
+        register item_stack {
+            custom(|value, _from, _to| {
+                if let Some(item) = fix::compound(value) {
+                    flatten_item_stack(item);
+                }
+                Ok(())
+            }),
+            opt("tag", all(
+                opt("EntityTag", refer(entity)),
+                opt("BlockEntityTag", refer(tile_entity))
+            ))
+        },
+        register entity {
+            tagged_choice("id", {
+                "minecraft:falling_block" => custom(|value, _from, _to| {
+                    replace_legacy_block(value.try_into()?, &["Block", "TileID", "Tile"], "Data", "BlockState");
+                    Ok(())
+                }),
+                "minecraft:enderman" => custom(|value, _from, _to| {
+                    replace_legacy_block(value.try_into()?, &["carried"], "carriedData", "carriedBlockState");
+                    Ok(())
+                }),
+                _ => custom(|value, _from, _to| {
+                    if let Some(minecart) = fix::compound(value).filter(|entity| entity.contains_key("DisplayTile")) {
+                        replace_legacy_block(minecart, &["DisplayTile"], "DisplayData", "DisplayState");
+                    }
+                    Ok(())
+                })
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("DecorItem", refer(item_stack)),
+            opt("Passengers", list(refer(entity))),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity)))),
+            opt("id", refer(entity_name))
+        },
+        register tile_entity {
+            tagged_choice("id", {
+                "minecraft:piston" => custom(|value, _from, _to| {
+                    replace_legacy_block(value.try_into()?, &["blockId"], "blockData", "blockState");
+                    Ok(())
+                })
+            }),
+            opt("Items", list(refer(item_stack))),
+            opt("RecordItem", refer(item_stack)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        },
+        register chunk {
+            custom(|value, _from, _to| {
+                if let Some(level) = fix::compound(value).and_then(|chunk| fix::child(chunk, "Level")) {
+                    flatten_sections(level);
+                }
+                Ok(())
+            }),
+            req("Level", all(
+                opt("Entities", list(refer(entity))),
+                opt("TileEntities", list(refer(tile_entity))),
+                opt("TileTicks", list(req("i", refer(block_name))))
+            ))
+        }
+    });
+}
+
+/// Items lost their damage values, variants became items of their own and durability moved into the tag
+fn flatten_item_stack(item: &mut NbtCompound) {
+    let name = match fix::id(item) {
+        Some(name) => name.to_string(),
+        None => return,
+    };
+    let damage = fix::get_int(item, "Damage").unwrap_or(0);
+    fix::remove(item, "Damage");
+
+    let new_name = if name == "minecraft:spawn_egg" {
+        let entity = fix::child(item, "tag")
+            .and_then(|tag| fix::child(tag, "EntityTag"))
+            .and_then(|entity| fix::id(entity).map(str::to_string));
+        Some(spawn_egg(entity.as_deref()))
+    } else {
+        flattened_item_name(&name, damage)
+    };
+    if let Some(new_name) = new_name {
+        item.insert("id", new_name);
+    }
+
+    let (key, value) = if name == "minecraft:filled_map" {
+        ("map", damage)
+    } else if is_damageable(&name) && damage != 0 {
+        ("Damage", damage)
+    } else {
+        return;
+    };
+    if fix::child(item, "tag").is_none() {
+        item.insert("tag", NbtCompound::new());
+    }
+    if let Some(tag) = fix::child(item, "tag") {
+        tag.insert(key, value);
+    }
+}
+
+/// Entities and tile entities carrying blocks store them as block states. Replaces a legacy block stored as a name or id
+/// at the first of `id_keys`, with its metadata at `meta_key`, by its block state
+fn replace_legacy_block(compound: &mut NbtCompound, id_keys: &[&str], meta_key: &str, state_key: &str) {
+    let id = id_keys.iter().find_map(|key| match compound.inner().get(*key)? {
+        NbtTag::String(name) => legacy_block_id(name),
+        // the oldest falling blocks store their id in a byte, which is unsigned
+        NbtTag::Byte(id) => Some(*id as u8 as i32),
+        _ => fix::get_int(compound, key),
+    });
+    let meta = fix::get_int(compound, meta_key).unwrap_or(0);
+    for key in id_keys {
+        fix::remove(compound, key);
+    }
+    fix::remove(compound, meta_key);
+    if let Some(id) = id.filter(|id| *id != 0) {
+        compound.insert(state_key, block_state(id, meta & 15).clone());
+    }
+}
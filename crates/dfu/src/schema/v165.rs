@@ -0,0 +1,34 @@
+use crate::util::fix;
+use crate::util::v101_text_component::strict_json;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtList, NbtTag};
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 165,
+        references: types,
+    };
+    define_schema!(info, {
+        register item_stack {
+            tagged_choice("id", {
+                "minecraft:written_book" => custom(|value, _from, _to| {
+                    let pages = fix::compound(value)
+                        .and_then(|book| fix::child(book, "tag"))
+                        .and_then(|tag| tag.get_mut::<_, &mut NbtList>("pages").ok());
+                    for page in pages.into_iter().flat_map(|pages| pages.iter_mut()) {
+                        if let NbtTag::String(text) = page {
+                            *text = strict_json(text);
+                        }
+                    }
+                    Ok(())
+                })
+            }),
+            opt("tag", all(
+                opt("EntityTag", refer(entity)),
+                opt("BlockEntityTag", refer(tile_entity))
+            ))
+        }
+    });
+}
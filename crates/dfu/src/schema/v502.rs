@@ -0,0 +1,69 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtCompound, NbtTag};
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 502,
+        references: types,
+    };
+    define_schema!(info, {
+        register item_name {
+            match_value({
+                "cooked_fished" | "minecraft:cooked_fished" => custom(|value, _from, _to| {
+                    *value = NbtTag::String("minecraft:cooked_fish".to_string());
+                    Ok(())
+                })
+            })
+        },
+        register item_stack {
+            opt("id", refer(item_name)),
+            opt("tag", all(
+                opt("EntityTag", refer(entity)),
+                opt("BlockEntityTag", refer(tile_entity))
+            ))
+        },
+        register entity {
+            tagged_choice("id", {
+                "Zombie" => custom(|value, _from, _to| {
+                    if let Some(zombie) = fix::compound(value) {
+                        villager_zombie_type(zombie);
+                    }
+                    Ok(())
+                })
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Passengers", list(refer(entity))),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Zombie villagers used to be zombies with `IsVillager` and a `VillagerProfession`, which became a `ZombieType` of the
+/// profession plus one. Vanilla picks a random profession when it is missing or unknown, farmer keeps upgrades repeatable.
+fn villager_zombie_type(zombie: &mut NbtCompound) {
+    if fix::get_int(zombie, "IsVillager").unwrap_or(0) == 0 {
+        return;
+    }
+    if fix::get_int(zombie, "ZombieType").is_none() {
+        let profession = fix::get_int(zombie, "VillagerProfession")
+            .filter(|profession| (0..5).contains(profession))
+            .unwrap_or(0);
+        zombie.insert("ZombieType", profession + 1);
+    }
+    fix::remove(zombie, "IsVillager");
+}
@@ -0,0 +1,22 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 505,
+        references: types,
+    };
+    define_schema!(info, {
+        register options {
+            // vertex buffer objects were turned on for everyone
+            custom(|value, _from, _to| {
+                if let Some(options) = fix::compound(value) {
+                    options.insert("useVbo", "true");
+                }
+                Ok(())
+            })
+        }
+    });
+}
@@ -0,0 +1,48 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtCompound;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 700,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            custom(|value, _from, _to| {
+                if let Some(entity) = fix::compound(value) {
+                    split_elder_guardian(entity);
+                }
+                Ok(())
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Passengers", list(refer(entity))),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Elder guardians used to be guardians with the `Elder` flag
+fn split_elder_guardian(entity: &mut NbtCompound) {
+    if fix::id(entity) != Some("Guardian") {
+        return;
+    }
+    if fix::get_int(entity, "Elder").unwrap_or(0) != 0 {
+        entity.insert("id", "ElderGuardian");
+    }
+    fix::remove(entity, "Elder");
+}
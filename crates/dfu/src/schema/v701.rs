@@ -0,0 +1,50 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtCompound;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 701,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            custom(|value, _from, _to| {
+                if let Some(entity) = fix::compound(value) {
+                    split_skeleton(entity);
+                }
+                Ok(())
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Passengers", list(refer(entity))),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Wither skeletons and strays used to be skeletons with a `SkeletonType`
+fn split_skeleton(entity: &mut NbtCompound) {
+    if fix::id(entity) != Some("Skeleton") {
+        return;
+    }
+    match fix::get_int(entity, "SkeletonType") {
+        Some(1) => entity.insert("id", "WitherSkeleton"),
+        Some(2) => entity.insert("id", "Stray"),
+        _ => {}
+    }
+    fix::remove(entity, "SkeletonType");
+}
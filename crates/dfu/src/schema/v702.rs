@@ -0,0 +1,53 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtCompound;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 702,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            custom(|value, _from, _to| {
+                if let Some(entity) = fix::compound(value) {
+                    split_zombie(entity);
+                }
+                Ok(())
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Passengers", list(refer(entity))),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Zombie villagers and husks used to be zombies with a `ZombieType`, which for zombie villagers is their profession plus one
+fn split_zombie(entity: &mut NbtCompound) {
+    if fix::id(entity) != Some("Zombie") {
+        return;
+    }
+    match fix::get_int(entity, "ZombieType") {
+        Some(zombie_type @ 1..=5) => {
+            entity.insert("id", "ZombieVillager");
+            entity.insert("Profession", zombie_type - 1);
+        }
+        Some(6) => entity.insert("id", "Husk"),
+        _ => {}
+    }
+    fix::remove(entity, "ZombieType");
+}
@@ -0,0 +1,53 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtCompound;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 703,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity {
+            custom(|value, _from, _to| {
+                if let Some(entity) = fix::compound(value) {
+                    split_horse(entity);
+                }
+                Ok(())
+            }),
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("Passengers", list(refer(entity))),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Donkeys, mules, zombie horses and skeleton horses used to be horses with a `Type`
+fn split_horse(entity: &mut NbtCompound) {
+    if fix::id(entity) != Some("EntityHorse") {
+        return;
+    }
+    let id = match fix::get_int(entity, "Type") {
+        Some(1) => "Donkey",
+        Some(2) => "Mule",
+        Some(3) => "ZombieHorse",
+        Some(4) => "SkeletonHorse",
+        _ => "Horse",
+    };
+    entity.insert("id", id);
+    fix::remove(entity, "Type");
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use lazy_static::lazy_static;
+
+const TILE_ENTITY_IDS: [(&str, &str); 23] = [
+    ("Airportal", "minecraft:end_portal"),
+    ("Banner", "minecraft:banner"),
+    ("Beacon", "minecraft:beacon"),
+    ("Cauldron", "minecraft:brewing_stand"),
+    ("Chest", "minecraft:chest"),
+    ("Comparator", "minecraft:comparator"),
+    ("Control", "minecraft:command_block"),
+    ("DLDetector", "minecraft:daylight_detector"),
+    ("Dropper", "minecraft:dropper"),
+    ("EnchantTable", "minecraft:enchanting_table"),
+    ("EndGateway", "minecraft:end_gateway"),
+    ("EnderChest", "minecraft:ender_chest"),
+    ("FlowerPot", "minecraft:flower_pot"),
+    ("Furnace", "minecraft:furnace"),
+    ("Hopper", "minecraft:hopper"),
+    ("MobSpawner", "minecraft:mob_spawner"),
+    ("Music", "minecraft:noteblock"),
+    ("Piston", "minecraft:piston"),
+    ("RecordPlayer", "minecraft:jukebox"),
+    ("Sign", "minecraft:sign"),
+    ("Skull", "minecraft:skull"),
+    ("Structure", "minecraft:structure_block"),
+    ("Trap", "minecraft:dispenser"),
+];
+
+lazy_static! {
+    static ref TILE_ENTITY_NAMES: HashMap<&'static str, &'static str> = TILE_ENTITY_IDS.into_iter().collect();
+}
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 704,
+        references: types,
+    };
+    define_schema!(info, {
+        register tile_entity {
+            custom(|value, _from, _to| {
+                if let Some(id) = fix::compound(value).and_then(|tile_entity| tile_entity.inner_mut().get_mut("id")) {
+                    fix::rename(id, &TILE_ENTITY_NAMES);
+                }
+                Ok(())
+            }),
+            opt("Items", list(refer(item_stack))),
+            opt("RecordItem", refer(item_stack)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
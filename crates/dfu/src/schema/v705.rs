@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use lazy_static::lazy_static;
+
+const ENTITY_IDS: [(&str, &str); 75] = [
+    ("AreaEffectCloud", "minecraft:area_effect_cloud"),
+    ("ArmorStand", "minecraft:armor_stand"),
+    ("Arrow", "minecraft:arrow"),
+    ("Bat", "minecraft:bat"),
+    ("Blaze", "minecraft:blaze"),
+    ("Boat", "minecraft:boat"),
+    ("CaveSpider", "minecraft:cave_spider"),
+    ("Chicken", "minecraft:chicken"),
+    ("Cow", "minecraft:cow"),
+    ("Creeper", "minecraft:creeper"),
+    ("Donkey", "minecraft:donkey"),
+    ("DragonFireball", "minecraft:dragon_fireball"),
+    ("ElderGuardian", "minecraft:elder_guardian"),
+    ("EnderCrystal", "minecraft:ender_crystal"),
+    ("EnderDragon", "minecraft:ender_dragon"),
+    ("Enderman", "minecraft:enderman"),
+    ("Endermite", "minecraft:endermite"),
+    ("EyeOfEnderSignal", "minecraft:eye_of_ender_signal"),
+    ("FallingSand", "minecraft:falling_block"),
+    ("Fireball", "minecraft:fireball"),
+    ("FireworksRocketEntity", "minecraft:fireworks_rocket"),
+    ("Ghast", "minecraft:ghast"),
+    ("Giant", "minecraft:giant"),
+    ("Guardian", "minecraft:guardian"),
+    ("Horse", "minecraft:horse"),
+    ("Husk", "minecraft:husk"),
+    ("Item", "minecraft:item"),
+    ("ItemFrame", "minecraft:item_frame"),
+    ("LavaSlime", "minecraft:magma_cube"),
+    ("LeashKnot", "minecraft:leash_knot"),
+    ("MinecartChest", "minecraft:chest_minecart"),
+    ("MinecartCommandBlock", "minecraft:commandblock_minecart"),
+    ("MinecartFurnace", "minecraft:furnace_minecart"),
+    ("MinecartHopper", "minecraft:hopper_minecart"),
+    ("MinecartRideable", "minecraft:minecart"),
+    ("MinecartSpawner", "minecraft:spawner_minecart"),
+    ("MinecartTNT", "minecraft:tnt_minecart"),
+    ("Mule", "minecraft:mule"),
+    ("MushroomCow", "minecraft:mooshroom"),
+    ("Ozelot", "minecraft:ocelot"),
+    ("Painting", "minecraft:painting"),
+    ("Pig", "minecraft:pig"),
+    ("PigZombie", "minecraft:zombie_pigman"),
+    ("PolarBear", "minecraft:polar_bear"),
+    ("PrimedTnt", "minecraft:tnt"),
+    ("Rabbit", "minecraft:rabbit"),
+    ("Sheep", "minecraft:sheep"),
+    ("Shulker", "minecraft:shulker"),
+    ("ShulkerBullet", "minecraft:shulker_bullet"),
+    ("Silverfish", "minecraft:silverfish"),
+    ("Skeleton", "minecraft:skeleton"),
+    ("SkeletonHorse", "minecraft:skeleton_horse"),
+    ("Slime", "minecraft:slime"),
+    ("SmallFireball", "minecraft:small_fireball"),
+    ("SnowMan", "minecraft:snowman"),
+    ("Snowball", "minecraft:snowball"),
+    ("SpectralArrow", "minecraft:spectral_arrow"),
+    ("Spider", "minecraft:spider"),
+    ("Squid", "minecraft:squid"),
+    ("Stray", "minecraft:stray"),
+    ("ThrownEgg", "minecraft:egg"),
+    ("ThrownEnderpearl", "minecraft:ender_pearl"),
+    ("ThrownExpBottle", "minecraft:xp_bottle"),
+    ("ThrownPotion", "minecraft:potion"),
+    ("Villager", "minecraft:villager"),
+    ("VillagerGolem", "minecraft:villager_golem"),
+    ("Witch", "minecraft:witch"),
+    ("WitherBoss", "minecraft:wither"),
+    ("WitherSkeleton", "minecraft:wither_skeleton"),
+    ("WitherSkull", "minecraft:wither_skull"),
+    ("Wolf", "minecraft:wolf"),
+    ("XPOrb", "minecraft:xp_orb"),
+    ("Zombie", "minecraft:zombie"),
+    ("ZombieHorse", "minecraft:zombie_horse"),
+    ("ZombieVillager", "minecraft:zombie_villager"),
+];
+
+lazy_static! {
+    static ref ENTITY_NAMES: HashMap<&'static str, &'static str> = ENTITY_IDS.into_iter().collect();
+}
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 705,
+        references: types,
+    };
+    define_schema!(info, {
+        register entity_name {
+            custom(|value, _from, _to| {
+                fix::rename(value, &ENTITY_NAMES);
+                Ok(())
+            })
+        },
+        register entity {
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("HandItems", list(refer(item_stack))),
+            opt("ArmorItems", list(refer(item_stack))),
+            opt("SaddleItem", refer(item_stack)),
+            opt("ArmorItem", refer(item_stack)),
+            opt("Inventory", list(refer(item_stack))),
+            opt("Offers", opt("Recipes", list(all(
+                opt("buy", refer(item_stack)),
+                opt("buyB", refer(item_stack)),
+                opt("sell", refer(item_stack))
+            )))),
+            opt("DecorItem", refer(item_stack)),
+            opt("Passengers", list(refer(entity))),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity)))),
+            opt("id", refer(entity_name))
+        }
+    });
+}
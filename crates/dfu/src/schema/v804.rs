@@ -0,0 +1,58 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtCompound, NbtList};
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 804,
+        references: types,
+    };
+    define_schema!(info, {
+        register item_stack {
+            custom(|value, _from, _to| {
+                if let Some(item) = fix::compound(value) {
+                    banner_base_to_damage(item);
+                }
+                Ok(())
+            }),
+            opt("id", refer(item_name)),
+            opt("tag", all(
+                opt("EntityTag", refer(entity)),
+                opt("BlockEntityTag", refer(tile_entity))
+            ))
+        }
+    });
+}
+
+/// The base color of banner items moved from their block entity tag to their damage
+fn banner_base_to_damage(item: &mut NbtCompound) {
+    if fix::id(item) != Some("minecraft:banner") {
+        return;
+    }
+    let tag = match fix::child(item, "tag") {
+        Some(tag) => tag,
+        None => return,
+    };
+    let base = match fix::child(tag, "BlockEntityTag").and_then(|block_entity| fix::get_int(block_entity, "Base")) {
+        Some(base) => base,
+        None => return,
+    };
+    // items only showing the "(+NBT)" lore were created by pick block, the game keeps their tag as is
+    let pick_block_lore = fix::child(tag, "display")
+        .and_then(|display| display.get::<_, &NbtList>("Lore").ok())
+        .is_some_and(|lore| lore.len() == 1 && lore.get::<&str>(0).ok() == Some("(+NBT)"));
+
+    if !pick_block_lore {
+        let block_entity = fix::child(tag, "BlockEntityTag").expect("checked above");
+        fix::remove(block_entity, "Base");
+        if block_entity.is_empty() {
+            fix::remove(tag, "BlockEntityTag");
+        }
+        if tag.is_empty() {
+            fix::remove(item, "tag");
+        }
+    }
+    item.insert("Damage", (base & 15) as i16);
+}
@@ -0,0 +1,49 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtCompound;
+
+const POTION_ITEMS: [&str; 4] = [
+    "minecraft:potion",
+    "minecraft:splash_potion",
+    "minecraft:lingering_potion",
+    "minecraft:tipped_arrow",
+];
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 806,
+        references: types,
+    };
+    define_schema!(info, {
+        register item_stack {
+            custom(|value, _from, _to| {
+                if let Some(item) = fix::compound(value) {
+                    default_water_potion(item);
+                }
+                Ok(())
+            }),
+            opt("id", refer(item_name)),
+            opt("tag", all(
+                opt("EntityTag", refer(entity)),
+                opt("BlockEntityTag", refer(tile_entity))
+            ))
+        }
+    });
+}
+
+/// Potions without a `Potion` became water bottles
+fn default_water_potion(item: &mut NbtCompound) {
+    if !fix::id(item).is_some_and(|id| POTION_ITEMS.contains(&id)) {
+        return;
+    }
+    if !item.contains_key("tag") {
+        item.insert("tag", NbtCompound::new());
+    }
+    if let Some(tag) = fix::child(item, "tag") {
+        if !tag.contains_key("Potion") {
+            tag.insert("Potion", "minecraft:water");
+        }
+    }
+}
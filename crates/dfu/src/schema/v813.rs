@@ -0,0 +1,82 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::{NbtCompound, NbtList};
+
+const SHULKER_BOXES: [&str; 16] = [
+    "minecraft:white_shulker_box",
+    "minecraft:orange_shulker_box",
+    "minecraft:magenta_shulker_box",
+    "minecraft:light_blue_shulker_box",
+    "minecraft:yellow_shulker_box",
+    "minecraft:lime_shulker_box",
+    "minecraft:pink_shulker_box",
+    "minecraft:gray_shulker_box",
+    "minecraft:silver_shulker_box",
+    "minecraft:cyan_shulker_box",
+    "minecraft:purple_shulker_box",
+    "minecraft:blue_shulker_box",
+    "minecraft:brown_shulker_box",
+    "minecraft:green_shulker_box",
+    "minecraft:red_shulker_box",
+    "minecraft:black_shulker_box",
+];
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 813,
+        references: types,
+    };
+    define_schema!(info, {
+        register item_stack {
+            custom(|value, _from, _to| {
+                if let Some(item) = fix::compound(value) {
+                    shulker_box_item_color(item);
+                }
+                Ok(())
+            }),
+            opt("id", refer(item_name)),
+            opt("tag", all(
+                opt("EntityTag", refer(entity)),
+                opt("BlockEntityTag", refer(tile_entity))
+            ))
+        },
+        register tile_entity {
+            tagged_choice("id", {
+                "minecraft:shulker_box" => remove("Color")
+            }),
+            opt("Items", list(refer(item_stack))),
+            opt("RecordItem", refer(item_stack)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        }
+    });
+}
+
+/// Each shulker box color became its own item, instead of a `Color` in the block entity tag
+fn shulker_box_item_color(item: &mut NbtCompound) {
+    if fix::id(item) != Some("minecraft:shulker_box") {
+        return;
+    }
+    let tag = match fix::child(item, "tag") {
+        Some(tag) => tag,
+        None => return,
+    };
+    let block_entity = match fix::child(tag, "BlockEntityTag") {
+        Some(block_entity) => block_entity,
+        None => return,
+    };
+    if block_entity.get::<_, &NbtList>("Items").is_ok_and(|items| items.is_empty()) {
+        fix::remove(block_entity, "Items");
+    }
+    let color = fix::get_int(block_entity, "Color").unwrap_or(0);
+    fix::remove(block_entity, "Color");
+    if block_entity.is_empty() {
+        fix::remove(tag, "BlockEntityTag");
+    }
+    if tag.is_empty() {
+        fix::remove(item, "tag");
+    }
+    item.insert("id", SHULKER_BOXES[color.rem_euclid(16) as usize]);
+}
@@ -0,0 +1,22 @@
+use crate::util::fix;
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtTag;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 816,
+        references: types,
+    };
+    define_schema!(info, {
+        register options {
+            custom(|value, _from, _to| {
+                if let Some(NbtTag::String(lang)) = fix::compound(value).and_then(|options| options.inner_mut().get_mut("lang")) {
+                    *lang = lang.to_lowercase();
+                }
+                Ok(())
+            })
+        }
+    });
+}
@@ -0,0 +1,21 @@
+use dfu_schema_define::define_schema;
+use dfu_structures::types::Types;
+use dfu_structures::SchemaInfo;
+use quartz_nbt::NbtTag;
+
+pub fn define(types: &mut Types) {
+    let info = SchemaInfo {
+        version: 820,
+        references: types,
+    };
+    define_schema!(info, {
+        register item_name {
+            match_value({
+                "minecraft:totem" => custom(|value, _from, _to| {
+                    *value = NbtTag::String("minecraft:totem_of_undying".to_string());
+                    Ok(())
+                })
+            })
+        }
+    });
+}
@@ -1,8 +1,8 @@
 use dfu_schema_define::define_schema;
-use dfu_structures::types::{ConversionFunc, Types};
+use dfu_structures::types::Types;
 use dfu_structures::SchemaInfo;
 
-pub fn define(types: &mut Types<&'static ConversionFunc>) {
+pub fn define(types: &mut Types) {
     let info = SchemaInfo {
         version: 99,
         references: types,
@@ -12,10 +12,22 @@ pub fn define(types: &mut Types<&'static ConversionFunc>) {
             opt("Inventory", list(refer(item_stack))),
             opt("EnderItems", list(refer(item_stack)))
         },
+        register entity {
+            opt("Item", refer(item_stack)),
+            opt("Items", list(refer(item_stack))),
+            opt("Equipment", list(refer(item_stack))),
+            opt("Riding", refer(entity))
+        },
+        register tile_entity {
+            opt("Items", list(refer(item_stack))),
+            opt("RecordItem", refer(item_stack)),
+            opt("SpawnData", refer(entity)),
+            opt("SpawnPotentials", list(opt("Entity", refer(entity))))
+        },
         register chunk {
             req("Level", all(
                 opt("Entities", list(refer(entity))),
-                opt("TileEntitites", list(refer(tile_entity))),
+                opt("TileEntities", list(refer(tile_entity))),
                 opt("TileTicks", list(req("i", refer(block_name))))
             ))
         },
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use quartz_nbt::{NbtCompound, NbtTag};
+
+/// The compound in `value`, if it is one
+pub(crate) fn compound(value: &mut NbtTag) -> Option<&mut NbtCompound> {
+    match value {
+        NbtTag::Compound(compound) => Some(compound),
+        _ => None,
+    }
+}
+
+/// The compound stored at `key` in `compound`, if there is one
+pub(crate) fn child<'a>(compound: &'a mut NbtCompound, key: &str) -> Option<&'a mut NbtCompound> {
+    compound.get_mut::<_, &mut NbtCompound>(key).ok()
+}
+
+/// The string id of `compound`, if it has one
+pub(crate) fn id(compound: &NbtCompound) -> Option<&str> {
+    compound.get::<_, &str>("id").ok()
+}
+
+/// Reads any numeric tag at `key` as an int, the way the game reads numbers regardless of their stored width
+pub(crate) fn get_int(compound: &NbtCompound, key: &str) -> Option<i32> {
+    match compound.inner().get(key)? {
+        NbtTag::Byte(value) => Some(*value as i32),
+        NbtTag::Short(value) => Some(*value as i32),
+        NbtTag::Int(value) => Some(*value),
+        NbtTag::Long(value) => Some(*value as i32),
+        NbtTag::Float(value) => Some(*value as i32),
+        NbtTag::Double(value) => Some(*value as i32),
+        _ => None,
+    }
+}
+
+/// Reads any numeric tag at `key` as a float, like [`get_int`]
+pub(crate) fn get_float(compound: &NbtCompound, key: &str) -> Option<f32> {
+    match compound.inner().get(key)? {
+        NbtTag::Byte(value) => Some(*value as f32),
+        NbtTag::Short(value) => Some(*value as f32),
+        NbtTag::Int(value) => Some(*value as f32),
+        NbtTag::Long(value) => Some(*value as f32),
+        NbtTag::Float(value) => Some(*value),
+        NbtTag::Double(value) => Some(*value as f32),
+        _ => None,
+    }
+}
+
+/// Removes `key` from `compound`, returning its value
+pub(crate) fn remove(compound: &mut NbtCompound, key: &str) -> Option<NbtTag> {
+    compound.inner_mut().remove(key)
+}
+
+/// Replaces the string in `value` with its entry in `names`. Unknown names are left as they are.
+pub(crate) fn rename(value: &mut NbtTag, names: &HashMap<&'static str, &'static str>) {
+    if let NbtTag::String(name) = value {
+        if let Some(new_name) = names.get(name.as_str()) {
+            *name = new_name.to_string();
+        }
+    }
+}
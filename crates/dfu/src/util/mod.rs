@@ -1 +1,6 @@
+pub(crate) mod fix;
+pub(crate) mod v101_text_component;
 pub(crate) mod v102_item_name;
+pub(crate) mod v1451_block_states;
+pub(crate) mod v1451_chunk;
+pub(crate) mod v1451_item_names;
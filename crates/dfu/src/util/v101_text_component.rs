@@ -0,0 +1,39 @@
+use serde_json::{json, Value};
+
+/// Turns text saved before text components were strict JSON into a component, the way the game reads it: quoted strings
+/// and objects are parsed as JSON, anything else, or JSON which doesn't parse, is plain text
+pub(crate) fn strict_json(text: &str) -> String {
+    if text.is_empty() || text == "null" {
+        return plain_text("");
+    }
+    let quoted = text.len() > 1 && text.starts_with('"') && text.ends_with('"');
+    let object = text.starts_with('{') && text.ends_with('}');
+    if !quoted && !object {
+        return plain_text(text);
+    }
+    match serde_json::from_str::<Value>(text) {
+        Ok(Value::String(string)) => plain_text(&string),
+        Ok(component @ Value::Object(_)) => component.to_string(),
+        _ => plain_text(text),
+    }
+}
+
+fn plain_text(text: &str) -> String {
+    json!({ "text": text }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strict_json;
+
+    #[test]
+    fn texts_become_components() {
+        assert_eq!(strict_json(""), r#"{"text":""}"#);
+        assert_eq!(strict_json("null"), r#"{"text":""}"#);
+        assert_eq!(strict_json("Hello"), r#"{"text":"Hello"}"#);
+        assert_eq!(strict_json(r#""quoted""#), r#"{"text":"quoted"}"#);
+        assert_eq!(strict_json(r#"{"text":"bold","bold":true}"#), r#"{"bold":true,"text":"bold"}"#);
+        assert_eq!(strict_json("{not json}"), r#"{"text":"{not json}"}"#);
+        assert_eq!(strict_json(r#"say "hi""#), r#"{"text":"say \"hi\""}"#);
+    }
+}
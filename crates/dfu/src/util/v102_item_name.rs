@@ -1,4 +1,5 @@
 use lazy_static::lazy_static;
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
 use std::collections::HashMap;
 
 // This class is responsible for mapping the id -> string update in itemstacks and potions
@@ -8,6 +9,85 @@ lazy_static! {
     pub static ref POTION_NAMES: [Option<&'static str>; 128] = create_potion_names();
 }
 
+const SPLASH_BIT: i32 = 16384;
+
+/// Numeric item ids of a modded world, which Forge assigns per world and saves in the `FML` compound of level.dat
+#[derive(Clone, Debug, Default)]
+pub struct ModdedItemIds {
+    names: HashMap<i32, String>,
+}
+
+impl ModdedItemIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, id: i32, name: impl Into<String>) {
+        self.names.insert(id, name.into());
+    }
+
+    pub fn get(&self, id: i32) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    /// Reads the item ids of the `FML` compound of level.dat. 1.7 saves every id in `ItemData`, with items prefixed by
+    /// `\u{2}` and blocks by `\u{1}`, later versions save items in `Registries."minecraft:items".ids`.
+    pub fn from_forge_registry(fml: &NbtCompound) -> Self {
+        let mut ids = Self::new();
+        if let Ok(item_data) = fml.get::<_, &NbtList>("ItemData") {
+            for (name, id) in registry_entries(item_data) {
+                if let Some(name) = name.strip_prefix('\u{2}') {
+                    ids.insert(id, name);
+                }
+            }
+        }
+        let items = fml
+            .get::<_, &NbtCompound>("Registries")
+            .and_then(|registries| registries.get::<_, &NbtCompound>("minecraft:items"))
+            .and_then(|items| items.get::<_, &NbtList>("ids"));
+        if let Ok(items) = items {
+            for (name, id) in registry_entries(items) {
+                ids.insert(id, name);
+            }
+        }
+        ids
+    }
+}
+
+/// The `K` name and `V` id of every entry of a Forge registry list
+fn registry_entries(entries: &NbtList) -> impl Iterator<Item = (&str, i32)> {
+    entries.iter().filter_map(|entry| match entry {
+        NbtTag::Compound(entry) => Some((entry.get::<_, &str>("K").ok()?, entry.get::<_, i32>("V").ok()?)),
+        _ => None,
+    })
+}
+
+/// The name of a numeric item id, looked up in the ids of the world's mods first. The game drops items with unknown ids,
+/// they become air.
+pub fn item_name(id: i32, modded: &ModdedItemIds) -> &str {
+    modded.get(id).or_else(|| ITEM_NAMES.get(&id).copied()).unwrap_or_else(|| {
+        log::warn!(target: crate::LOG_TARGET, "Unknown legacy item id {id}, replacing it with air");
+        "minecraft:air"
+    })
+}
+
+/// A potion saved before 1.9, which stored its effect in the damage of the item
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LegacyPotion {
+    pub name: &'static str,
+    pub splash: bool,
+}
+
+/// Decodes the damage of a legacy potion. Bits 0-3 are the effect, bit 4 makes a potion without one awkward, bit 5
+/// amplifies it and bit 6 extends it, as listed in [`POTION_NAMES`]. Bit 14 makes it a splash potion, every other bit
+/// is ignored. Effects which don't exist are water.
+pub fn legacy_potion(damage: i32) -> LegacyPotion {
+    LegacyPotion {
+        name: POTION_NAMES[(damage & 127) as usize].unwrap_or("minecraft:water"),
+        splash: damage & SPLASH_BIT != 0,
+    }
+}
+
 fn create_item_names() -> HashMap<i32, &'static str> {
     let mut names = HashMap::new();
     names.insert(0, "minecraft:air");
@@ -149,12 +229,83 @@ fn create_item_names() -> HashMap<i32, &'static str> {
     names.insert(162, "minecraft:log2");
     names.insert(163, "minecraft:acacia_stairs");
     names.insert(164, "minecraft:dark_oak_stairs");
+    // blocks added in 1.8 to 1.12, saved by name but written as ids by some servers and mods
+    names.insert(165, "minecraft:slime");
+    names.insert(166, "minecraft:barrier");
+    names.insert(167, "minecraft:iron_trapdoor");
+    names.insert(168, "minecraft:prismarine");
+    names.insert(169, "minecraft:sea_lantern");
     names.insert(170, "minecraft:hay_block");
     names.insert(171, "minecraft:carpet");
     names.insert(172, "minecraft:hardened_clay");
     names.insert(173, "minecraft:coal_block");
     names.insert(174, "minecraft:packed_ice");
     names.insert(175, "minecraft:double_plant");
+    names.insert(179, "minecraft:red_sandstone");
+    names.insert(180, "minecraft:red_sandstone_stairs");
+    names.insert(182, "minecraft:stone_slab2");
+    names.insert(183, "minecraft:spruce_fence_gate");
+    names.insert(184, "minecraft:birch_fence_gate");
+    names.insert(185, "minecraft:jungle_fence_gate");
+    names.insert(186, "minecraft:dark_oak_fence_gate");
+    names.insert(187, "minecraft:acacia_fence_gate");
+    names.insert(188, "minecraft:spruce_fence");
+    names.insert(189, "minecraft:birch_fence");
+    names.insert(190, "minecraft:jungle_fence");
+    names.insert(191, "minecraft:dark_oak_fence");
+    names.insert(192, "minecraft:acacia_fence");
+    names.insert(198, "minecraft:end_rod");
+    names.insert(199, "minecraft:chorus_plant");
+    names.insert(200, "minecraft:chorus_flower");
+    names.insert(201, "minecraft:purpur_block");
+    names.insert(202, "minecraft:purpur_pillar");
+    names.insert(203, "minecraft:purpur_stairs");
+    names.insert(205, "minecraft:purpur_slab");
+    names.insert(206, "minecraft:end_bricks");
+    names.insert(208, "minecraft:grass_path");
+    names.insert(210, "minecraft:repeating_command_block");
+    names.insert(211, "minecraft:chain_command_block");
+    names.insert(213, "minecraft:magma");
+    names.insert(214, "minecraft:nether_wart_block");
+    names.insert(215, "minecraft:red_nether_brick");
+    names.insert(216, "minecraft:bone_block");
+    names.insert(217, "minecraft:structure_void");
+    names.insert(218, "minecraft:observer");
+    names.insert(219, "minecraft:white_shulker_box");
+    names.insert(220, "minecraft:orange_shulker_box");
+    names.insert(221, "minecraft:magenta_shulker_box");
+    names.insert(222, "minecraft:light_blue_shulker_box");
+    names.insert(223, "minecraft:yellow_shulker_box");
+    names.insert(224, "minecraft:lime_shulker_box");
+    names.insert(225, "minecraft:pink_shulker_box");
+    names.insert(226, "minecraft:gray_shulker_box");
+    names.insert(227, "minecraft:silver_shulker_box");
+    names.insert(228, "minecraft:cyan_shulker_box");
+    names.insert(229, "minecraft:purple_shulker_box");
+    names.insert(230, "minecraft:blue_shulker_box");
+    names.insert(231, "minecraft:brown_shulker_box");
+    names.insert(232, "minecraft:green_shulker_box");
+    names.insert(233, "minecraft:red_shulker_box");
+    names.insert(234, "minecraft:black_shulker_box");
+    names.insert(235, "minecraft:white_glazed_terracotta");
+    names.insert(236, "minecraft:orange_glazed_terracotta");
+    names.insert(237, "minecraft:magenta_glazed_terracotta");
+    names.insert(238, "minecraft:light_blue_glazed_terracotta");
+    names.insert(239, "minecraft:yellow_glazed_terracotta");
+    names.insert(240, "minecraft:lime_glazed_terracotta");
+    names.insert(241, "minecraft:pink_glazed_terracotta");
+    names.insert(242, "minecraft:gray_glazed_terracotta");
+    names.insert(243, "minecraft:silver_glazed_terracotta");
+    names.insert(244, "minecraft:cyan_glazed_terracotta");
+    names.insert(245, "minecraft:purple_glazed_terracotta");
+    names.insert(246, "minecraft:blue_glazed_terracotta");
+    names.insert(247, "minecraft:brown_glazed_terracotta");
+    names.insert(248, "minecraft:green_glazed_terracotta");
+    names.insert(249, "minecraft:red_glazed_terracotta");
+    names.insert(250, "minecraft:black_glazed_terracotta");
+    names.insert(251, "minecraft:concrete");
+    names.insert(252, "minecraft:concrete_powder");
+    names.insert(255, "minecraft:structure_block");
     names.insert(256, "minecraft:iron_shovel");
     names.insert(257, "minecraft:iron_pickaxe");
     names.insert(258, "minecraft:iron_axe");
@@ -330,61 +481,54 @@ fn create_item_names() -> HashMap<i32, &'static str> {
     // It's likely that this is a result of old CB/Spigot behavior still writing ids into items as ints.
     // These ids do not appear to be used by regular MC anyways, so I do not see the harm of porting it here.
     // Extras can be added if needed
-    let mut extra = [None; 4000];
     // EMC start
-    extra[409] = Some("minecraft:prismarine_shard");
-    extra[410] = Some("minecraft:prismarine_crystals");
-    extra[411] = Some("minecraft:rabbit");
-    extra[412] = Some("minecraft:cooked_rabbit");
-    extra[413] = Some("minecraft:rabbit_stew");
-    extra[414] = Some("minecraft:rabbit_foot");
-    extra[415] = Some("minecraft:rabbit_hide");
-    extra[416] = Some("minecraft:armor_stand");
-    extra[423] = Some("minecraft:mutton");
-    extra[424] = Some("minecraft:cooked_mutton");
-    extra[425] = Some("minecraft:banner");
-    extra[426] = Some("minecraft:end_crystal");
-    extra[427] = Some("minecraft:spruce_door");
-    extra[428] = Some("minecraft:birch_door");
-    extra[429] = Some("minecraft:jungle_door");
-    extra[430] = Some("minecraft:acacia_door");
-    extra[431] = Some("minecraft:dark_oak_door");
-    extra[432] = Some("minecraft:chorus_fruit");
-    extra[433] = Some("minecraft:chorus_fruit_popped");
-    extra[434] = Some("minecraft:beetroot");
-    extra[435] = Some("minecraft:beetroot_seeds");
-    extra[436] = Some("minecraft:beetroot_soup");
-    extra[437] = Some("minecraft:dragon_breath");
-    extra[438] = Some("minecraft:splash_potion");
-    extra[439] = Some("minecraft:spectral_arrow");
-    extra[440] = Some("minecraft:tipped_arrow");
-    extra[441] = Some("minecraft:lingering_potion");
-    extra[442] = Some("minecraft:shield");
-    extra[443] = Some("minecraft:elytra");
-    extra[444] = Some("minecraft:spruce_boat");
-    extra[445] = Some("minecraft:birch_boat");
-    extra[446] = Some("minecraft:jungle_boat");
-    extra[447] = Some("minecraft:acacia_boat");
-    extra[448] = Some("minecraft:dark_oak_boat");
-    extra[449] = Some("minecraft:totem_of_undying");
-    extra[450] = Some("minecraft:shulker_shell");
-    extra[452] = Some("minecraft:iron_nugget");
-    extra[453] = Some("minecraft:knowledge_book");
+    names.insert(409, "minecraft:prismarine_shard");
+    names.insert(410, "minecraft:prismarine_crystals");
+    names.insert(411, "minecraft:rabbit");
+    names.insert(412, "minecraft:cooked_rabbit");
+    names.insert(413, "minecraft:rabbit_stew");
+    names.insert(414, "minecraft:rabbit_foot");
+    names.insert(415, "minecraft:rabbit_hide");
+    names.insert(416, "minecraft:armor_stand");
+    names.insert(423, "minecraft:mutton");
+    names.insert(424, "minecraft:cooked_mutton");
+    names.insert(425, "minecraft:banner");
+    names.insert(426, "minecraft:end_crystal");
+    names.insert(427, "minecraft:spruce_door");
+    names.insert(428, "minecraft:birch_door");
+    names.insert(429, "minecraft:jungle_door");
+    names.insert(430, "minecraft:acacia_door");
+    names.insert(431, "minecraft:dark_oak_door");
+    names.insert(432, "minecraft:chorus_fruit");
+    names.insert(433, "minecraft:chorus_fruit_popped");
+    names.insert(434, "minecraft:beetroot");
+    names.insert(435, "minecraft:beetroot_seeds");
+    names.insert(436, "minecraft:beetroot_soup");
+    names.insert(437, "minecraft:dragon_breath");
+    names.insert(438, "minecraft:splash_potion");
+    names.insert(439, "minecraft:spectral_arrow");
+    names.insert(440, "minecraft:tipped_arrow");
+    names.insert(441, "minecraft:lingering_potion");
+    names.insert(442, "minecraft:shield");
+    names.insert(443, "minecraft:elytra");
+    names.insert(444, "minecraft:spruce_boat");
+    names.insert(445, "minecraft:birch_boat");
+    names.insert(446, "minecraft:jungle_boat");
+    names.insert(447, "minecraft:acacia_boat");
+    names.insert(448, "minecraft:dark_oak_boat");
+    names.insert(449, "minecraft:totem_of_undying");
+    names.insert(450, "minecraft:shulker_shell");
+    names.insert(452, "minecraft:iron_nugget");
+    names.insert(453, "minecraft:knowledge_book");
     // EMC end
 
-    // dump extra into map
-    for i in 0..extra.len() {
-        if let Some(value) = extra[i] {
-            names.insert(i as i32, value);
-        }
-    }
-
     // Add block ids into conversion as well
     // Very old versions of the game handled them, but it seems 1.8.8 did not parse them at all, so no conversion
     // was written.
     // block ids are only skipped (set to AIR) if there is no 1-1 replacement item.
     names.insert(26, "minecraft:bed"); // bed block
     names.insert(34, names.get(&0).unwrap()); // skip (piston head block)
+    names.insert(36, names.get(&0).unwrap()); // skip (moving piston block)
     names.insert(55, "minecraft:redstone"); // redstone wire block
     names.insert(59, names.get(&0).unwrap()); // skip (wheat crop block)
     names.insert(63, "minecraft:sign"); // standing sign
@@ -408,6 +552,19 @@ fn create_item_names() -> HashMap<i32, &'static str> {
     names.insert(144, "minecraft:skull"); // skull block
     names.insert(149, "minecraft:comparator"); // unpowered comparator block
     names.insert(150, "minecraft:comparator"); // powered comparator block
+    names.insert(176, "minecraft:banner"); // standing banner block
+    names.insert(177, "minecraft:banner"); // wall banner block
+    names.insert(178, "minecraft:daylight_detector"); // inverted daylight detector block
+    names.insert(181, "minecraft:double_stone_slab2"); // double red sandstone slab block
+    names.insert(193, "minecraft:spruce_door"); // spruce door block
+    names.insert(194, "minecraft:birch_door"); // birch door block
+    names.insert(195, "minecraft:jungle_door"); // jungle door block
+    names.insert(196, "minecraft:acacia_door"); // acacia door block
+    names.insert(197, "minecraft:dark_oak_door"); // dark oak door block
+    names.insert(204, "minecraft:purpur_double_slab"); // double purpur slab block
+    names.insert(207, names.get(&0).unwrap()); // skip (beetroot crop block)
+    names.insert(209, names.get(&0).unwrap()); // skip (end gateway block)
+    names.insert(212, names.get(&0).unwrap()); // skip (frosted ice block)
                                                // there are technically more, but at some point even older versions pre id -> name conversion didn't even load them.
                                                // (all I know is 1.7.10 does not load them)
                                                // and so given even the vanilla game wouldn't load them, there's no conversion path for them - they were never valid.
@@ -548,3 +705,105 @@ fn create_potion_names() -> [Option<&'static str>; 128] {
 
     names
 }
+
+#[cfg(test)]
+mod tests {
+    use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+    use super::{item_name, legacy_potion, LegacyPotion, ModdedItemIds};
+
+    #[test]
+    fn item_names() {
+        let vanilla = ModdedItemIds::new();
+        assert_eq!(item_name(1, &vanilla), "minecraft:stone");
+        assert_eq!(item_name(2267, &vanilla), "minecraft:record_wait");
+        // items and blocks added up to 1.12
+        assert_eq!(item_name(443, &vanilla), "minecraft:elytra");
+        assert_eq!(item_name(453, &vanilla), "minecraft:knowledge_book");
+        assert_eq!(item_name(165, &vanilla), "minecraft:slime");
+        assert_eq!(item_name(227, &vanilla), "minecraft:silver_shulker_box");
+        assert_eq!(item_name(250, &vanilla), "minecraft:black_glazed_terracotta");
+        // blocks with an item of another id, or without any
+        assert_eq!(item_name(64, &vanilla), "minecraft:wooden_door");
+        assert_eq!(item_name(194, &vanilla), "minecraft:birch_door");
+        assert_eq!(item_name(207, &vanilla), "minecraft:air");
+        // unknown ids
+        assert_eq!(item_name(451, &vanilla), "minecraft:air");
+        assert_eq!(item_name(-1, &vanilla), "minecraft:air");
+        assert_eq!(item_name(4096, &vanilla), "minecraft:air");
+
+        let mut modded = ModdedItemIds::new();
+        modded.insert(4096, "ic2:wrench");
+        modded.insert(1, "minecraft:stone");
+        assert_eq!(item_name(4096, &modded), "ic2:wrench");
+        assert_eq!(item_name(2, &modded), "minecraft:grass");
+    }
+
+    fn registry(entries: &[(&str, i32)]) -> NbtList {
+        let entries = entries.iter().map(|(name, id)| {
+            let mut entry = NbtCompound::new();
+            entry.insert("K", *name);
+            entry.insert("V", *id);
+            NbtTag::Compound(entry)
+        });
+        NbtList::from(entries.collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn forge_registries() {
+        // 1.7
+        let mut fml = NbtCompound::new();
+        let mut item_data = registry(&[
+            ("\u{1}ic2:blockOreCopper", 4000),
+            ("\u{2}ic2:blockOreCopper", 4000),
+            ("\u{2}ic2:itemToolWrench", 4100),
+        ]);
+        item_data.push(NbtCompound::new());
+        fml.insert("ItemData", item_data);
+        let ids = ModdedItemIds::from_forge_registry(&fml);
+        assert_eq!(ids.get(4000), Some("ic2:blockOreCopper"));
+        assert_eq!(ids.get(4100), Some("ic2:itemToolWrench"));
+        assert_eq!(ids.names.len(), 2);
+
+        // 1.8 and later
+        let mut blocks = NbtCompound::new();
+        blocks.insert("ids", registry(&[("ic2:resource", 500)]));
+        let mut items = NbtCompound::new();
+        items.insert("ids", registry(&[("ic2:wrench", 4100)]));
+        let mut registries = NbtCompound::new();
+        registries.insert("minecraft:blocks", blocks);
+        registries.insert("minecraft:items", items);
+        let mut fml = NbtCompound::new();
+        fml.insert("Registries", registries);
+        let ids = ModdedItemIds::from_forge_registry(&fml);
+        assert_eq!(ids.get(4100), Some("ic2:wrench"));
+        assert_eq!(ids.get(500), None);
+
+        assert_eq!(ModdedItemIds::from_forge_registry(&NbtCompound::new()).names.len(), 0);
+    }
+
+    #[test]
+    fn legacy_potions() {
+        let potion = |name, splash| LegacyPotion { name, splash };
+        assert_eq!(legacy_potion(0), potion("minecraft:water", false));
+        assert_eq!(legacy_potion(16), potion("minecraft:awkward", false));
+        assert_eq!(legacy_potion(8193), potion("minecraft:regeneration", false));
+        // amplified, extended, and both which the game reads as neither
+        assert_eq!(legacy_potion(8229), potion("minecraft:strong_healing", false));
+        assert_eq!(legacy_potion(8258), potion("minecraft:long_swiftness", false));
+        assert_eq!(legacy_potion(8290), potion("minecraft:swiftness", false));
+        // effects which can't be amplified or extended keep their base potion
+        assert_eq!(legacy_potion(8227), potion("minecraft:fire_resistance", false));
+        assert_eq!(legacy_potion(8261), potion("minecraft:healing", false));
+        // splash
+        assert_eq!(legacy_potion(16389), potion("minecraft:healing", true));
+        assert_eq!(legacy_potion(16458), potion("minecraft:long_slowness", true));
+        // effects which don't exist
+        assert_eq!(legacy_potion(7), potion("minecraft:water", false));
+        assert_eq!(legacy_potion(16399), potion("minecraft:water", true));
+        // bits above the known ones are ignored, negative damage included
+        assert_eq!(legacy_potion(0x10000 | 5), potion("minecraft:healing", false));
+        assert_eq!(legacy_potion(-16379), potion("minecraft:healing", true));
+        assert_eq!(legacy_potion(i16::MIN as i32), potion("minecraft:water", false));
+    }
+}
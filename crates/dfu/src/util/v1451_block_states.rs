@@ -0,0 +1,989 @@
+use lazy_static::lazy_static;
+use quartz_nbt::NbtCompound;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+// Maps legacy block ids and metadata to the block states of 1.13, for the flattening.
+// States are the ones of the 1.13 release, including the renames and properties of the later 1.13 snapshots.
+// Properties which depend on neighbouring blocks or tile entities get their default values here.
+
+lazy_static! {
+    static ref LEGACY_BLOCKS: LegacyBlocks = create_legacy_blocks();
+}
+
+pub(crate) const COLORS: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "light_gray",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+const LEGACY_COLORS: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "silver",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+pub(crate) const WOODS: [&str; 6] = ["oak", "spruce", "birch", "jungle", "acacia", "dark_oak"];
+
+const FACINGS: [&str; 6] = ["down", "up", "north", "south", "west", "east"];
+/// Horizontal facings by their legacy horizontal index
+const HORIZONTAL_FACINGS: [&str; 4] = ["south", "west", "north", "east"];
+const RAIL_SHAPES: [&str; 10] = [
+    "north_south",
+    "east_west",
+    "ascending_east",
+    "ascending_west",
+    "ascending_north",
+    "ascending_south",
+    "south_east",
+    "south_west",
+    "north_west",
+    "north_east",
+];
+pub(crate) const DOUBLE_PLANTS: [&str; 6] = ["sunflower", "lilac", "tall_grass", "large_fern", "rose_bush", "peony"];
+
+struct LegacyBlocks {
+    /// Indexed by `id << 4 | meta`, `None` for invalid metadata
+    states: Vec<Option<NbtCompound>>,
+    /// Legacy block names without namespace to their id
+    ids: HashMap<String, i32>,
+}
+
+impl LegacyBlocks {
+    fn block(&mut self, id: i32, name: &str, state: impl Fn(i32) -> Option<NbtCompound>) {
+        self.ids.insert(name.to_string(), id);
+        for meta in 0..16 {
+            self.states[(id << 4 | meta) as usize] = state(meta);
+        }
+    }
+
+    fn simple(&mut self, id: i32, name: &'static str, new_name: &'static str) {
+        self.block(id, name, |meta| (meta == 0).then(|| state(new_name, &[])));
+    }
+
+    fn variants(&mut self, id: i32, name: &'static str, new_names: &[&str]) {
+        self.block(id, name, |meta| new_names.get(meta as usize).map(|new_name| state(new_name, &[])));
+    }
+
+    fn colored(&mut self, id: i32, name: &'static str, suffix: &str) {
+        self.block(id, name, |meta| Some(state(&format!("{}_{suffix}", COLORS[meta as usize]), &[])));
+    }
+}
+
+/// The block state for a legacy block id and metadata. Invalid metadata maps to the state of metadata 0, unknown ids to air.
+pub(crate) fn block_state(id: i32, meta: i32) -> &'static NbtCompound {
+    let states = &LEGACY_BLOCKS.states;
+    let state = |id: i32, meta: i32| states.get((id << 4 | (meta & 15)) as usize).and_then(Option::as_ref);
+    state(id, meta)
+        .or_else(|| state(id, 0))
+        .unwrap_or_else(|| states[0].as_ref().unwrap())
+}
+
+/// The id of a legacy block name, with or without namespace
+pub(crate) fn legacy_block_id(name: &str) -> Option<i32> {
+    LEGACY_BLOCKS.ids.get(name.strip_prefix("minecraft:").unwrap_or(name)).copied()
+}
+
+/// The block name of a legacy block id, as the name of its metadata 0 state
+pub(crate) fn block_name(id: i32) -> &'static str {
+    block_state(id, 0).get::<_, &str>("Name").unwrap()
+}
+
+/// Creates a block state. Property values are stored as strings, the way block state properties are serialized.
+pub(crate) fn state(name: &str, properties: &[(&str, &dyn Display)]) -> NbtCompound {
+    let mut state = NbtCompound::new();
+    state.insert("Name", format!("minecraft:{name}"));
+    if !properties.is_empty() {
+        let mut props = NbtCompound::new();
+        for (key, value) in properties {
+            props.insert(*key, value.to_string());
+        }
+        state.insert("Properties", props);
+    }
+    state
+}
+
+fn facing(meta: i32) -> Option<&'static str> {
+    FACINGS.get((meta & 7) as usize).copied()
+}
+
+fn horizontal_facing(meta: i32) -> &'static str {
+    HORIZONTAL_FACINGS[(meta & 3) as usize]
+}
+
+/// Facing of blocks attached to a wall, vertical facings default to north
+fn wall_facing(meta: i32) -> &'static str {
+    match meta & 7 {
+        2..=5 => FACINGS[(meta & 7) as usize],
+        _ => "north",
+    }
+}
+
+fn half(top: bool) -> &'static str {
+    if top {
+        "top"
+    } else {
+        "bottom"
+    }
+}
+
+fn axis(meta: i32) -> Option<&'static str> {
+    ["y", "x", "z"].get((meta >> 2 & 3) as usize).copied()
+}
+
+fn stairs(name: &'static str) -> impl Fn(i32) -> Option<NbtCompound> {
+    move |meta| {
+        Some(state(
+            name,
+            &[
+                ("facing", &["east", "west", "south", "north"][(meta & 3) as usize]),
+                ("half", &half(meta & 4 != 0)),
+                ("shape", &"straight"),
+                ("waterlogged", &false),
+            ],
+        ))
+    }
+}
+
+fn slab(name: &str, slab_type: &str) -> NbtCompound {
+    state(name, &[("type", &slab_type), ("waterlogged", &false)])
+}
+
+fn door(name: impl Into<String>) -> impl Fn(i32) -> Option<NbtCompound> {
+    let name = name.into();
+    move |meta| {
+        if meta & 8 == 0 {
+            Some(state(
+                &name,
+                &[
+                    ("facing", &["east", "south", "west", "north"][(meta & 3) as usize]),
+                    ("half", &"lower"),
+                    ("hinge", &"left"),
+                    ("open", &(meta & 4 != 0)),
+                    ("powered", &false),
+                ],
+            ))
+        } else {
+            Some(state(
+                &name,
+                &[
+                    ("facing", &"east"),
+                    ("half", &"upper"),
+                    ("hinge", &if meta & 1 != 0 { "right" } else { "left" }),
+                    ("open", &false),
+                    ("powered", &(meta & 2 != 0)),
+                ],
+            ))
+        }
+    }
+}
+
+fn trapdoor(name: &'static str) -> impl Fn(i32) -> Option<NbtCompound> {
+    move |meta| {
+        Some(state(
+            name,
+            &[
+                ("facing", &["north", "south", "west", "east"][(meta & 3) as usize]),
+                ("half", &half(meta & 8 != 0)),
+                ("open", &(meta & 4 != 0)),
+                ("powered", &false),
+                ("waterlogged", &false),
+            ],
+        ))
+    }
+}
+
+fn fence_gate(name: impl Into<String>) -> impl Fn(i32) -> Option<NbtCompound> {
+    let name = name.into();
+    move |meta| {
+        Some(state(
+            &name,
+            &[
+                ("facing", &horizontal_facing(meta)),
+                ("in_wall", &false),
+                ("open", &(meta & 4 != 0)),
+                ("powered", &(meta & 8 != 0)),
+            ],
+        ))
+    }
+}
+
+/// Fences, panes and bars, whose connections are computed from their neighbours
+fn connected(name: &str) -> NbtCompound {
+    state(
+        name,
+        &[
+            ("east", &false),
+            ("north", &false),
+            ("south", &false),
+            ("waterlogged", &false),
+            ("west", &false),
+        ],
+    )
+}
+
+fn button(name: &'static str) -> impl Fn(i32) -> Option<NbtCompound> {
+    move |meta| {
+        let (face, facing) = match meta & 7 {
+            0 => ("ceiling", "north"),
+            1 => ("wall", "east"),
+            2 => ("wall", "west"),
+            3 => ("wall", "south"),
+            4 => ("wall", "north"),
+            5 => ("floor", "north"),
+            _ => return None,
+        };
+        Some(state(name, &[("face", &face), ("facing", &facing), ("powered", &(meta & 8 != 0))]))
+    }
+}
+
+fn torch(name: &'static str, wall_name: &'static str, lit: Option<bool>) -> impl Fn(i32) -> Option<NbtCompound> {
+    move |meta| {
+        let wall = match meta {
+            1..=4 => Some(["east", "west", "south", "north"][meta as usize - 1]),
+            0 | 5 => None,
+            _ => return None,
+        };
+        Some(match (wall, lit) {
+            (Some(facing), Some(lit)) => state(wall_name, &[("facing", &facing), ("lit", &lit)]),
+            (Some(facing), None) => state(wall_name, &[("facing", &facing)]),
+            (None, Some(lit)) => state(name, &[("lit", &lit)]),
+            (None, None) => state(name, &[]),
+        })
+    }
+}
+
+fn rail(name: &'static str) -> impl Fn(i32) -> Option<NbtCompound> {
+    move |meta| {
+        let shape = RAIL_SHAPES.get((meta & 7) as usize).filter(|_| meta & 7 < 6)?;
+        Some(state(name, &[("powered", &(meta & 8 != 0)), ("shape", shape)]))
+    }
+}
+
+fn age(name: &'static str, max_age: i32) -> impl Fn(i32) -> Option<NbtCompound> {
+    move |meta| (meta <= max_age).then(|| state(name, &[("age", &meta)]))
+}
+
+fn mushroom_block(name: &'static str) -> impl Fn(i32) -> Option<NbtCompound> {
+    move |meta| {
+        // the faces showing the cap: up, down, north, south, west and east
+        let (name, sides) = match meta {
+            0 => (name, [false; 6]),
+            1 => (name, [true, false, true, false, true, false]),
+            2 => (name, [true, false, true, false, false, false]),
+            3 => (name, [true, false, true, false, false, true]),
+            4 => (name, [true, false, false, false, true, false]),
+            5 => (name, [true, false, false, false, false, false]),
+            6 => (name, [true, false, false, false, false, true]),
+            7 => (name, [true, false, false, true, true, false]),
+            8 => (name, [true, false, false, true, false, false]),
+            9 => (name, [true, false, false, true, false, true]),
+            10 => ("mushroom_stem", [false, false, true, true, true, true]),
+            14 => (name, [true; 6]),
+            15 => ("mushroom_stem", [true; 6]),
+            _ => return None,
+        };
+        Some(state(
+            name,
+            &[
+                ("down", &sides[1]),
+                ("east", &sides[5]),
+                ("north", &sides[2]),
+                ("south", &sides[3]),
+                ("up", &sides[0]),
+                ("west", &sides[4]),
+            ],
+        ))
+    }
+}
+
+fn create_legacy_blocks() -> LegacyBlocks {
+    let mut blocks = LegacyBlocks {
+        states: vec![None; 4096],
+        ids: HashMap::new(),
+    };
+    let b = &mut blocks;
+
+    b.simple(0, "air", "air");
+    b.variants(
+        1,
+        "stone",
+        &[
+            "stone",
+            "granite",
+            "polished_granite",
+            "diorite",
+            "polished_diorite",
+            "andesite",
+            "polished_andesite",
+        ],
+    );
+    b.block(2, "grass", |meta| (meta == 0).then(|| state("grass_block", &[("snowy", &false)])));
+    b.block(3, "dirt", |meta| match meta {
+        0 => Some(state("dirt", &[])),
+        1 => Some(state("coarse_dirt", &[])),
+        2 => Some(state("podzol", &[("snowy", &false)])),
+        _ => None,
+    });
+    b.simple(4, "cobblestone", "cobblestone");
+    b.block(5, "planks", |meta| {
+        WOODS.get(meta as usize).map(|wood| state(&format!("{wood}_planks"), &[]))
+    });
+    b.block(6, "sapling", |meta| {
+        WOODS
+            .get((meta & 7) as usize)
+            .map(|wood| state(&format!("{wood}_sapling"), &[("stage", &(meta >> 3))]))
+    });
+    b.simple(7, "bedrock", "bedrock");
+    b.block(8, "flowing_water", |meta| Some(state("water", &[("level", &meta)])));
+    b.block(9, "water", |meta| Some(state("water", &[("level", &meta)])));
+    b.block(10, "flowing_lava", |meta| Some(state("lava", &[("level", &meta)])));
+    b.block(11, "lava", |meta| Some(state("lava", &[("level", &meta)])));
+    b.variants(12, "sand", &["sand", "red_sand"]);
+    b.simple(13, "gravel", "gravel");
+    b.simple(14, "gold_ore", "gold_ore");
+    b.simple(15, "iron_ore", "iron_ore");
+    b.simple(16, "coal_ore", "coal_ore");
+    b.block(17, "log", |meta| log(WOODS[(meta & 3) as usize], meta));
+    b.block(18, "leaves", |meta| Some(leaves(WOODS[(meta & 3) as usize], meta)));
+    b.variants(19, "sponge", &["sponge", "wet_sponge"]);
+    b.simple(20, "glass", "glass");
+    b.simple(21, "lapis_ore", "lapis_ore");
+    b.simple(22, "lapis_block", "lapis_block");
+    b.block(23, "dispenser", |meta| {
+        Some(state("dispenser", &[("facing", &facing(meta)?), ("triggered", &(meta & 8 != 0))]))
+    });
+    b.variants(24, "sandstone", &["sandstone", "chiseled_sandstone", "cut_sandstone"]);
+    b.block(25, "noteblock", |meta| {
+        (meta == 0).then(|| state("note_block", &[("instrument", &"harp"), ("note", &0), ("powered", &false)]))
+    });
+    b.block(26, "bed", |meta| {
+        Some(state(
+            "red_bed",
+            &[
+                ("facing", &horizontal_facing(meta)),
+                ("occupied", &(meta & 4 != 0)),
+                ("part", &if meta & 8 != 0 { "head" } else { "foot" }),
+            ],
+        ))
+    });
+    b.block(27, "golden_rail", rail("powered_rail"));
+    b.block(28, "detector_rail", rail("detector_rail"));
+    b.block(29, "sticky_piston", |meta| {
+        Some(state(
+            "sticky_piston",
+            &[("extended", &(meta & 8 != 0)), ("facing", &facing(meta)?)],
+        ))
+    });
+    b.simple(30, "web", "cobweb");
+    b.variants(31, "tallgrass", &["dead_bush", "grass", "fern"]);
+    b.simple(32, "deadbush", "dead_bush");
+    b.block(33, "piston", |meta| {
+        Some(state("piston", &[("extended", &(meta & 8 != 0)), ("facing", &facing(meta)?)]))
+    });
+    b.block(34, "piston_head", |meta| {
+        Some(state(
+            "piston_head",
+            &[
+                ("facing", &facing(meta)?),
+                ("short", &false),
+                ("type", &if meta & 8 != 0 { "sticky" } else { "normal" }),
+            ],
+        ))
+    });
+    b.colored(35, "wool", "wool");
+    b.block(36, "piston_extension", |meta| {
+        Some(state(
+            "moving_piston",
+            &[
+                ("facing", &facing(meta)?),
+                ("type", &if meta & 8 != 0 { "sticky" } else { "normal" }),
+            ],
+        ))
+    });
+    b.simple(37, "yellow_flower", "dandelion");
+    b.variants(
+        38,
+        "red_flower",
+        &[
+            "poppy",
+            "blue_orchid",
+            "allium",
+            "azure_bluet",
+            "red_tulip",
+            "orange_tulip",
+            "white_tulip",
+            "pink_tulip",
+            "oxeye_daisy",
+        ],
+    );
+    b.simple(39, "brown_mushroom", "brown_mushroom");
+    b.simple(40, "red_mushroom", "red_mushroom");
+    b.simple(41, "gold_block", "gold_block");
+    b.simple(42, "iron_block", "iron_block");
+    const STONE_SLABS: [&str; 8] = [
+        "stone_slab",
+        "sandstone_slab",
+        "petrified_oak_slab",
+        "cobblestone_slab",
+        "brick_slab",
+        "stone_brick_slab",
+        "nether_brick_slab",
+        "quartz_slab",
+    ];
+    b.block(43, "double_stone_slab", |meta| match meta {
+        8 => Some(state("smooth_stone", &[])),
+        9 => Some(state("smooth_sandstone", &[])),
+        15 => Some(state("smooth_quartz", &[])),
+        _ => Some(slab(STONE_SLABS[(meta & 7) as usize], "double")),
+    });
+    b.block(44, "stone_slab", |meta| {
+        Some(slab(STONE_SLABS[(meta & 7) as usize], half(meta & 8 != 0)))
+    });
+    b.simple(45, "brick_block", "bricks");
+    b.block(46, "tnt", |meta| (meta < 2).then(|| state("tnt", &[("unstable", &(meta == 1))])));
+    b.simple(47, "bookshelf", "bookshelf");
+    b.simple(48, "mossy_cobblestone", "mossy_cobblestone");
+    b.simple(49, "obsidian", "obsidian");
+    b.block(50, "torch", torch("torch", "wall_torch", None));
+    b.block(51, "fire", |meta| {
+        Some(state(
+            "fire",
+            &[
+                ("age", &meta),
+                ("east", &false),
+                ("north", &false),
+                ("south", &false),
+                ("up", &false),
+                ("west", &false),
+            ],
+        ))
+    });
+    b.simple(52, "mob_spawner", "spawner");
+    b.block(53, "oak_stairs", stairs("oak_stairs"));
+    b.block(54, "chest", |meta| {
+        Some(state(
+            "chest",
+            &[("facing", &wall_facing(meta)), ("type", &"single"), ("waterlogged", &false)],
+        ))
+    });
+    b.block(55, "redstone_wire", |meta| {
+        Some(state(
+            "redstone_wire",
+            &[
+                ("east", &"none"),
+                ("north", &"none"),
+                ("power", &meta),
+                ("south", &"none"),
+                ("west", &"none"),
+            ],
+        ))
+    });
+    b.simple(56, "diamond_ore", "diamond_ore");
+    b.simple(57, "diamond_block", "diamond_block");
+    b.simple(58, "crafting_table", "crafting_table");
+    b.block(59, "wheat", age("wheat", 7));
+    b.block(60, "farmland", |meta| (meta < 8).then(|| state("farmland", &[("moisture", &meta)])));
+    b.block(61, "furnace", |meta| {
+        Some(state("furnace", &[("facing", &wall_facing(meta)), ("lit", &false)]))
+    });
+    b.block(62, "lit_furnace", |meta| {
+        Some(state("furnace", &[("facing", &wall_facing(meta)), ("lit", &true)]))
+    });
+    b.block(63, "standing_sign", |meta| {
+        Some(state("sign", &[("rotation", &meta), ("waterlogged", &false)]))
+    });
+    b.block(64, "wooden_door", door("oak_door"));
+    b.block(65, "ladder", |meta| {
+        Some(state("ladder", &[("facing", &wall_facing(meta)), ("waterlogged", &false)]))
+    });
+    b.block(66, "rail", |meta| {
+        RAIL_SHAPES.get(meta as usize).map(|shape| state("rail", &[("shape", shape)]))
+    });
+    b.block(67, "stone_stairs", stairs("cobblestone_stairs"));
+    b.block(68, "wall_sign", |meta| {
+        Some(state("wall_sign", &[("facing", &wall_facing(meta)), ("waterlogged", &false)]))
+    });
+    b.block(69, "lever", |meta| {
+        let (face, facing) = match meta & 7 {
+            0 => ("ceiling", "west"),
+            1 => ("wall", "east"),
+            2 => ("wall", "west"),
+            3 => ("wall", "south"),
+            4 => ("wall", "north"),
+            5 => ("floor", "north"),
+            6 => ("floor", "west"),
+            _ => ("ceiling", "north"),
+        };
+        Some(state(
+            "lever",
+            &[("face", &face), ("facing", &facing), ("powered", &(meta & 8 != 0))],
+        ))
+    });
+    b.block(70, "stone_pressure_plate", |meta| {
+        (meta < 2).then(|| state("stone_pressure_plate", &[("powered", &(meta == 1))]))
+    });
+    b.block(71, "iron_door", door("iron_door"));
+    b.block(72, "wooden_pressure_plate", |meta| {
+        (meta < 2).then(|| state("oak_pressure_plate", &[("powered", &(meta == 1))]))
+    });
+    b.block(73, "redstone_ore", |meta| {
+        (meta == 0).then(|| state("redstone_ore", &[("lit", &false)]))
+    });
+    b.block(74, "lit_redstone_ore", |meta| {
+        (meta == 0).then(|| state("redstone_ore", &[("lit", &true)]))
+    });
+    b.block(
+        75,
+        "unlit_redstone_torch",
+        torch("redstone_torch", "redstone_wall_torch", Some(false)),
+    );
+    b.block(76, "redstone_torch", torch("redstone_torch", "redstone_wall_torch", Some(true)));
+    b.block(77, "stone_button", button("stone_button"));
+    b.block(78, "snow_layer", |meta| {
+        (meta < 8).then(|| state("snow", &[("layers", &(meta + 1))]))
+    });
+    b.simple(79, "ice", "ice");
+    b.simple(80, "snow", "snow_block");
+    b.block(81, "cactus", |meta| Some(state("cactus", &[("age", &meta)])));
+    b.simple(82, "clay", "clay");
+    b.block(83, "reeds", |meta| Some(state("sugar_cane", &[("age", &meta)])));
+    b.block(84, "jukebox", |meta| {
+        (meta < 2).then(|| state("jukebox", &[("has_record", &(meta == 1))]))
+    });
+    b.block(85, "fence", |meta| (meta == 0).then(|| connected("oak_fence")));
+    b.block(86, "pumpkin", |meta| {
+        Some(state("carved_pumpkin", &[("facing", &horizontal_facing(meta))]))
+    });
+    b.simple(87, "netherrack", "netherrack");
+    b.simple(88, "soul_sand", "soul_sand");
+    b.simple(89, "glowstone", "glowstone");
+    b.block(90, "portal", |meta| {
+        (meta < 3).then(|| state("nether_portal", &[("axis", &if meta == 2 { "z" } else { "x" })]))
+    });
+    b.block(91, "lit_pumpkin", |meta| {
+        Some(state("jack_o_lantern", &[("facing", &horizontal_facing(meta))]))
+    });
+    b.block(92, "cake", |meta| (meta < 7).then(|| state("cake", &[("bites", &meta)])));
+    for (id, name, powered) in [(93, "unpowered_repeater", false), (94, "powered_repeater", true)] {
+        b.block(id, name, |meta| {
+            Some(state(
+                "repeater",
+                &[
+                    ("delay", &((meta >> 2) + 1)),
+                    ("facing", &horizontal_facing(meta)),
+                    ("locked", &false),
+                    ("powered", &powered),
+                ],
+            ))
+        });
+    }
+    b.colored(95, "stained_glass", "stained_glass");
+    b.block(96, "trapdoor", trapdoor("oak_trapdoor"));
+    b.variants(
+        97,
+        "monster_egg",
+        &[
+            "infested_stone",
+            "infested_cobblestone",
+            "infested_stone_bricks",
+            "infested_mossy_stone_bricks",
+            "infested_cracked_stone_bricks",
+            "infested_chiseled_stone_bricks",
+        ],
+    );
+    b.variants(
+        98,
+        "stonebrick",
+        &[
+            "stone_bricks",
+            "mossy_stone_bricks",
+            "cracked_stone_bricks",
+            "chiseled_stone_bricks",
+        ],
+    );
+    b.block(99, "brown_mushroom_block", mushroom_block("brown_mushroom_block"));
+    b.block(100, "red_mushroom_block", mushroom_block("red_mushroom_block"));
+    b.block(101, "iron_bars", |meta| (meta == 0).then(|| connected("iron_bars")));
+    b.block(102, "glass_pane", |meta| (meta == 0).then(|| connected("glass_pane")));
+    b.simple(103, "melon_block", "melon");
+    b.block(104, "pumpkin_stem", age("pumpkin_stem", 7));
+    b.block(105, "melon_stem", age("melon_stem", 7));
+    b.block(106, "vine", |meta| {
+        Some(state(
+            "vine",
+            &[
+                ("east", &(meta & 8 != 0)),
+                ("north", &(meta & 4 != 0)),
+                ("south", &(meta & 1 != 0)),
+                ("up", &false),
+                ("west", &(meta & 2 != 0)),
+            ],
+        ))
+    });
+    b.block(107, "fence_gate", fence_gate("oak_fence_gate"));
+    b.block(108, "brick_stairs", stairs("brick_stairs"));
+    b.block(109, "stone_brick_stairs", stairs("stone_brick_stairs"));
+    b.block(110, "mycelium", |meta| (meta == 0).then(|| state("mycelium", &[("snowy", &false)])));
+    b.simple(111, "waterlily", "lily_pad");
+    b.simple(112, "nether_brick", "nether_bricks");
+    b.block(113, "nether_brick_fence", |meta| {
+        (meta == 0).then(|| connected("nether_brick_fence"))
+    });
+    b.block(114, "nether_brick_stairs", stairs("nether_brick_stairs"));
+    b.block(115, "nether_wart", age("nether_wart", 3));
+    b.simple(116, "enchanting_table", "enchanting_table");
+    b.block(117, "brewing_stand", |meta| {
+        (meta < 8).then(|| {
+            state(
+                "brewing_stand",
+                &[
+                    ("has_bottle_0", &(meta & 1 != 0)),
+                    ("has_bottle_1", &(meta & 2 != 0)),
+                    ("has_bottle_2", &(meta & 4 != 0)),
+                ],
+            )
+        })
+    });
+    b.block(118, "cauldron", |meta| (meta < 4).then(|| state("cauldron", &[("level", &meta)])));
+    b.simple(119, "end_portal", "end_portal");
+    b.block(120, "end_portal_frame", |meta| {
+        (meta < 8).then(|| {
+            state(
+                "end_portal_frame",
+                &[("eye", &(meta & 4 != 0)), ("facing", &horizontal_facing(meta))],
+            )
+        })
+    });
+    b.simple(121, "end_stone", "end_stone");
+    b.simple(122, "dragon_egg", "dragon_egg");
+    b.block(123, "redstone_lamp", |meta| {
+        (meta == 0).then(|| state("redstone_lamp", &[("lit", &false)]))
+    });
+    b.block(124, "lit_redstone_lamp", |meta| {
+        (meta == 0).then(|| state("redstone_lamp", &[("lit", &true)]))
+    });
+    b.block(125, "double_wooden_slab", |meta| {
+        WOODS.get(meta as usize).map(|wood| slab(&format!("{wood}_slab"), "double"))
+    });
+    b.block(126, "wooden_slab", |meta| {
+        WOODS
+            .get((meta & 7) as usize)
+            .map(|wood| slab(&format!("{wood}_slab"), half(meta & 8 != 0)))
+    });
+    b.block(127, "cocoa", |meta| {
+        (meta < 12).then(|| state("cocoa", &[("age", &(meta >> 2)), ("facing", &horizontal_facing(meta))]))
+    });
+    b.block(128, "sandstone_stairs", stairs("sandstone_stairs"));
+    b.simple(129, "emerald_ore", "emerald_ore");
+    b.block(130, "ender_chest", |meta| {
+        Some(state("ender_chest", &[("facing", &wall_facing(meta)), ("waterlogged", &false)]))
+    });
+    b.block(131, "tripwire_hook", |meta| {
+        Some(state(
+            "tripwire_hook",
+            &[
+                ("attached", &(meta & 4 != 0)),
+                ("facing", &horizontal_facing(meta)),
+                ("powered", &(meta & 8 != 0)),
+            ],
+        ))
+    });
+    b.block(132, "tripwire", |meta| {
+        Some(state(
+            "tripwire",
+            &[
+                ("attached", &(meta & 4 != 0)),
+                ("disarmed", &(meta & 8 != 0)),
+                ("east", &false),
+                ("north", &false),
+                ("powered", &(meta & 1 != 0)),
+                ("south", &false),
+                ("west", &false),
+            ],
+        ))
+    });
+    b.simple(133, "emerald_block", "emerald_block");
+    b.block(134, "spruce_stairs", stairs("spruce_stairs"));
+    b.block(135, "birch_stairs", stairs("birch_stairs"));
+    b.block(136, "jungle_stairs", stairs("jungle_stairs"));
+    for (id, name, new_name) in [
+        (137, "command_block", "command_block"),
+        (210, "repeating_command_block", "repeating_command_block"),
+        (211, "chain_command_block", "chain_command_block"),
+    ] {
+        b.block(id, name, |meta| {
+            Some(state(new_name, &[("conditional", &(meta & 8 != 0)), ("facing", &facing(meta)?)]))
+        });
+    }
+    b.simple(138, "beacon", "beacon");
+    b.block(139, "cobblestone_wall", |meta| {
+        ["cobblestone_wall", "mossy_cobblestone_wall"].get(meta as usize).map(|name| {
+            state(
+                name,
+                &[
+                    ("east", &false),
+                    ("north", &false),
+                    ("south", &false),
+                    ("up", &true),
+                    ("waterlogged", &false),
+                    ("west", &false),
+                ],
+            )
+        })
+    });
+    b.block(140, "flower_pot", |_| Some(state("flower_pot", &[])));
+    b.block(141, "carrots", age("carrots", 7));
+    b.block(142, "potatoes", age("potatoes", 7));
+    b.block(143, "wooden_button", button("oak_button"));
+    b.block(144, "skull", |meta| match meta & 7 {
+        0 | 1 => Some(state("skeleton_skull", &[("rotation", &0)])),
+        2..=5 => Some(state("skeleton_wall_skull", &[("facing", &wall_facing(meta))])),
+        _ => None,
+    });
+    b.block(145, "anvil", |meta| {
+        ["anvil", "chipped_anvil", "damaged_anvil"]
+            .get((meta >> 2) as usize)
+            .map(|name| state(name, &[("facing", &horizontal_facing(meta))]))
+    });
+    b.block(146, "trapped_chest", |meta| {
+        Some(state(
+            "trapped_chest",
+            &[("facing", &wall_facing(meta)), ("type", &"single"), ("waterlogged", &false)],
+        ))
+    });
+    b.block(147, "light_weighted_pressure_plate", |meta| {
+        Some(state("light_weighted_pressure_plate", &[("power", &meta)]))
+    });
+    b.block(148, "heavy_weighted_pressure_plate", |meta| {
+        Some(state("heavy_weighted_pressure_plate", &[("power", &meta)]))
+    });
+    for (id, name) in [(149, "unpowered_comparator"), (150, "powered_comparator")] {
+        b.block(id, name, |meta| {
+            Some(state(
+                "comparator",
+                &[
+                    ("facing", &horizontal_facing(meta)),
+                    ("mode", &if meta & 4 != 0 { "subtract" } else { "compare" }),
+                    ("powered", &(meta & 8 != 0)),
+                ],
+            ))
+        });
+    }
+    b.block(151, "daylight_detector", |meta| {
+        Some(state("daylight_detector", &[("inverted", &false), ("power", &meta)]))
+    });
+    b.simple(152, "redstone_block", "redstone_block");
+    b.simple(153, "quartz_ore", "nether_quartz_ore");
+    b.block(154, "hopper", |meta| {
+        let facing = match meta & 7 {
+            0 | 1 => "down",
+            2..=5 => FACINGS[(meta & 7) as usize],
+            _ => return None,
+        };
+        Some(state("hopper", &[("enabled", &(meta & 8 == 0)), ("facing", &facing)]))
+    });
+    b.block(155, "quartz_block", |meta| match meta {
+        0 => Some(state("quartz_block", &[])),
+        1 => Some(state("chiseled_quartz_block", &[])),
+        2..=4 => Some(state("quartz_pillar", &[("axis", &["y", "x", "z"][meta as usize - 2])])),
+        _ => None,
+    });
+    b.block(156, "quartz_stairs", stairs("quartz_stairs"));
+    b.block(157, "activator_rail", rail("activator_rail"));
+    b.block(158, "dropper", |meta| {
+        Some(state("dropper", &[("facing", &facing(meta)?), ("triggered", &(meta & 8 != 0))]))
+    });
+    b.colored(159, "stained_hardened_clay", "terracotta");
+    b.block(160, "stained_glass_pane", |meta| {
+        Some(connected(&format!("{}_stained_glass_pane", COLORS[meta as usize])))
+    });
+    b.block(161, "leaves2", |meta| {
+        (meta & 3 < 2).then(|| leaves(WOODS[(meta & 3) as usize + 4], meta))
+    });
+    b.block(162, "log2", |meta| {
+        (meta & 3 < 2).then(|| log(WOODS[(meta & 3) as usize + 4], meta)).flatten()
+    });
+    b.block(163, "acacia_stairs", stairs("acacia_stairs"));
+    b.block(164, "dark_oak_stairs", stairs("dark_oak_stairs"));
+    b.simple(165, "slime", "slime_block");
+    b.simple(166, "barrier", "barrier");
+    b.block(167, "iron_trapdoor", trapdoor("iron_trapdoor"));
+    b.variants(168, "prismarine", &["prismarine", "prismarine_bricks", "dark_prismarine"]);
+    b.simple(169, "sea_lantern", "sea_lantern");
+    b.block(170, "hay_block", |meta| Some(state("hay_block", &[("axis", &axis(meta)?)])));
+    b.colored(171, "carpet", "carpet");
+    b.simple(172, "hardened_clay", "terracotta");
+    b.simple(173, "coal_block", "coal_block");
+    b.simple(174, "packed_ice", "packed_ice");
+    b.block(175, "double_plant", |meta| {
+        if meta & 8 != 0 {
+            Some(state(DOUBLE_PLANTS[0], &[("half", &"upper")]))
+        } else {
+            DOUBLE_PLANTS.get(meta as usize).map(|name| state(name, &[("half", &"lower")]))
+        }
+    });
+    b.block(176, "standing_banner", |meta| Some(state("white_banner", &[("rotation", &meta)])));
+    b.block(177, "wall_banner", |meta| {
+        Some(state("white_wall_banner", &[("facing", &wall_facing(meta))]))
+    });
+    b.block(178, "daylight_detector_inverted", |meta| {
+        Some(state("daylight_detector", &[("inverted", &true), ("power", &meta)]))
+    });
+    b.variants(
+        179,
+        "red_sandstone",
+        &["red_sandstone", "chiseled_red_sandstone", "cut_red_sandstone"],
+    );
+    b.block(180, "red_sandstone_stairs", stairs("red_sandstone_stairs"));
+    b.block(181, "double_stone_slab2", |meta| match meta {
+        0 => Some(slab("red_sandstone_slab", "double")),
+        8 => Some(state("smooth_red_sandstone", &[])),
+        _ => None,
+    });
+    b.block(182, "stone_slab2", |meta| {
+        (meta & 7 == 0).then(|| slab("red_sandstone_slab", half(meta & 8 != 0)))
+    });
+    for (idx, wood) in ["spruce", "birch", "jungle", "dark_oak", "acacia"].into_iter().enumerate() {
+        let fence_gate_name = format!("{wood}_fence_gate");
+        b.block(183 + idx as i32, &fence_gate_name, fence_gate(fence_gate_name.clone()));
+        let fence = format!("{wood}_fence");
+        b.block(188 + idx as i32, &fence, |meta| (meta == 0).then(|| connected(&fence)));
+    }
+    for (idx, wood) in ["spruce", "birch", "jungle", "acacia", "dark_oak"].into_iter().enumerate() {
+        let door_name = format!("{wood}_door");
+        b.block(193 + idx as i32, &door_name, door(door_name.clone()));
+    }
+    b.block(198, "end_rod", |meta| Some(state("end_rod", &[("facing", &facing(meta)?)])));
+    b.block(199, "chorus_plant", |meta| {
+        (meta == 0).then(|| {
+            state(
+                "chorus_plant",
+                &[
+                    ("down", &false),
+                    ("east", &false),
+                    ("north", &false),
+                    ("south", &false),
+                    ("up", &false),
+                    ("west", &false),
+                ],
+            )
+        })
+    });
+    b.block(200, "chorus_flower", age("chorus_flower", 5));
+    b.simple(201, "purpur_block", "purpur_block");
+    b.block(202, "purpur_pillar", |meta| Some(state("purpur_pillar", &[("axis", &axis(meta)?)])));
+    b.block(203, "purpur_stairs", stairs("purpur_stairs"));
+    b.block(204, "purpur_double_slab", |meta| (meta == 0).then(|| slab("purpur_slab", "double")));
+    b.block(205, "purpur_slab", |meta| {
+        (meta & 7 == 0).then(|| slab("purpur_slab", half(meta & 8 != 0)))
+    });
+    b.simple(206, "end_bricks", "end_stone_bricks");
+    b.block(207, "beetroots", age("beetroots", 3));
+    b.simple(208, "grass_path", "grass_path");
+    b.simple(209, "end_gateway", "end_gateway");
+    b.block(212, "frosted_ice", age("frosted_ice", 3));
+    b.simple(213, "magma", "magma_block");
+    b.simple(214, "nether_wart_block", "nether_wart_block");
+    b.simple(215, "red_nether_brick", "red_nether_bricks");
+    b.block(216, "bone_block", |meta| Some(state("bone_block", &[("axis", &axis(meta)?)])));
+    b.simple(217, "structure_void", "structure_void");
+    b.block(218, "observer", |meta| {
+        Some(state("observer", &[("facing", &facing(meta)?), ("powered", &(meta & 8 != 0))]))
+    });
+    for (idx, (color, legacy_color)) in COLORS.into_iter().zip(LEGACY_COLORS).enumerate() {
+        let shulker_box = format!("{color}_shulker_box");
+        b.block(219 + idx as i32, &format!("{legacy_color}_shulker_box"), |meta| {
+            Some(state(&shulker_box, &[("facing", &facing(meta)?)]))
+        });
+        let glazed_terracotta = format!("{color}_glazed_terracotta");
+        b.block(235 + idx as i32, &format!("{legacy_color}_glazed_terracotta"), |meta| {
+            Some(state(&glazed_terracotta, &[("facing", &horizontal_facing(meta))]))
+        });
+    }
+    b.colored(251, "concrete", "concrete");
+    b.colored(252, "concrete_powder", "concrete_powder");
+    b.block(255, "structure_block", |meta| {
+        ["save", "load", "corner", "data"]
+            .get(meta as usize)
+            .map(|mode| state("structure_block", &[("mode", mode)]))
+    });
+
+    blocks
+}
+
+fn log(wood: &str, meta: i32) -> Option<NbtCompound> {
+    Some(match axis(meta) {
+        Some(axis) => state(&format!("{wood}_log"), &[("axis", &axis)]),
+        None => state(&format!("{wood}_wood"), &[("axis", &"y")]),
+    })
+}
+
+/// The distance to the nearest log is computed by the game, leaves start out as if they were next to one
+fn leaves(wood: &str, meta: i32) -> NbtCompound {
+    state(&format!("{wood}_leaves"), &[("distance", &1), ("persistent", &(meta & 4 != 0))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{block_name, block_state, legacy_block_id, state};
+
+    #[test]
+    fn legacy_states() {
+        assert_eq!(block_state(1, 3), &state("diorite", &[]));
+        assert_eq!(block_state(17, 5), &state("spruce_log", &[("axis", &"x")]));
+        assert_eq!(block_state(162, 13), &state("dark_oak_wood", &[("axis", &"y")]));
+        assert_eq!(block_state(35, 8), &state("light_gray_wool", &[]));
+        assert_eq!(
+            block_state(53, 6),
+            &state(
+                "oak_stairs",
+                &[
+                    ("facing", &"south"),
+                    ("half", &"top"),
+                    ("shape", &"straight"),
+                    ("waterlogged", &false)
+                ]
+            )
+        );
+        assert_eq!(block_state(227, 1), &state("light_gray_shulker_box", &[("facing", &"up")]));
+        // invalid metadata falls back to metadata 0, unknown blocks to air
+        assert_eq!(block_state(1, 9), &state("stone", &[]));
+        assert_eq!(block_state(253, 0), &state("air", &[]));
+    }
+
+    #[test]
+    fn legacy_names() {
+        assert_eq!(legacy_block_id("minecraft:stained_hardened_clay"), Some(159));
+        assert_eq!(legacy_block_id("silver_glazed_terracotta"), Some(243));
+        assert_eq!(legacy_block_id("minecraft:dark_oak_fence_gate"), Some(186));
+        assert_eq!(legacy_block_id("minecraft:dark_oak_door"), Some(197));
+        assert_eq!(legacy_block_id("minecraft:granite"), None);
+        assert_eq!(block_name(2), "minecraft:grass_block");
+        assert_eq!(block_name(31), "minecraft:dead_bush");
+    }
+}
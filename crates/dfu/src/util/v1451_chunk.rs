@@ -0,0 +1,455 @@
+use crate::util::fix;
+use crate::util::v102_item_name::ITEM_NAMES;
+use crate::util::v1451_block_states::{block_state, state, COLORS, DOUBLE_PLANTS, WOODS};
+use crate::util::v1451_item_names::SKULLS;
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+use std::collections::{HashMap, HashSet};
+
+// This class converts the legacy block ids, metadata and tile entities of chunk sections to the palettes of the flattening.
+// Properties the game recomputes from neighbouring blocks are left at their defaults, those blocks are listed in UpgradeData.
+
+const GRASS: u16 = 2;
+const DIRT: u16 = 3;
+const NOTE_BLOCK: u16 = 25;
+const BED: u16 = 26;
+const SNOW_LAYER: u16 = 78;
+const SNOW: u16 = 80;
+const MYCELIUM: u16 = 110;
+const FLOWER_POT: u16 = 140;
+const SKULL: u16 = 144;
+const DOUBLE_PLANT: u16 = 175;
+const STANDING_BANNER: u16 = 176;
+const WALL_BANNER: u16 = 177;
+const PODZOL_META: u16 = 2;
+
+const WALL_SKULLS: [&str; 6] = [
+    "skeleton_wall_skull",
+    "wither_skeleton_wall_skull",
+    "zombie_wall_head",
+    "player_wall_head",
+    "creeper_wall_head",
+    "dragon_wall_head",
+];
+const FLOWERS: [&str; 9] = [
+    "poppy",
+    "blue_orchid",
+    "allium",
+    "azure_bluet",
+    "red_tulip",
+    "orange_tulip",
+    "white_tulip",
+    "pink_tulip",
+    "oxeye_daisy",
+];
+
+/// Legacy blocks stored as `id << 4 | meta`, by section y
+type LegacyBlocks = HashMap<i32, Vec<u16>>;
+/// Tile entities by their position within the chunk
+type TileEntities = HashMap<(i32, i32, i32), NbtCompound>;
+
+/// Replaces `Blocks`, `Add` and `Data` of every section in `level` with a `Palette` and `BlockStates`.
+/// Tile entities which became part of the block state are removed.
+pub(crate) fn flatten_sections(level: &mut NbtCompound) {
+    let mut sections = match fix::remove(level, "Sections") {
+        Some(NbtTag::List(sections)) => sections,
+        Some(sections) => {
+            level.insert("Sections", sections);
+            return;
+        }
+        None => return,
+    };
+
+    // read all sections first, so that blocks can look at their neighbours in other sections
+    let mut legacy_blocks = LegacyBlocks::new();
+    for section in sections.iter() {
+        if let NbtTag::Compound(section) = section {
+            if let Some(blocks) = read_legacy_blocks(section) {
+                legacy_blocks.insert(fix::get_int(section, "Y").unwrap_or(0), blocks);
+            }
+        }
+    }
+    let tile_entities = read_tile_entities(level);
+
+    let mut removed_tile_entities = HashSet::new();
+    let mut upgrade_indices = NbtCompound::new();
+    for section in sections.iter_mut() {
+        let section = match fix::compound(section) {
+            Some(section) => section,
+            None => continue,
+        };
+        let section_y = fix::get_int(section, "Y").unwrap_or(0);
+        let blocks = match legacy_blocks.get(&section_y) {
+            Some(blocks) => blocks,
+            None => continue,
+        };
+
+        let mut palette = Palette::default();
+        let mut states = Vec::with_capacity(blocks.len());
+        let mut upgrades = Vec::new();
+        for (idx, block) in blocks.iter().enumerate() {
+            let pos = ((idx & 15) as i32, (section_y << 4) + (idx >> 8) as i32, (idx >> 4 & 15) as i32);
+            let tile_entity = tile_entities.get(&pos);
+            let state = match special_state(*block, pos, &legacy_blocks, tile_entity) {
+                Some(state) => palette.state(state),
+                None => palette.legacy(*block),
+            };
+            states.push(state);
+
+            if matches!(*block >> 4, NOTE_BLOCK | FLOWER_POT) && tile_entity.is_some() {
+                removed_tile_entities.insert(pos);
+            }
+            if needs_shape_update(*block >> 4) {
+                upgrades.push(idx as i32);
+            }
+        }
+
+        fix::remove(section, "Blocks");
+        fix::remove(section, "Data");
+        fix::remove(section, "Add");
+        section.insert("BlockStates", NbtTag::LongArray(pack(&states, palette.bits())));
+        section.insert("Palette", palette.into_list());
+        if !upgrades.is_empty() {
+            upgrade_indices.insert(section_y.to_string(), upgrades);
+        }
+    }
+    level.insert("Sections", sections);
+
+    if !removed_tile_entities.is_empty() {
+        if let Ok(list) = level.get_mut::<_, &mut NbtList>("TileEntities") {
+            let remaining = list
+                .iter()
+                .filter(|tile_entity| match tile_entity {
+                    NbtTag::Compound(tile_entity) => position(tile_entity).is_none_or(|pos| !removed_tile_entities.contains(&pos)),
+                    _ => true,
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            *list = NbtList::from(remaining);
+        }
+    }
+    if !upgrade_indices.is_empty() {
+        let mut upgrade_data = NbtCompound::new();
+        upgrade_data.insert("Indices", upgrade_indices);
+        level.insert("UpgradeData", upgrade_data);
+    }
+}
+
+#[derive(Default)]
+struct Palette {
+    states: Vec<NbtCompound>,
+    legacy: HashMap<u16, usize>,
+}
+
+impl Palette {
+    fn legacy(&mut self, block: u16) -> usize {
+        if let Some(idx) = self.legacy.get(&block) {
+            return *idx;
+        }
+        let idx = self.state(block_state((block >> 4) as i32, (block & 15) as i32).clone());
+        self.legacy.insert(block, idx);
+        idx
+    }
+
+    fn state(&mut self, state: NbtCompound) -> usize {
+        // air is always the first palette entry
+        if self.states.is_empty() && state != *block_state(0, 0) {
+            self.states.push(block_state(0, 0).clone());
+        }
+        match self.states.iter().position(|existing| *existing == state) {
+            Some(idx) => idx,
+            None => {
+                self.states.push(state);
+                self.states.len() - 1
+            }
+        }
+    }
+
+    /// Bits per block of the packed states, at least 4
+    fn bits(&self) -> u32 {
+        let max = self.states.len().saturating_sub(1) as u64;
+        (u64::BITS - max.leading_zeros()).max(4)
+    }
+
+    fn into_list(self) -> NbtList {
+        NbtList::from(self.states.into_iter().map(NbtTag::Compound).collect::<Vec<_>>())
+    }
+}
+
+/// Packs palette indices tightly into longs, values may span two longs
+fn pack(states: &[usize], bits: u32) -> Vec<i64> {
+    let mut longs = vec![0u64; (states.len() * bits as usize).div_ceil(64)];
+    for (idx, state) in states.iter().enumerate() {
+        let bit = idx * bits as usize;
+        let (long, offset) = (bit / 64, bit % 64);
+        longs[long] |= (*state as u64) << offset;
+        if offset + bits as usize > 64 {
+            longs[long + 1] |= (*state as u64) >> (64 - offset);
+        }
+    }
+    longs.into_iter().map(|long| long as i64).collect()
+}
+
+fn read_legacy_blocks(section: &NbtCompound) -> Option<Vec<u16>> {
+    let blocks = section.get::<_, &[i8]>("Blocks").ok()?;
+    let data = section.get::<_, &[i8]>("Data").ok();
+    let add = section.get::<_, &[i8]>("Add").ok();
+    let nibble = |array: Option<&[i8]>, idx: usize| {
+        array
+            .and_then(|array| array.get(idx >> 1))
+            .map_or(0, |value| (*value as u8 >> ((idx & 1) * 4)) as u16 & 15)
+    };
+    Some(
+        (0..4096)
+            .map(|idx| {
+                let id = blocks.get(idx).map_or(0, |block| *block as u8 as u16) | nibble(add, idx) << 8;
+                id << 4 | nibble(data, idx)
+            })
+            .collect(),
+    )
+}
+
+fn read_tile_entities(level: &NbtCompound) -> TileEntities {
+    level
+        .get::<_, &NbtList>("TileEntities")
+        .into_iter()
+        .flat_map(|tile_entities| tile_entities.iter())
+        .filter_map(|tile_entity| match tile_entity {
+            NbtTag::Compound(tile_entity) => Some((position(tile_entity)?, tile_entity.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The position of a tile entity within its chunk
+fn position(tile_entity: &NbtCompound) -> Option<(i32, i32, i32)> {
+    Some((
+        fix::get_int(tile_entity, "x")? & 15,
+        fix::get_int(tile_entity, "y")?,
+        fix::get_int(tile_entity, "z")? & 15,
+    ))
+}
+
+fn block_at(legacy_blocks: &LegacyBlocks, (x, y, z): (i32, i32, i32)) -> Option<u16> {
+    let blocks = legacy_blocks.get(&(y >> 4))?;
+    blocks.get(((y & 15) << 8 | z << 4 | x) as usize).copied()
+}
+
+/// The block state of blocks which depend on their tile entity or the blocks around them
+fn special_state(
+    block: u16,
+    (x, y, z): (i32, i32, i32),
+    legacy_blocks: &LegacyBlocks,
+    tile_entity: Option<&NbtCompound>,
+) -> Option<NbtCompound> {
+    let (id, meta) = (block >> 4, block & 15);
+    let legacy_state = || block_state(id as i32, meta as i32).clone();
+    let tile_int = |key: &str| tile_entity.and_then(|tile_entity| fix::get_int(tile_entity, key));
+    match id {
+        GRASS | MYCELIUM | DIRT => {
+            if id == DIRT && meta != PODZOL_META {
+                return None;
+            }
+            let above = block_at(legacy_blocks, (x, y + 1, z)).map(|above| above >> 4);
+            matches!(above, Some(SNOW_LAYER | SNOW)).then(|| with_property(legacy_state(), "snowy", "true"))
+        }
+        NOTE_BLOCK => {
+            tile_entity?;
+            Some(state(
+                "note_block",
+                &[
+                    ("instrument", &"harp"),
+                    ("note", &tile_int("note").unwrap_or(0).clamp(0, 24)),
+                    ("powered", &(tile_int("powered").unwrap_or(0) != 0)),
+                ],
+            ))
+        }
+        BED => {
+            let color = tile_int("color").unwrap_or(14);
+            Some(renamed(legacy_state(), &format!("{}_bed", COLORS[(color & 15) as usize])))
+        }
+        STANDING_BANNER | WALL_BANNER => {
+            let color = COLORS[15 - (tile_int("Base").unwrap_or(0) & 15) as usize];
+            let suffix = if id == WALL_BANNER { "wall_banner" } else { "banner" };
+            Some(renamed(legacy_state(), &format!("{color}_{suffix}")))
+        }
+        SKULL => {
+            let skull_type = tile_int("SkullType").unwrap_or(0).clamp(0, 5) as usize;
+            if meta & 7 <= 1 {
+                Some(state(SKULLS[skull_type], &[("rotation", &(tile_int("Rot").unwrap_or(0) & 15))]))
+            } else {
+                Some(renamed(legacy_state(), WALL_SKULLS[skull_type]))
+            }
+        }
+        FLOWER_POT => Some(state(&potted_plant(tile_entity?), &[])),
+        DOUBLE_PLANT if meta & 8 != 0 => {
+            let below = block_at(legacy_blocks, (x, y - 1, z)).filter(|below| below >> 4 == DOUBLE_PLANT && below & 8 == 0)?;
+            Some(state(DOUBLE_PLANTS.get((below & 7) as usize)?, &[("half", &"upper")]))
+        }
+        64 | 71 | 193..=197 => {
+            let other = if meta & 8 == 0 { (x, y + 1, z) } else { (x, y - 1, z) };
+            let other = block_at(legacy_blocks, other).filter(|other| other >> 4 == id && (other & 8) != (meta & 8));
+            let (lower, upper) = if meta & 8 == 0 {
+                (meta, other.map_or(8, |other| other & 15))
+            } else {
+                (other.map_or(0, |other| other & 15), meta)
+            };
+            let name = block_state(id as i32, 0).get::<_, &str>("Name").ok()?;
+            Some(state(
+                name.strip_prefix("minecraft:").unwrap_or(name),
+                &[
+                    ("facing", &["east", "south", "west", "north"][(lower & 3) as usize]),
+                    ("half", &if meta & 8 == 0 { "lower" } else { "upper" }),
+                    ("hinge", &if upper & 1 != 0 { "right" } else { "left" }),
+                    ("open", &(lower & 4 != 0)),
+                    ("powered", &(upper & 2 != 0)),
+                ],
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// The potted block name for the plant stored in a flower pot tile entity
+fn potted_plant(tile_entity: &NbtCompound) -> String {
+    let item = match tile_entity.inner().get("Item") {
+        Some(NbtTag::String(item)) => item.as_str(),
+        Some(_) => fix::get_int(tile_entity, "Item")
+            .and_then(|id| ITEM_NAMES.get(&id))
+            .copied()
+            .unwrap_or("minecraft:air"),
+        None => "minecraft:air",
+    };
+    let data = fix::get_int(tile_entity, "Data").unwrap_or(0);
+    let plant = match item.strip_prefix("minecraft:").unwrap_or(item) {
+        "sapling" => WOODS.get(data as usize).map(|wood| format!("{wood}_sapling")),
+        "red_flower" => FLOWERS.get(data as usize).map(|flower| flower.to_string()),
+        "yellow_flower" => Some("dandelion".to_string()),
+        "red_mushroom" | "brown_mushroom" | "cactus" => Some(item.strip_prefix("minecraft:").unwrap_or(item).to_string()),
+        "deadbush" => Some("dead_bush".to_string()),
+        "tallgrass" if data == 2 => Some("fern".to_string()),
+        _ => None,
+    };
+    plant.map_or_else(|| "flower_pot".to_string(), |plant| format!("potted_{plant}"))
+}
+
+fn renamed(mut state: NbtCompound, name: &str) -> NbtCompound {
+    state.insert("Name", format!("minecraft:{name}"));
+    state
+}
+
+fn with_property(mut state: NbtCompound, key: &str, value: &str) -> NbtCompound {
+    if let Ok(properties) = state.get_mut::<_, &mut NbtCompound>("Properties") {
+        properties.insert(key, value);
+    }
+    state
+}
+
+/// Blocks whose properties depend on their neighbours, which the game updates when it loads the chunk
+fn needs_shape_update(id: u16) -> bool {
+    matches!(
+        id,
+        18 | 25 | 51 | 53 | 54 | 55 | 67 | 85 | 101 | 102 | 106 | 107 | 108 | 109 | 113 | 114 | 128 | 132 | 134..=136 | 139 | 146 | 156 | 160
+            | 161 | 163 | 164 | 180 | 183..=192 | 199 | 203
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flatten_sections, pack};
+    use crate::util::v1451_block_states::state;
+    use quartz_nbt::{snbt, NbtCompound, NbtList, NbtTag};
+
+    /// Reads the block state at `idx` back from a flattened section
+    fn state_at(section: &NbtCompound, idx: usize) -> NbtCompound {
+        let palette = section.get::<_, &NbtList>("Palette").unwrap();
+        let longs = section.get::<_, &[i64]>("BlockStates").unwrap();
+        let bits = longs.len() * 64 / 4096;
+        let (long, offset) = (idx * bits / 64, idx * bits % 64);
+        let mut value = longs[long] as u64 >> offset;
+        if offset + bits > 64 {
+            value |= (longs[long + 1] as u64) << (64 - offset);
+        }
+        palette.get::<&NbtCompound>(value as usize & ((1 << bits) - 1)).unwrap().clone()
+    }
+
+    #[test]
+    fn flatten_section() {
+        let mut blocks = vec![0i8; 4096];
+        let mut data = vec![0i8; 2048];
+        // snowy grass, a door with its hinge in the upper half, a blue bed, a potted allium and a fence
+        blocks[0] = 2;
+        blocks[256] = 78;
+        blocks[1] = 64;
+        data[0] = 0x10;
+        blocks[257] = 64;
+        data[128] = 0x90u8 as i8;
+        blocks[2] = 26;
+        blocks[3] = 140u8 as i8;
+        blocks[4] = 85;
+
+        let mut level = snbt::parse(
+            "{xPos: 0, zPos: 0, TileEntities: [
+                {id: \"minecraft:bed\", x: 2, y: 0, z: 0, color: 11},
+                {id: \"minecraft:flower_pot\", x: 3, y: 0, z: 0, Item: \"minecraft:red_flower\", Data: 2}
+            ]}",
+        )
+        .unwrap();
+        let mut section = NbtCompound::new();
+        section.insert("Y", 0i8);
+        section.insert("Blocks", NbtTag::ByteArray(blocks));
+        section.insert("Data", NbtTag::ByteArray(data));
+        level.insert("Sections", NbtList::from(vec![NbtTag::Compound(section)]));
+
+        flatten_sections(&mut level);
+
+        let section = level.get::<_, &NbtList>("Sections").unwrap().get::<&NbtCompound>(0).unwrap();
+        assert!(!section.contains_key("Blocks") && !section.contains_key("Data"));
+        assert_eq!(
+            section.get::<_, &NbtList>("Palette").unwrap().get::<&NbtCompound>(0).unwrap(),
+            &state("air", &[])
+        );
+        assert_eq!(state_at(section, 0), state("grass_block", &[("snowy", &true)]));
+        assert_eq!(state_at(section, 256), state("snow", &[("layers", &1)]));
+        let door = |half: &str| {
+            state(
+                "oak_door",
+                &[
+                    ("facing", &"south"),
+                    ("half", &half),
+                    ("hinge", &"right"),
+                    ("open", &false),
+                    ("powered", &false),
+                ],
+            )
+        };
+        assert_eq!(state_at(section, 1), door("lower"));
+        assert_eq!(state_at(section, 257), door("upper"));
+        assert_eq!(
+            state_at(section, 2),
+            state("blue_bed", &[("facing", &"south"), ("occupied", &false), ("part", &"foot")])
+        );
+        assert_eq!(state_at(section, 3), state("potted_allium", &[]));
+        assert_eq!(state_at(section, 5), state("air", &[]));
+
+        // the flower pot became part of the block state
+        let tile_entities = level.get::<_, &NbtList>("TileEntities").unwrap();
+        assert_eq!(tile_entities.len(), 1);
+        let indices = level
+            .get::<_, &NbtCompound>("UpgradeData")
+            .unwrap()
+            .get::<_, &NbtCompound>("Indices")
+            .unwrap();
+        assert_eq!(indices.get::<_, &[i32]>("0").unwrap(), &[4]);
+    }
+
+    #[test]
+    fn pack_spanning_longs() {
+        let states = (0..64).map(|idx| idx % 32).collect::<Vec<_>>();
+        let longs = pack(&states, 5);
+        assert_eq!(longs.len(), 5);
+        // the 13th value starts at bit 60 and continues in the second long
+        assert_eq!((longs[0] as u64) >> 60, 12 & 15);
+        assert_eq!(longs[1] as u64 & 1, 12 >> 4);
+        assert_eq!(longs[1] as u64 >> 1 & 31, 13);
+    }
+}
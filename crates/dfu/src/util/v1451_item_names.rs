@@ -0,0 +1,219 @@
+use crate::util::v1451_block_states::{block_state, legacy_block_id, COLORS};
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+
+// This class is responsible for mapping item names and damage values to the flattened item names of 1.13
+
+lazy_static! {
+    static ref RENAMED_ITEMS: HashMap<&'static str, &'static str> = create_renamed_items();
+    static ref DAMAGEABLE_ITEMS: HashSet<String> = create_damageable_items();
+    static ref SPAWN_EGG_ENTITIES: HashSet<&'static str> = create_spawn_egg_entities();
+}
+
+const DYES: [&str; 16] = [
+    "ink_sac",
+    "rose_red",
+    "cactus_green",
+    "cocoa_beans",
+    "lapis_lazuli",
+    "purple_dye",
+    "cyan_dye",
+    "light_gray_dye",
+    "gray_dye",
+    "pink_dye",
+    "lime_dye",
+    "dandelion_yellow",
+    "light_blue_dye",
+    "magenta_dye",
+    "orange_dye",
+    "bone_meal",
+];
+pub(crate) const SKULLS: [&str; 6] = [
+    "skeleton_skull",
+    "wither_skeleton_skull",
+    "zombie_head",
+    "player_head",
+    "creeper_head",
+    "dragon_head",
+];
+
+/// The flattened name of a legacy item with its damage, `None` for items which keep their name
+pub(crate) fn flattened_item_name(name: &str, damage: i32) -> Option<String> {
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    let variant = |names: &[&str]| {
+        let new_name = usize::try_from(damage)
+            .ok()
+            .and_then(|damage| names.get(damage))
+            .unwrap_or(&names[0]);
+        Some(format!("minecraft:{new_name}"))
+    };
+    match name {
+        "coal" => variant(&["coal", "charcoal"]),
+        "golden_apple" => variant(&["golden_apple", "enchanted_golden_apple"]),
+        "fish" => variant(&["cod", "salmon", "tropical_fish", "pufferfish"]),
+        "cooked_fish" => variant(&["cooked_cod", "cooked_salmon"]),
+        "dye" => variant(&DYES),
+        "skull" => variant(&SKULLS),
+        // banner damage is a dye color, which counts in the opposite direction
+        "banner" => Some(format!("minecraft:{}_banner", COLORS[15 - (damage & 15) as usize])),
+        "bed" => Some(format!("minecraft:{}_bed", COLORS[(damage & 15) as usize])),
+        _ => {
+            if let Some(new_name) = RENAMED_ITEMS.get(name) {
+                return Some(format!("minecraft:{new_name}"));
+            }
+            let id = legacy_block_id(name)?;
+            // anvil items store their damage in the upper bits of the block metadata
+            let meta = if name == "anvil" { damage << 2 } else { damage };
+            let meta = if (0..16).contains(&meta) { meta } else { 0 };
+            block_state(id, meta).get::<_, &str>("Name").ok().map(str::to_string)
+        }
+    }
+}
+
+/// Whether the damage of an item is its durability, which moved into its tag
+pub(crate) fn is_damageable(name: &str) -> bool {
+    DAMAGEABLE_ITEMS.contains(name.strip_prefix("minecraft:").unwrap_or(name))
+}
+
+/// The spawn egg item for an entity id. Entities without their own egg get a pig spawn egg, which still spawns the entity of its tag.
+pub(crate) fn spawn_egg(entity_id: Option<&str>) -> String {
+    let entity = entity_id.map_or("pig", |id| id.strip_prefix("minecraft:").unwrap_or(id));
+    let entity = match entity {
+        "evocation_illager" => "evoker",
+        "vindication_illager" => "vindicator",
+        entity if SPAWN_EGG_ENTITIES.contains(entity) => entity,
+        _ => "pig",
+    };
+    format!("minecraft:{entity}_spawn_egg")
+}
+
+fn create_renamed_items() -> HashMap<&'static str, &'static str> {
+    let mut names = HashMap::new();
+    names.insert("boat", "oak_boat");
+    names.insert("chorus_fruit_popped", "popped_chorus_fruit");
+    names.insert("firework_charge", "firework_star");
+    names.insert("fireworks", "firework_rocket");
+    names.insert("melon", "melon_slice");
+    names.insert("netherbrick", "nether_brick");
+    names.insert("speckled_melon", "glistering_melon_slice");
+    names.insert("record_11", "music_disc_11");
+    names.insert("record_13", "music_disc_13");
+    names.insert("record_blocks", "music_disc_blocks");
+    names.insert("record_cat", "music_disc_cat");
+    names.insert("record_chirp", "music_disc_chirp");
+    names.insert("record_far", "music_disc_far");
+    names.insert("record_mall", "music_disc_mall");
+    names.insert("record_mellohi", "music_disc_mellohi");
+    names.insert("record_stal", "music_disc_stal");
+    names.insert("record_strad", "music_disc_strad");
+    names.insert("record_wait", "music_disc_wait");
+    names.insert("record_ward", "music_disc_ward");
+    names
+}
+
+fn create_damageable_items() -> HashSet<String> {
+    let mut names = HashSet::new();
+    for material in ["wooden", "stone", "iron", "golden", "diamond"] {
+        for tool in ["sword", "shovel", "pickaxe", "axe", "hoe"] {
+            names.insert(format!("{material}_{tool}"));
+        }
+    }
+    for material in ["leather", "chainmail", "iron", "golden", "diamond"] {
+        for armor in ["helmet", "chestplate", "leggings", "boots"] {
+            names.insert(format!("{material}_{armor}"));
+        }
+    }
+    for name in [
+        "bow",
+        "fishing_rod",
+        "flint_and_steel",
+        "shears",
+        "shield",
+        "elytra",
+        "carrot_on_a_stick",
+    ] {
+        names.insert(name.to_string());
+    }
+    names
+}
+
+fn create_spawn_egg_entities() -> HashSet<&'static str> {
+    [
+        "bat",
+        "blaze",
+        "cave_spider",
+        "chicken",
+        "cow",
+        "creeper",
+        "donkey",
+        "elder_guardian",
+        "enderman",
+        "endermite",
+        "ghast",
+        "guardian",
+        "horse",
+        "husk",
+        "llama",
+        "magma_cube",
+        "mooshroom",
+        "mule",
+        "ocelot",
+        "parrot",
+        "pig",
+        "polar_bear",
+        "rabbit",
+        "sheep",
+        "shulker",
+        "silverfish",
+        "skeleton",
+        "skeleton_horse",
+        "slime",
+        "spider",
+        "squid",
+        "stray",
+        "vex",
+        "villager",
+        "witch",
+        "wither_skeleton",
+        "wolf",
+        "zombie",
+        "zombie_horse",
+        "zombie_pigman",
+        "zombie_villager",
+    ]
+    .into_iter()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flattened_item_name, is_damageable, spawn_egg};
+
+    #[test]
+    fn item_names() {
+        let name = |name: &str, damage: i32| flattened_item_name(name, damage);
+        assert_eq!(name("minecraft:wool", 14).as_deref(), Some("minecraft:red_wool"));
+        assert_eq!(name("minecraft:log", 2).as_deref(), Some("minecraft:birch_log"));
+        assert_eq!(name("minecraft:anvil", 2).as_deref(), Some("minecraft:damaged_anvil"));
+        assert_eq!(name("minecraft:dye", 4).as_deref(), Some("minecraft:lapis_lazuli"));
+        assert_eq!(name("minecraft:dye", 99).as_deref(), Some("minecraft:ink_sac"));
+        assert_eq!(name("minecraft:banner", 0).as_deref(), Some("minecraft:black_banner"));
+        assert_eq!(name("minecraft:bed", 0).as_deref(), Some("minecraft:white_bed"));
+        assert_eq!(name("minecraft:skull", 3).as_deref(), Some("minecraft:player_head"));
+        assert_eq!(name("minecraft:pumpkin", 0).as_deref(), Some("minecraft:carved_pumpkin"));
+        assert_eq!(name("minecraft:melon", 0).as_deref(), Some("minecraft:melon_slice"));
+        assert_eq!(name("minecraft:melon_block", 0).as_deref(), Some("minecraft:melon"));
+        assert_eq!(name("minecraft:record_cat", 0).as_deref(), Some("minecraft:music_disc_cat"));
+        assert_eq!(name("minecraft:diamond_sword", 0), None);
+        assert!(is_damageable("minecraft:diamond_sword"));
+        assert!(!is_damageable("minecraft:diamond"));
+    }
+
+    #[test]
+    fn spawn_eggs() {
+        assert_eq!(spawn_egg(Some("minecraft:zombie_pigman")), "minecraft:zombie_pigman_spawn_egg");
+        assert_eq!(spawn_egg(Some("minecraft:vindication_illager")), "minecraft:vindicator_spawn_egg");
+        assert_eq!(spawn_egg(Some("minecraft:ender_dragon")), "minecraft:pig_spawn_egg");
+        assert_eq!(spawn_egg(None), "minecraft:pig_spawn_egg");
+    }
+}
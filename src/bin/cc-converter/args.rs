@@ -0,0 +1,231 @@
+use std::path::PathBuf;
+
+use clap::{ArgAction, Args, Parser, Subcommand};
+#[cfg(feature = "dfu")]
+use lazy_static::lazy_static;
+use lib_cc_converter::{BlockAabb, ConverterCreateCtx, CubeAabb};
+
+#[cfg(feature = "dfu")]
+lazy_static! {
+    /// `--upgrade-to` without a version
+    static ref DEFAULT_UPGRADE_TO: String = lib_cc_converter::DATA_VERSION_1_12_2.to_string();
+}
+
+/// Converts anvil worlds to cubic chunks and edits cubic chunks worlds.
+///
+/// Exits with 1 when a command fails, and 2 when its arguments are wrong.
+#[derive(Parser, Debug)]
+#[command(name = "cc-converter", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+    /// Log more than warnings, -vv logs every stage of the conversion
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+    /// Only log errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Converts an anvil world to a cubic chunks world
+    Anvil2cc(Anvil2CCArgs),
+    /// Copies a box of cubes within a cubic chunks world, or into another one
+    Copy(CopyArgs),
+    /// Removes the cubes outside of a height range, and optionally outside of boxes, from a cubic chunks world
+    Prune(PruneArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct Anvil2CCArgs {
+    /// The anvil world to convert
+    pub src: PathBuf,
+    /// Where to write the cubic chunks world
+    pub dst: PathBuf,
+    /// Add the tile entities of blocks which are missing them
+    #[arg(long)]
+    pub fix_missing_tile_entities: bool,
+    /// Don't write cubes with only air and no entities, except the ones cubic chunks requires
+    #[arg(long)]
+    pub skip_empty_cubes: bool,
+    #[command(flatten)]
+    pub offset: OffsetArgs,
+    /// Only convert the blocks in this box, given as minX,minY,minZ,maxX,maxY,maxZ. Can be repeated
+    #[arg(long, value_name = "BOX", value_parser = parse_box, allow_hyphen_values = true)]
+    pub select: Vec<BoxArg>,
+    /// Upgrade data saved by older versions of the game to this DataVersion first, the one of 1.12.2 if no version is given.
+    /// Later versions aren't supported
+    #[cfg(feature = "dfu")]
+    #[arg(
+        long,
+        value_name = "DATA_VERSION",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_UPGRADE_TO.as_str(),
+        value_parser = clap::value_parser!(i32).range(..=i64::from(lib_cc_converter::DATA_VERSION_1_12_2))
+    )]
+    pub upgrade_to: Option<i32>,
+    /// Chunks read ahead of the convert threads
+    #[arg(long, value_name = "SIZE", default_value_t = ConverterCreateCtx::default().convert_queue_size)]
+    pub convert_queue_size: usize,
+    /// Converted cubes and columns waiting to be written
+    #[arg(long, value_name = "SIZE", default_value_t = ConverterCreateCtx::default().write_queue_size)]
+    pub write_queue_size: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct CopyArgs {
+    /// The cubic chunks world to copy from
+    pub src: PathBuf,
+    /// The cubic chunks world to copy into, instead of the source world
+    #[arg(long)]
+    pub dst: Option<PathBuf>,
+    /// The cubes to copy, given as minX,minY,minZ,maxX,maxY,maxZ in chunk and cube coordinates
+    #[arg(long, value_name = "BOX", value_parser = parse_box, allow_hyphen_values = true)]
+    pub source: BoxArg,
+    #[command(flatten)]
+    pub offset: OffsetArgs,
+    /// Remove the cubes from the source box after copying them, turning the copy into a move
+    #[arg(long)]
+    pub clear_source: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PruneArgs {
+    /// The cubic chunks world to prune
+    pub path: PathBuf,
+    /// Lowest cube y to keep
+    #[arg(long, default_value_t = i32::MIN, allow_negative_numbers = true)]
+    pub min_y: i32,
+    /// Highest cube y to keep
+    #[arg(long, default_value_t = i32::MAX, allow_negative_numbers = true)]
+    pub max_y: i32,
+    /// Also remove the cubes outside of this box, given as minX,minY,minZ,maxX,maxY,maxZ in chunk and cube coordinates.
+    /// Can be repeated, cubes in any of the boxes are kept
+    #[arg(long, value_name = "BOX", value_parser = parse_box, allow_hyphen_values = true)]
+    pub keep: Vec<BoxArg>,
+}
+
+#[derive(Args, Debug)]
+pub struct OffsetArgs {
+    /// Horizontal shift of the world, in chunks
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub x_offset: i32,
+    /// Vertical shift of the world, in cubes
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub y_offset: i32,
+    /// Horizontal shift of the world, in chunks
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub z_offset: i32,
+}
+
+/// The corners of a box given on the command line, in whichever space the flag uses
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BoxArg([i32; 6]);
+
+impl BoxArg {
+    pub fn to_block_aabb(self) -> BlockAabb {
+        let [min_x, min_y, min_z, max_x, max_y, max_z] = self.0;
+        BlockAabb::from_coords(min_x, min_y, min_z, max_x, max_y, max_z)
+    }
+
+    pub fn to_cube_aabb(self) -> CubeAabb {
+        let [min_x, min_y, min_z, max_x, max_y, max_z] = self.0;
+        CubeAabb::from_coords(min_x, min_y, min_z, max_x, max_y, max_z)
+    }
+}
+
+fn parse_box(value: &str) -> Result<BoxArg, String> {
+    let coords = value
+        .split(',')
+        .map(|coord| {
+            coord
+                .trim()
+                .parse::<i32>()
+                .map_err(|err| format!("`{coord}` isn't a coordinate: {err}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let coords: [i32; 6] = coords
+        .try_into()
+        .map_err(|coords: Vec<i32>| format!("expected 6 coordinates, got {}", coords.len()))?;
+    Ok(BoxArg(coords))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser};
+
+    use super::{parse_box, BoxArg, Cli, Command};
+
+    #[test]
+    fn cli_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn boxes() {
+        assert_eq!(parse_box("-10,0,-10,10, 15,10"), Ok(BoxArg([-10, 0, -10, 10, 15, 10])));
+        assert!(parse_box("1,2,3").unwrap_err().contains("expected 6 coordinates, got 3"));
+        assert!(parse_box("1,2,3,4,5,x").unwrap_err().contains("`x` isn't a coordinate"));
+    }
+
+    #[test]
+    fn anvil2cc_args() {
+        let cli = Cli::try_parse_from([
+            "cc-converter",
+            "anvil2cc",
+            "in",
+            "out",
+            "--x-offset",
+            "-4",
+            "--select",
+            "-16,0,-16,15,255,15",
+            "--select=0,0,0,1,1,1",
+            "--skip-empty-cubes",
+        ])
+        .unwrap();
+        let Command::Anvil2cc(args) = cli.command else {
+            panic!("parsed the wrong command");
+        };
+        assert_eq!(args.offset.x_offset, -4);
+        assert_eq!(args.offset.y_offset, 0);
+        assert_eq!(args.select.len(), 2);
+        assert!(args.skip_empty_cubes);
+        assert!(!args.fix_missing_tile_entities);
+        assert_eq!(args.convert_queue_size, 1024);
+        assert_eq!(cli.verbose, 0);
+
+        assert!(Cli::try_parse_from(["cc-converter", "anvil2cc", "in"]).is_err());
+    }
+
+    #[test]
+    fn verbosity() {
+        let cli = Cli::try_parse_from(["cc-converter", "prune", "world", "-vv"]).unwrap();
+        assert_eq!(cli.verbose, 2);
+        assert!(Cli::try_parse_from(["cc-converter", "-q", "prune", "world"]).unwrap().quiet);
+        assert!(Cli::try_parse_from(["cc-converter", "prune", "world", "-q", "-v"]).is_err());
+    }
+
+    #[cfg(feature = "dfu")]
+    #[test]
+    fn upgrade_to_defaults_to_1_12_2() {
+        let parse = |extra: &[&str]| {
+            let args = ["cc-converter", "anvil2cc", "in", "out"].iter().chain(extra);
+            match Cli::try_parse_from(args).unwrap().command {
+                Command::Anvil2cc(args) => args.upgrade_to,
+                _ => unreachable!(),
+            }
+        };
+        assert_eq!(parse(&[]), None);
+        assert_eq!(parse(&["--upgrade-to"]), Some(lib_cc_converter::DATA_VERSION_1_12_2));
+        assert_eq!(parse(&["--upgrade-to", "1139"]), Some(1139));
+    }
+
+    #[cfg(feature = "dfu")]
+    #[test]
+    fn upgrade_to_stops_at_1_12_2() {
+        let err = Cli::try_parse_from(["cc-converter", "anvil2cc", "in", "out", "--upgrade-to", "1451"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+        assert_eq!(err.exit_code(), 2);
+    }
+}
@@ -0,0 +1,60 @@
+use std::io::{IsTerminal, Write};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Writes records to stderr, replacing the progress line on terminals. The progress line is drawn again below them.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let mut stderr = std::io::stderr().lock();
+        let clear_line = if stderr.is_terminal() { "\r\x1b[K" } else { "" };
+        let _ = writeln!(stderr, "{clear_line}[{} {}] {}", record.level(), record.target(), record.args());
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+pub fn init(verbose: u8, quiet: bool) {
+    log::set_logger(&LOGGER).expect("the logger is only set once");
+    log::set_max_level(level_filter(verbose, quiet));
+}
+
+/// Warnings and errors by default, each `-v` adds a level
+fn level_filter(verbose: u8, quiet: bool) -> LevelFilter {
+    if quiet {
+        return LevelFilter::Error;
+    }
+    match verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::LevelFilter;
+
+    use super::level_filter;
+
+    #[test]
+    fn levels() {
+        assert_eq!(level_filter(0, false), LevelFilter::Warn);
+        assert_eq!(level_filter(2, false), LevelFilter::Debug);
+        assert_eq!(level_filter(7, false), LevelFilter::Trace);
+        assert_eq!(level_filter(0, true), LevelFilter::Error);
+    }
+}
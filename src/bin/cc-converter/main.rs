@@ -0,0 +1,117 @@
+mod args;
+mod logger;
+mod progress;
+
+use std::process::ExitCode;
+
+use args::{Anvil2CCArgs, Cli, Command, CopyArgs, PruneArgs};
+use clap::Parser;
+use lib_cc_converter::{
+    anvil2cc, copy_cubes, prune_cubes, Anvil2CCConfig, ConverterCreateCtx, CopyCubesConfig, PruneCubesConfig, Selection,
+};
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    logger::init(cli.verbose, cli.quiet);
+    let result = match cli.command {
+        Command::Anvil2cc(args) => run_anvil2cc(args),
+        Command::Copy(args) => run_copy(args),
+        Command::Prune(args) => run_prune(args),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{error}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_anvil2cc(args: Anvil2CCArgs) -> Result<(), Vec<String>> {
+    let selection =
+        (!args.select.is_empty()).then(|| Selection::from_blocks(args.select.iter().map(|aabb| aabb.to_block_aabb()).collect()));
+    let config = Anvil2CCConfig {
+        fix_missing_tile_entities: args.fix_missing_tile_entities,
+        skip_empty_cubes: args.skip_empty_cubes,
+        x_offset: args.offset.x_offset,
+        y_offset: args.offset.y_offset,
+        z_offset: args.offset.z_offset,
+        selection,
+        #[cfg(feature = "dfu")]
+        upgrade_to: args.upgrade_to,
+        ctx: ConverterCreateCtx {
+            convert_queue_size: args.convert_queue_size,
+            write_queue_size: args.write_queue_size,
+        },
+    };
+
+    let waiter = anvil2cc(&args.src, &args.dst, config).map_err(|err| vec![format!("Couldn't start converting: {err}")])?;
+    progress::show_until_finished(&waiter);
+    waiter
+        .join_all()
+        .map_err(|errors| errors.iter().map(|err| err.to_string()).collect())
+}
+
+fn run_copy(args: CopyArgs) -> Result<(), Vec<String>> {
+    let config = CopyCubesConfig {
+        source: args.source.to_cube_aabb(),
+        x_offset: args.offset.x_offset,
+        y_offset: args.offset.y_offset,
+        z_offset: args.offset.z_offset,
+        clear_source: args.clear_source,
+    };
+    let copied = copy_cubes(&args.src, args.dst.as_deref(), config).map_err(|err| vec![format!("Couldn't copy cubes: {err}")])?;
+    eprintln!("Copied {copied} cubes");
+    Ok(())
+}
+
+fn run_prune(args: PruneArgs) -> Result<(), Vec<String>> {
+    let keep = (!args.keep.is_empty()).then(|| args.keep.iter().map(|aabb| aabb.to_cube_aabb()).collect());
+    let config = PruneCubesConfig {
+        min_y: args.min_y,
+        max_y: args.max_y,
+        keep,
+    };
+    let removed = prune_cubes(&args.path, config).map_err(|err| vec![format!("Couldn't prune cubes: {err}")])?;
+    eprintln!("Removed {removed} cubes");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use clap::Parser;
+
+    use super::{run_anvil2cc, Cli, Command};
+
+    #[test]
+    fn anvil2cc_fails_when_chunks_are_skipped() {
+        let world = std::env::temp_dir().join("cc-converter-test/anvil2cc_skipped_chunks");
+        if world.exists() {
+            fs::remove_dir_all(&world).unwrap();
+        }
+        fs::create_dir_all(world.join("in/region")).unwrap();
+
+        // one chunk in sector 2, which isn't valid zlib data
+        let mut region = vec![0u8; 8192];
+        region[..4].copy_from_slice(&(2u32 << 8 | 1).to_be_bytes());
+        let mut chunk = vec![0, 0, 0, 5, 2, 1, 2, 3, 4];
+        chunk.resize(4096, 0);
+        region.extend(chunk);
+        fs::write(world.join("in/region/r.0.0.mca"), region).unwrap();
+
+        let cli = Cli::try_parse_from([
+            "cc-converter".as_ref(),
+            "anvil2cc".as_ref(),
+            world.join("in").as_os_str(),
+            world.join("out").as_os_str(),
+        ])
+        .unwrap();
+        let Command::Anvil2cc(args) = cli.command else { unreachable!() };
+        let errors = run_anvil2cc(args).unwrap_err();
+        assert_eq!(errors, vec!["Conversion failed: 1 inputs couldn't be converted and were skipped"]);
+    }
+}
@@ -0,0 +1,46 @@
+use std::{
+    io::{IsTerminal, Write},
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+use lib_cc_converter::ConverterWaiter;
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Redraws a line with the counters of `waiter` on stderr until all of its threads have finished. Nothing is drawn when
+/// stderr isn't a terminal, but this still waits.
+pub fn show_until_finished(waiter: &ConverterWaiter) {
+    let start = Instant::now();
+    let draw = std::io::stderr().is_terminal();
+    while !waiter.is_finished() {
+        if draw {
+            redraw(waiter, start);
+        }
+        std::thread::sleep(REFRESH_INTERVAL);
+    }
+    if draw {
+        redraw(waiter, start);
+        eprintln!();
+    }
+}
+
+fn redraw(waiter: &ConverterWaiter, start: Instant) {
+    let mut stderr = std::io::stderr().lock();
+    // \x1b[K clears what's left of a longer previous line
+    let _ = write!(stderr, "\r{}\x1b[K", line(waiter, start.elapsed()));
+    let _ = stderr.flush();
+}
+
+fn line(waiter: &ConverterWaiter, elapsed: Duration) -> String {
+    format!(
+        "{}s | {} chunks read | {} skipped | convert queue {}/{} | write queue {}/{}",
+        elapsed.as_secs(),
+        waiter.tasks_sent.load(Ordering::Relaxed),
+        waiter.tasks_skipped.load(Ordering::Relaxed),
+        waiter.convert_queue_fill.load(Ordering::Relaxed),
+        waiter.convert_queue_size,
+        waiter.write_queue_fill.load(Ordering::Relaxed),
+        waiter.write_queue_size,
+    )
+}
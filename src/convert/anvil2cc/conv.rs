@@ -1,13 +1,22 @@
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
+#[cfg(feature = "dfu")]
+use std::sync::Arc;
 
 use byteorder::{BigEndian, WriteBytesExt};
 
 use lazy_static::lazy_static;
 
 use crate::convert::anvil2cc::conv::Anvil2CCConversionError::{InvalidData, NbtIo, NbtRepr, NbtStructure, StdIo};
+use crate::convert::anvil2cc::population::PopulationMap;
+use crate::convert::anvil2cc::section::{SectionBlocks, BLOCKS_PER_SECTION};
+#[cfg(feature = "dfu")]
+use crate::convert::anvil2cc::upgrade::Upgrader;
 use crate::convert::converter::{ConversionError, Converter};
+use crate::convert::relocate::{offset_entities, offset_tile_entities, offset_tile_ticks};
+use crate::convert::selection::Selection;
+use crate::log_targets;
 use crate::util::errors::error_from;
 use quartz_nbt::io::NbtIoError;
 use quartz_nbt::NbtTag::{Byte, ByteArray, Compound, Int, IntArray, String};
@@ -15,7 +24,9 @@ use quartz_nbt::{NbtCompound, NbtList, NbtReprError, NbtStructureError, NbtTag};
 
 use crate::convert::data::anvil::Data;
 use crate::convert::data::cc_1_12::CubicChunks112Data;
+use crate::convert::entry_location::EntryLocation2d;
 use crate::util::compress::{read_compressed, write_compressed};
+use crate::util::positions::CubePos;
 use crate::util::reinterpret::vec_u8_into_i8;
 
 lazy_static! {
@@ -79,6 +90,14 @@ pub struct InvalidChunkTagError {
     message: std::string::String,
 }
 
+impl InvalidChunkTagError {
+    pub fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+        }
+    }
+}
+
 impl Debug for InvalidChunkTagError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str(&self.message)
@@ -99,6 +118,8 @@ pub enum Anvil2CCConversionError {
     StdIo(std::io::Error),
     MissingHeightmap,
     MissingBiomes,
+    #[cfg(feature = "dfu")]
+    Upgrade(Box<dyn Error>),
 }
 
 impl Debug for Anvil2CCConversionError {
@@ -111,6 +132,8 @@ impl Debug for Anvil2CCConversionError {
             Self::StdIo(err) => f.write_str(&format!("{:?}", err)),
             Self::MissingHeightmap => f.write_str("Chunk missing HeightMap tag"),
             Self::MissingBiomes => f.write_str("Chunk missing Biomes tag"),
+            #[cfg(feature = "dfu")]
+            Self::Upgrade(err) => f.write_str(&format!("Couldn't upgrade chunk: {}", err)),
         }
     }
 }
@@ -125,6 +148,8 @@ impl Display for Anvil2CCConversionError {
             Self::StdIo(err) => f.write_str(&format!("{}", err)),
             Self::MissingHeightmap => f.write_str("Chunk missing HeightMap tag"),
             Self::MissingBiomes => f.write_str("Chunk missing Biomes tag"),
+            #[cfg(feature = "dfu")]
+            Self::Upgrade(err) => f.write_str(&format!("Couldn't upgrade chunk: {}", err)),
         }
     }
 }
@@ -145,15 +170,66 @@ impl From<Anvil2CCConversionError> for ConversionError {
 
 pub struct Anvil2CCConverter {
     fix_missing_tile_entities: bool,
+    /// Sections with only air and nothing in them are not written, unless cubic chunks requires the cube
+    skip_empty_cubes: bool,
+    /// Added to the position of every converted cube, and to all block coordinates within them
+    offset: CubePos,
+    /// Cubes outside of the selection are dropped, in source coordinates
+    selection: Option<Selection>,
+    population: PopulationMap,
+    /// Upgrades chunks saved by older versions of the game before they are split into cubes
+    #[cfg(feature = "dfu")]
+    upgrader: Option<Arc<Upgrader>>,
 }
 
 impl Anvil2CCConverter {
-    pub fn new(fix_missing_tile_entities: bool) -> Self {
-        Self { fix_missing_tile_entities }
+    pub fn new(
+        fix_missing_tile_entities: bool,
+        skip_empty_cubes: bool,
+        offset: CubePos,
+        selection: Option<Selection>,
+        population: PopulationMap,
+    ) -> Self {
+        Self {
+            fix_missing_tile_entities,
+            skip_empty_cubes,
+            offset,
+            selection,
+            population,
+            #[cfg(feature = "dfu")]
+            upgrader: None,
+        }
+    }
+
+    #[cfg(feature = "dfu")]
+    pub fn with_upgrader(mut self, upgrader: Arc<Upgrader>) -> Self {
+        self.upgrader = Some(upgrader);
+        self
+    }
+
+    fn is_chunk_selected(&self, x: i32, z: i32) -> bool {
+        self.selection.as_ref().is_none_or(|selection| selection.contains_chunk(x, z))
     }
 
-    fn extract_column_data(&self, data: &[u8]) -> Result<Vec<u8>, Anvil2CCConversionError> {
+    fn is_cube_selected(&self, x: i32, y: i32, z: i32) -> bool {
+        self.selection.as_ref().is_none_or(|selection| selection.contains_cube(x, y, z))
+    }
+
+    fn read_chunk(&self, data: &[u8]) -> Result<NbtCompound, Anvil2CCConversionError> {
         let tag = read_compressed(data)?;
+        #[cfg(feature = "dfu")]
+        let tag = match &self.upgrader {
+            Some(upgrader) => {
+                let mut tag = tag;
+                upgrader.upgrade_chunk(&mut tag).map_err(Anvil2CCConversionError::Upgrade)?;
+                tag
+            }
+            None => tag,
+        };
+        Ok(tag)
+    }
+
+    fn extract_column_data(&self, tag: NbtCompound) -> Result<Vec<u8>, Anvil2CCConversionError> {
         let output = self.extract_column_data_from_tag(tag)?;
         Ok(write_compressed(&output, true)?)
     }
@@ -205,7 +281,7 @@ impl Anvil2CCConverter {
         let src_heightmap_tag: &NbtTag = src_level.get("HeightMap")?;
         let src_heightmap: Vec<_>;
         if let IntArray(heightmap) = src_heightmap_tag {
-            src_heightmap = Self::fix_heightmap(heightmap);
+            src_heightmap = Self::fix_heightmap(heightmap, self.offset.y * 16);
         } else {
             return Err(Anvil2CCConversionError::MissingHeightmap);
         }
@@ -218,8 +294,8 @@ impl Anvil2CCConverter {
             }));
         }
 
-        level.insert("x", Int(src_level.get::<_, i32>("xPos")?));
-        level.insert("z", Int(src_level.get::<_, i32>("zPos")?));
+        level.insert("x", Int(src_level.get::<_, i32>("xPos")? + self.offset.x));
+        level.insert("z", Int(src_level.get::<_, i32>("zPos")? + self.offset.z));
         level.insert("InhabitedTime", Int(src_level.get::<_, i32>("InhabitedTime").unwrap_or(0)));
 
         let src_biomes_tag: &NbtTag = src_level.get("Biomes")?;
@@ -229,7 +305,10 @@ impl Anvil2CCConverter {
             return Err(Anvil2CCConversionError::MissingBiomes);
         }
 
-        level.insert("OpacityIndex", ByteArray(Self::make_dummy_opacity_index(&src_heightmap)?));
+        level.insert(
+            "OpacityIndex",
+            ByteArray(Self::make_dummy_opacity_index(&src_heightmap, self.offset.y * 16)?),
+        );
 
         let mut root = NbtCompound::new();
         root.insert("Level", level);
@@ -240,28 +319,28 @@ impl Anvil2CCConverter {
         Ok(root)
     }
 
-    fn fix_heightmap(heights: &[i32]) -> Vec<i32> {
+    fn fix_heightmap(heights: &[i32], y_offset_blocks: i32) -> Vec<i32> {
         let mut heights = heights.to_vec();
         for height in heights.iter_mut() {
-            *height -= 1; // vanilla = 1 above top, data = top block
+            *height += y_offset_blocks - 1; // vanilla = 1 above top, data = top block
         }
         heights
     }
 
-    fn make_dummy_opacity_index(height_map: &[i32]) -> Result<Vec<i8>, std::io::Error> {
+    fn make_dummy_opacity_index(height_map: &[i32], min_y: i32) -> Result<Vec<i8>, std::io::Error> {
         let mut out = Vec::new();
 
         for entry in height_map {
             // 256 segment arrays
-            out.write_i32::<BigEndian>(0)?; // minY
+            out.write_i32::<BigEndian>(min_y)?; // minY
             out.write_i32::<BigEndian>(*entry)?; // maxY
             out.write_i16::<BigEndian>(0)?; // no segments - write zero
         }
         Ok(vec_u8_into_i8(out))
     }
 
-    fn extract_cube_data(&self, data: &[u8]) -> Result<Vec<(i32, Vec<u8>)>, Anvil2CCConversionError> {
-        let tags = self.extract_cube_data_from_tag(read_compressed(data)?)?;
+    fn extract_cube_data(&self, tag: NbtCompound) -> Result<Vec<(i32, Vec<u8>)>, Anvil2CCConversionError> {
+        let tags = self.extract_cube_data_from_tag(tag)?;
         let mut bytes_by_cube_y = Vec::new();
         for (y, tag) in tags {
             bytes_by_cube_y.push((y, write_compressed(&tag, false)?));
@@ -333,6 +412,12 @@ impl Anvil2CCConverter {
         let x = src_level.get::<_, i32>("xPos")?;
         let z = src_level.get::<_, i32>("zPos")?;
 
+        let populated = src_level.get::<_, i8>("TerrainPopulated").unwrap_or(0) != 0;
+        self.population.set_populated(x, z, populated);
+        let fully_populated = populated && self.population.is_fully_populated(x, z);
+
+        let offset = self.offset.to_block_pos();
+        let mut added_tile_entities = 0;
         let src_sections = src_level.get::<_, &NbtList>("Sections")?;
         for src_section in src_sections {
             let y = match src_section {
@@ -340,7 +425,30 @@ impl Anvil2CCConverter {
                 _ => continue,
             }? as i32;
 
+            if !self.is_cube_selected(x, y, z) {
+                continue;
+            }
+
             let src_section: &NbtCompound = src_section.try_into()?;
+            let mut blocks = SectionBlocks::read(src_section)?;
+            // entities, tile entities and ticks are selected in source coordinates, then moved with the cube
+            let mut entities = Self::filter_entities(src_level.get::<_, &NbtList>("Entities")?, y)?;
+            let mut tile_entities = Self::filter_tile_entities(src_level.get::<_, &NbtList>("TileEntities")?, y)?;
+            let tile_ticks = match src_level.get::<_, &NbtList>("TileTicks") {
+                Ok(tile_ticks) => Some(Self::filter_tile_ticks(tile_ticks, y)?),
+                Err(_) => None,
+            };
+            if self.skip_empty_cubes
+                && blocks.as_ref().is_some_and(SectionBlocks::is_air)
+                && entities.is_empty()
+                && tile_entities.is_empty()
+                && tile_ticks.as_ref().is_none_or(NbtList::is_empty)
+            {
+                // cubes cubic chunks requires are added back as empty cubes below
+                continue;
+            }
+
+            let cube_y = y + self.offset.y;
             let mut root = NbtCompound::new();
             {
                 if let Some(data_version) = &data_version {
@@ -350,13 +458,12 @@ impl Anvil2CCConverter {
                 let mut level = NbtCompound::new();
                 {
                     level.insert("v", Byte(1));
-                    level.insert("x", Int(x));
-                    level.insert("y", Int(y));
-                    level.insert("z", Int(z));
+                    level.insert("x", Int(x + self.offset.x));
+                    level.insert("y", Int(cube_y));
+                    level.insert("z", Int(z + self.offset.z));
 
-                    let populated = src_level.get::<_, i8>("TerrainPopulated").unwrap_or(0);
-                    level.insert("populated", Byte(populated));
-                    level.insert("fullyPopulated", Byte(populated)); // TODO: handle this properly
+                    level.insert("populated", Byte(populated as i8));
+                    level.insert("fullyPopulated", Byte(fully_populated as i8));
                     level.insert("isSurfaceTracked", Byte(0)); // so that cubic chunks can re-make surface tracking data on it's own
 
                     let light_populated = src_level.get::<_, i8>("LightPopulated").unwrap_or(0);
@@ -366,99 +473,99 @@ impl Anvil2CCConverter {
                     let mut sections_tag = NbtList::new();
 
                     let mut sec = src_section.clone();
-                    Self::fix_section(&mut sec)?;
+                    if let Some(blocks) = &mut blocks {
+                        if Self::fix_section(blocks) {
+                            blocks.write(&mut sec);
+                        }
+                    }
                     sections_tag.push(sec);
                     level.insert("Sections", sections_tag);
 
-                    level.insert("Entities", Self::filter_entities(src_level.get::<_, &NbtList>("Entities")?, y)?);
-                    let mut tile_entities = Self::filter_tile_entities(src_level.get::<_, &NbtList>("TileEntities")?, y)?;
-                    if self.fix_missing_tile_entities {
-                        tile_entities = Self::add_missing_tile_entities(x, y, z, tile_entities, src_section)?;
+                    offset_entities(&mut entities, offset)?;
+                    level.insert("Entities", entities);
+                    if let (true, Some(blocks)) = (self.fix_missing_tile_entities, &blocks) {
+                        let added;
+                        (tile_entities, added) = Self::add_missing_tile_entities(x, y, z, tile_entities, blocks)?;
+                        added_tile_entities += added;
                     }
+                    offset_tile_entities(&mut tile_entities, offset)?;
                     level.insert("TileEntities", tile_entities);
-                    if let Ok(tile_ticks) = src_level.get::<_, &NbtList>("TileTicks") {
-                        level.insert("TileTicks", Self::filter_tile_ticks(tile_ticks, y)?);
+                    if let Some(mut tile_ticks) = tile_ticks {
+                        offset_tile_ticks(&mut tile_ticks, offset)?;
+                        level.insert("TileTicks", tile_ticks);
                     }
-                    level.insert("LightingInfo", Self::make_lighting_info(src_level)?);
+                    level.insert("LightingInfo", Self::make_lighting_info(src_level, offset.y)?);
                 }
                 root.insert("Level", level);
             }
-            tags.insert(y, root);
+            tags.insert(cube_y, root);
+        }
+
+        if added_tile_entities > 0 {
+            log::debug!(
+                target: log_targets::CONVERTER,
+                "Added {added_tile_entities} missing tile entities to chunk {x}, {z}"
+            );
         }
 
         // make sure the 0-15 range is there because it's using vanilla generator which expects it to be the case
-        for y in 0..16 {
-            tags.entry(y).or_insert_with(|| Self::empty_cube(x, y, z));
+        for y in self.offset.y..self.offset.y + 16 {
+            if !self.is_cube_selected(x, y - self.offset.y, z) {
+                continue;
+            }
+            tags.entry(y)
+                .or_insert_with(|| Self::empty_cube(x + self.offset.x, y, z + self.offset.z, populated, fully_populated));
         }
         let tags: Vec<_> = tags.drain().map(|(y, tag)| (y, tag)).collect();
         Ok(tags)
     }
 
+    /// Adds tile entities for blocks that require one but have none, returning the new list and how many were added.
     fn add_missing_tile_entities(
         cube_x: i32,
         cube_y: i32,
         cube_z: i32,
         tile_entities: NbtList,
-        section: &NbtCompound,
-    ) -> Result<NbtList, Anvil2CCConversionError> {
-        return match section.get::<_, &[i8]>("Blocks") {
-            Err(_) => Ok(tile_entities),
-            Ok(blocks) => {
-                let add: Option<&[i8]> = section.get("Add").ok();
-                let add2neid: Option<&[i8]> = section.get("Add2").ok();
-
-                let mut te_map: HashMap<i32, NbtCompound> = HashMap::new();
-                for tag in tile_entities {
-                    let te: NbtCompound = tag.try_into()?;
-                    let x: i32 = te.get("x").unwrap_or(0);
-                    let y: i32 = te.get("y").unwrap_or(0);
-                    let z: i32 = te.get("z").unwrap_or(0);
-                    let idx = y & 0xF << 8 | z & 0xF << 4 | x & 0xF;
-                    te_map.insert(idx, te);
-                }
-                for i in 0..4096 {
-                    let x = i & 15;
-                    let y = i >> 8 & 15;
-                    let z = i >> 4 & 15;
-
-                    let mut to_add = if let Some(add) = add { Self::get_nibble(add, i) } else { 0 };
-
-                    let asd = if let Some(add2neid) = add2neid {
-                        Self::get_nibble(add2neid, i) << 4
-                    } else {
-                        0
-                    };
-                    to_add = (to_add & 0xF) | asd;
-
-                    let id = (to_add << 8) | blocks[i as usize] as i32;
-                    let te_id = TE_REGISTRY.get(&id);
-                    if let Some(te_id) = te_id {
-                        te_map.entry(i).or_insert_with(|| {
-                            let mut tag = NbtCompound::new();
-                            tag.insert("id", String(te_id.to_string()));
-                            tag.insert("x", Int(cube_x * 16 + x));
-                            tag.insert("y", Int(cube_y * 16 + y));
-                            tag.insert("z", Int(cube_z * 16 + z));
-                            tag
-                        });
-                    }
-                }
-                let mut tile_entities_list = NbtList::new();
-                te_map.drain().for_each(|(_, value)| {
-                    tile_entities_list.push(Compound(value));
+        blocks: &SectionBlocks,
+    ) -> Result<(NbtList, usize), Anvil2CCConversionError> {
+        let mut te_map: HashMap<usize, NbtCompound> = HashMap::new();
+        for tag in tile_entities {
+            let te: NbtCompound = tag.try_into()?;
+            let x: i32 = te.get("x").unwrap_or(0);
+            let y: i32 = te.get("y").unwrap_or(0);
+            let z: i32 = te.get("z").unwrap_or(0);
+            te_map.insert(Self::block_index(x, y, z), te);
+        }
+
+        let mut added = 0;
+        for i in 0..BLOCKS_PER_SECTION {
+            if let Some(te_id) = TE_REGISTRY.get(&(blocks.id(i) as i32)) {
+                te_map.entry(i).or_insert_with(|| {
+                    added += 1;
+
+                    let mut tag = NbtCompound::new();
+                    tag.insert("id", String(te_id.to_string()));
+                    tag.insert("x", Int(cube_x * 16 + (i & 15) as i32));
+                    tag.insert("y", Int(cube_y * 16 + (i >> 8 & 15) as i32));
+                    tag.insert("z", Int(cube_z * 16 + (i >> 4 & 15) as i32));
+                    tag
                 });
-                Ok(tile_entities_list)
             }
-        };
+        }
+
+        let mut tile_entities_list = NbtList::new();
+        te_map.drain().for_each(|(_, value)| {
+            tile_entities_list.push(Compound(value));
+        });
+        Ok((tile_entities_list, added))
     }
 
-    fn get_nibble(array: &[i8], i: i32) -> i32 {
-        let v = array[(i >> 1) as usize];
-        let shifted_value = if (i & 1) == 0 { v } else { v >> 4 };
-        (shifted_value & 0xF) as i32
+    /// Index of a block within its 16x16x16 section, in vanilla YZX order. Accepts world coordinates.
+    fn block_index(x: i32, y: i32, z: i32) -> usize {
+        ((y & 0xF) << 8 | (z & 0xF) << 4 | (x & 0xF)) as usize
     }
 
-    fn empty_cube(x: i32, y: i32, z: i32) -> NbtCompound {
+    fn empty_cube(x: i32, y: i32, z: i32, populated: bool, fully_populated: bool) -> NbtCompound {
         let mut root = NbtCompound::new();
         {
             let mut level = NbtCompound::new();
@@ -468,8 +575,9 @@ impl Anvil2CCConverter {
                 level.insert("y", Int(y));
                 level.insert("z", Int(z));
 
-                level.insert("populated", Byte(1));
-                level.insert("fullyPopulated", Byte(1));
+                // decoration of the column can still reach into empty cubes, so they share the column's flags
+                level.insert("populated", Byte(populated as i8));
+                level.insert("fullyPopulated", Byte(fully_populated as i8));
                 level.insert("isSurfaceTracked", Byte(1)); // it's empty, no need to re-track
 
                 // no need for Sections, CC has isEmpty check for that
@@ -493,23 +601,26 @@ impl Anvil2CCConverter {
         lighting_info
     }
 
-    fn fix_section(src_section: &mut NbtCompound) -> Result<(), NbtReprError> {
-        let ids = src_section.get_mut::<_, &mut Vec<i8>>("Blocks")?;
+    /// Returns whether any block was changed
+    fn fix_section(blocks: &mut SectionBlocks) -> bool {
+        let mut changed = false;
         // TODO: handle it the forge way
-        for id in ids.iter_mut() {
+        for i in 0..BLOCKS_PER_SECTION {
             // bedrock
-            if *id == 7 {
-                *id = 1; // stone
+            if blocks.id(i) == 7 {
+                blocks.set(i, 1, blocks.meta(i)); // stone
+                changed = true;
             }
         }
-        Ok(())
+        changed
     }
 
-    fn make_lighting_info(src_level: &NbtCompound) -> Result<NbtCompound, NbtReprError> {
+    fn make_lighting_info(src_level: &NbtCompound, y_offset_blocks: i32) -> Result<NbtCompound, NbtReprError> {
         let heightmap: &NbtTag = src_level.get("HeightMap")?;
         if let NbtTag::IntArray(heightmap) = heightmap {
+            let heightmap: Vec<_> = heightmap.iter().map(|height| height + y_offset_blocks).collect();
             let mut lighting_info_map = NbtCompound::new();
-            lighting_info_map.insert("LastHeightMap", heightmap.clone());
+            lighting_info_map.insert("LastHeightMap", heightmap);
             return Ok(lighting_info_map);
         }
         Err(NbtReprError::Custom(std::fmt::Error {}.into()))
@@ -565,12 +676,467 @@ impl Anvil2CCConverter {
 
 impl Converter<Data, CubicChunks112Data> for Anvil2CCConverter {
     fn convert(&self, src: Data) -> Result<Vec<CubicChunks112Data>, ConversionError> {
+        if !self.is_chunk_selected(src.position.x, src.position.z) {
+            return Ok(Vec::new());
+        }
+
+        let position = src.position.to_entry_location_2d();
+        let tag = self.read_chunk(&src.data)?;
         let data = CubicChunks112Data::from_data(
-            src.position.to_entry_location_2d(),
-            self.extract_column_data(&src.data)?,
-            self.extract_cube_data(&src.data)?,
+            EntryLocation2d::new(position.x + self.offset.x, position.z + self.offset.z),
+            self.extract_column_data(tag.clone())?,
+            self.extract_cube_data(tag)?,
         );
 
         Ok(Vec::from([data]))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, path::Path};
+
+    use quartz_nbt::{
+        io::{self, Flavor},
+        NbtCompound, NbtList,
+    };
+
+    use super::Anvil2CCConverter;
+    use crate::{
+        convert::{
+            anvil2cc::{population::PopulationMap, section::SectionBlocks},
+            converter::Converter,
+            data::anvil::Data,
+            entry_location::MinecraftChunkLocation,
+            selection::Selection,
+        },
+        util::{
+            aabb::BlockAabb,
+            compress::{read_compressed_cc, write_compressed},
+            positions::CubePos,
+            test_utils,
+        },
+    };
+
+    const CHEST: i8 = 54;
+    const FURNACE: i8 = 61;
+    const SIGN: i8 = 63;
+    const HOPPER: u8 = 154;
+
+    fn set_block(blocks: &mut [i8], x: i32, y: i32, z: i32, id: i8) {
+        blocks[Anvil2CCConverter::block_index(x, y, z)] = id;
+    }
+
+    fn tile_entity(id: &str, x: i32, y: i32, z: i32) -> NbtCompound {
+        let mut te = NbtCompound::new();
+        te.insert("id", id);
+        te.insert("x", x);
+        te.insert("y", y);
+        te.insert("z", z);
+        te
+    }
+
+    fn find_tile_entity(list: &NbtList, x: i32, y: i32, z: i32) -> Vec<NbtCompound> {
+        list.iter()
+            .map(|tag| <&NbtCompound>::try_from(tag).unwrap().clone())
+            .filter(|te| te.get::<_, i32>("x").unwrap() == x && te.get::<_, i32>("y").unwrap() == y && te.get::<_, i32>("z").unwrap() == z)
+            .collect()
+    }
+
+    fn chunk_tag(x: i32, z: i32, section_ys: &[i8]) -> NbtCompound {
+        let mut sections = NbtList::new();
+        for y in section_ys {
+            let mut section = NbtCompound::new();
+            section.insert("Y", *y);
+            section.insert("Blocks", vec![1i8; 4096]);
+            section.insert("Data", vec![0i8; 2048]);
+            sections.push(section);
+        }
+
+        let mut level = NbtCompound::new();
+        level.insert("xPos", x);
+        level.insert("zPos", z);
+        level.insert("TerrainPopulated", 1i8);
+        level.insert("HeightMap", vec![48; 256]);
+        level.insert("Biomes", vec![1i8; 256]);
+        level.insert("Sections", sections);
+        level.insert("Entities", NbtList::new());
+        level.insert("TileEntities", NbtList::new());
+
+        let mut root = NbtCompound::new();
+        root.insert("DataVersion", 1343);
+        root.insert("Level", level);
+        root
+    }
+
+    fn chunk_data(root: &NbtCompound) -> Vec<u8> {
+        let mut data = vec![1u8];
+        data.extend(write_compressed(root, false).unwrap());
+        data
+    }
+
+    fn converter(offset: CubePos) -> Anvil2CCConverter {
+        Anvil2CCConverter::new(false, false, offset, None, PopulationMap::new(Path::new("does_not_exist")))
+    }
+
+    #[test]
+    fn y_offset_moves_cubes_and_contents() {
+        let mut root = chunk_tag(3, -7, &[2]);
+        {
+            let level: &mut NbtCompound = root.get_mut("Level").unwrap();
+
+            let mut entity = NbtCompound::new();
+            entity.insert("id", "minecraft:cow");
+            entity.insert("Pos", NbtList::from(vec![48.5f64, 40.0, -100.5]));
+            let mut passenger = NbtCompound::new();
+            passenger.insert("Pos", NbtList::from(vec![48.5f64, 41.0, -100.5]));
+            entity.insert("Passengers", NbtList::from(vec![passenger]));
+            level.insert("Entities", NbtList::from(vec![entity]));
+
+            level.insert("TileEntities", NbtList::from(vec![tile_entity("minecraft:chest", 49, 33, -101)]));
+
+            let mut tick = NbtCompound::new();
+            tick.insert("i", "minecraft:water");
+            tick.insert("x", 50);
+            tick.insert("y", 35);
+            tick.insert("z", -102);
+            level.insert("TileTicks", NbtList::from(vec![tick]));
+        }
+
+        let converter = converter(CubePos::new(0, -4, 0));
+        let cubes = converter
+            .extract_cube_data_from_tag(root.clone())
+            .unwrap_or_else(|err| panic!("{err}"));
+
+        let mut cube_ys: Vec<_> = cubes.iter().map(|(y, _)| *y).collect();
+        cube_ys.sort();
+        assert_eq!(cube_ys, (-4..12).collect::<Vec<_>>());
+
+        let (_, cube) = cubes.iter().find(|(y, _)| *y == -2).unwrap();
+        let level: &NbtCompound = cube.get("Level").unwrap();
+        assert_eq!(level.get::<_, i32>("y").unwrap(), -2);
+
+        let entities: &NbtList = level.get("Entities").unwrap();
+        let entity: &NbtCompound = entities.get(0).unwrap();
+        let pos: &NbtList = entity.get("Pos").unwrap();
+        assert_eq!(pos.get::<f64>(1).unwrap(), 40.0 - 64.0);
+        let passengers: &NbtList = entity.get("Passengers").unwrap();
+        let passenger: &NbtCompound = passengers.get(0).unwrap();
+        let passenger_pos: &NbtList = passenger.get("Pos").unwrap();
+        assert_eq!(passenger_pos.get::<f64>(1).unwrap(), 41.0 - 64.0);
+
+        let tile_entities: &NbtList = level.get("TileEntities").unwrap();
+        let chest: &NbtCompound = tile_entities.get(0).unwrap();
+        assert_eq!(chest.get::<_, i32>("y").unwrap(), 33 - 64);
+
+        let tile_ticks: &NbtList = level.get("TileTicks").unwrap();
+        let tick: &NbtCompound = tile_ticks.get(0).unwrap();
+        assert_eq!(tick.get::<_, i32>("y").unwrap(), 35 - 64);
+
+        let lighting_info: &NbtCompound = level.get("LightingInfo").unwrap();
+        assert_eq!(lighting_info.get::<_, &[i32]>("LastHeightMap").unwrap(), &[48 - 64; 256]);
+
+        let column = converter.extract_column_data_from_tag(root).unwrap_or_else(|err| panic!("{err}"));
+        let column_level: &NbtCompound = column.get("Level").unwrap();
+        let opacity_index: &[i8] = column_level.get("OpacityIndex").unwrap();
+        // first entry: minY, maxY, segment count
+        let first: Vec<u8> = opacity_index[..8].iter().map(|b| *b as u8).collect();
+        assert_eq!(first, [(-64i32).to_be_bytes(), (47i32 - 64).to_be_bytes()].concat());
+    }
+
+    #[test]
+    fn xz_offset_relocates_chunk() {
+        let mut root = chunk_tag(3, -7, &[4]);
+        {
+            let level: &mut NbtCompound = root.get_mut("Level").unwrap();
+            let mut entity = NbtCompound::new();
+            entity.insert("Pos", NbtList::from(vec![48.5f64, 70.0, -100.5]));
+            entity.insert("TileX", 48);
+            entity.insert("TileY", 70);
+            entity.insert("TileZ", -101);
+            level.insert("Entities", NbtList::from(vec![entity]));
+            level.insert("TileEntities", NbtList::from(vec![tile_entity("minecraft:chest", 49, 65, -101)]));
+        }
+        let converted = converter(CubePos::new(10, 0, -2))
+            .convert(Data {
+                position: MinecraftChunkLocation::new(3, -7),
+                data: chunk_data(&root),
+            })
+            .unwrap();
+        assert_eq!(converted.len(), 1);
+        let converted = &converted[0];
+        assert_eq!((converted.position.x, converted.position.z), (13, -9));
+
+        let column = read_compressed_cc(&converted.column_data).unwrap();
+        let column_level: &NbtCompound = column.get("Level").unwrap();
+        assert_eq!(column_level.get::<_, i32>("x").unwrap(), 13);
+        assert_eq!(column_level.get::<_, i32>("z").unwrap(), -9);
+
+        let (_, cube_data) = converted.cube_data.iter().find(|(y, _)| *y == 4).unwrap();
+        let cube = read_compressed_cc(cube_data).unwrap();
+        let level: &NbtCompound = cube.get("Level").unwrap();
+        assert_eq!(level.get::<_, i32>("x").unwrap(), 13);
+        assert_eq!(level.get::<_, i32>("z").unwrap(), -9);
+
+        let entities: &NbtList = level.get("Entities").unwrap();
+        let entity: &NbtCompound = entities.get(0).unwrap();
+        let pos: &NbtList = entity.get("Pos").unwrap();
+        assert_eq!(pos.get::<f64>(0).unwrap(), 48.5 + 160.0);
+        assert_eq!(pos.get::<f64>(2).unwrap(), -100.5 - 32.0);
+        assert_eq!(entity.get::<_, i32>("TileX").unwrap(), 48 + 160);
+        assert_eq!(entity.get::<_, i32>("TileY").unwrap(), 70);
+        assert_eq!(entity.get::<_, i32>("TileZ").unwrap(), -101 - 32);
+
+        let tile_entities: &NbtList = level.get("TileEntities").unwrap();
+        let chest: &NbtCompound = tile_entities.get(0).unwrap();
+        assert_eq!(chest.get::<_, i32>("x").unwrap(), 49 + 160);
+        assert_eq!(chest.get::<_, i32>("z").unwrap(), -101 - 32);
+
+        // every cube of the column moves with it
+        for (_, cube_data) in &converted.cube_data {
+            let cube = read_compressed_cc(cube_data).unwrap();
+            let level: &NbtCompound = cube.get("Level").unwrap();
+            assert_eq!((level.get::<_, i32>("x").unwrap(), level.get::<_, i32>("z").unwrap()), (13, -9));
+        }
+    }
+
+    #[test]
+    fn selection_drops_chunks_and_clips_cubes() {
+        // chunks 0..=1 on x, 0 on z, blocks 40..=90 on y, which is cubes 2..=5
+        let selection = Selection::from_blocks(vec![BlockAabb::from_coords(0, 40, 0, 31, 90, 15)]);
+        let converter = Anvil2CCConverter::new(
+            false,
+            false,
+            CubePos::new(0, 0, 0),
+            Some(selection),
+            PopulationMap::new(Path::new("does_not_exist")),
+        );
+
+        let outside = converter
+            .convert(Data {
+                position: MinecraftChunkLocation::new(2, 0),
+                data: chunk_data(&chunk_tag(2, 0, &[3])),
+            })
+            .unwrap();
+        assert!(outside.is_empty());
+
+        let inside = converter
+            .convert(Data {
+                position: MinecraftChunkLocation::new(1, 0),
+                data: chunk_data(&chunk_tag(1, 0, &[1, 3, 7])),
+            })
+            .unwrap();
+        assert_eq!(inside.len(), 1);
+        let mut cube_ys: Vec<_> = inside[0].cube_data.iter().map(|(y, _)| *y).collect();
+        cube_ys.sort();
+        assert_eq!(cube_ys, vec![2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn add_missing_tile_entities() {
+        // cube at negative x/z, so local coordinates have to be recovered from negative world coordinates
+        let (cube_x, cube_y, cube_z) = (-3, 4, -1);
+
+        let mut blocks = vec![0i8; 4096];
+        set_block(&mut blocks, 1, 2, 3, CHEST);
+        set_block(&mut blocks, 15, 0, 7, CHEST);
+        set_block(&mut blocks, 5, 5, 5, FURNACE);
+        set_block(&mut blocks, 0, 15, 15, SIGN);
+        set_block(&mut blocks, 8, 9, 10, HOPPER as i8);
+
+        let mut section = NbtCompound::new();
+        section.insert("Blocks", blocks);
+
+        // the chest at (1, 2, 3) already has a tile entity with contents that must be kept
+        let mut existing_chest = tile_entity("minecraft:chest", cube_x * 16 + 1, cube_y * 16 + 2, cube_z * 16 + 3);
+        existing_chest.insert("Items", NbtList::new());
+        let mut tile_entities = NbtList::new();
+        tile_entities.push(existing_chest.clone());
+
+        let blocks = SectionBlocks::read(&section).unwrap_or_else(|err| panic!("{err}")).unwrap();
+        let (tile_entities, added) = Anvil2CCConverter::add_missing_tile_entities(cube_x, cube_y, cube_z, tile_entities, &blocks)
+            .unwrap_or_else(|err| panic!("{err}"));
+
+        assert_eq!(added, 4);
+        assert_eq!(tile_entities.len(), 5);
+
+        let chests = find_tile_entity(&tile_entities, cube_x * 16 + 1, cube_y * 16 + 2, cube_z * 16 + 3);
+        assert_eq!(chests, vec![existing_chest]);
+
+        let expected = [("chest", 15, 0, 7), ("furnace", 5, 5, 5), ("sign", 0, 15, 15), ("hopper", 8, 9, 10)];
+        for (id, x, y, z) in expected {
+            let (x, y, z) = (cube_x * 16 + x, cube_y * 16 + y, cube_z * 16 + z);
+            assert_eq!(find_tile_entity(&tile_entities, x, y, z), vec![tile_entity(id, x, y, z)]);
+        }
+    }
+
+    /// A dungeon chunk generated by 1.12.2, with furnaces and signs placed without tile entities and the tile entity of one
+    /// chest removed, like worlds edited by external tools
+    #[test]
+    fn add_missing_tile_entities_in_saved_chunk() {
+        let path = test_utils::test_resources_path().join("anvil2cc/missing_tile_entities.nbt");
+        let (root, _) = io::read_nbt(&mut File::open(path).unwrap(), Flavor::ZlibCompressed).unwrap();
+        let converter = Anvil2CCConverter::new(
+            true,
+            false,
+            CubePos::new(0, 0, 0),
+            None,
+            PopulationMap::new(Path::new("does_not_exist")),
+        );
+        let converted = converter
+            .convert(Data {
+                position: MinecraftChunkLocation::new(-22, 5),
+                data: chunk_data(&root),
+            })
+            .unwrap();
+
+        let (_, cube_data) = converted[0].cube_data.iter().find(|(y, _)| *y == 3).unwrap();
+        let cube = read_compressed_cc(cube_data).unwrap();
+        let level: &NbtCompound = cube.get("Level").unwrap();
+        let tile_entities: &NbtList = level.get("TileEntities").unwrap();
+        assert_eq!(tile_entities.len(), 7);
+
+        // the saved chest and spawner are kept as they are
+        let chests = find_tile_entity(tile_entities, -352, 57, 81);
+        assert_eq!(chests.len(), 1);
+        assert_eq!(chests[0].get::<_, &str>("LootTable").unwrap(), "minecraft:chests/simple_dungeon");
+        let spawners = find_tile_entity(tile_entities, -352, 57, 84);
+        assert_eq!(spawners.len(), 1);
+        assert_eq!(spawners[0].get::<_, &str>("id").unwrap(), "minecraft:mob_spawner");
+
+        let expected = [
+            ("chest", -351, 57, 87),
+            ("furnace", -351, 57, 81),
+            ("furnace", -350, 57, 82),
+            ("sign", -351, 58, 84),
+            ("sign", -350, 58, 86),
+        ];
+        for (id, x, y, z) in expected {
+            assert_eq!(find_tile_entity(tile_entities, x, y, z), vec![tile_entity(id, x, y, z)]);
+        }
+    }
+
+    #[test]
+    fn add_missing_tile_entities_uses_add_nibbles() {
+        let mut blocks = vec![0i8; 4096];
+        // 0x0B6 | 0x100 from Add is 438, not a tile entity; 0x0D2 | 0x000 is 210, a command block
+        set_block(&mut blocks, 0, 0, 0, 0xB6u8 as i8);
+        set_block(&mut blocks, 1, 0, 0, 0xD2u8 as i8);
+
+        let mut add = vec![0i8; 2048];
+        add[0] = 0x01; // index 0 is the low nibble, index 1 the high nibble
+
+        let mut section = NbtCompound::new();
+        section.insert("Blocks", blocks);
+        section.insert("Add", add);
+
+        let blocks = SectionBlocks::read(&section).unwrap_or_else(|err| panic!("{err}")).unwrap();
+        let (tile_entities, added) =
+            Anvil2CCConverter::add_missing_tile_entities(0, 0, 0, NbtList::new(), &blocks).unwrap_or_else(|err| panic!("{err}"));
+
+        assert_eq!(added, 1);
+        assert_eq!(
+            find_tile_entity(&tile_entities, 1, 0, 0),
+            vec![tile_entity("command_block", 1, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn fix_section_only_replaces_bedrock() {
+        // NotEnoughIDs id 0x107 shares its low byte with bedrock, it must stay untouched
+        let mut blocks16 = vec![0i8; 8192];
+        blocks16[1] = 7;
+        blocks16[2] = 0x01;
+        blocks16[3] = 0x07;
+
+        let mut section = NbtCompound::new();
+        section.insert("Blocks16", blocks16);
+
+        let mut blocks = SectionBlocks::read(&section).unwrap_or_else(|err| panic!("{err}")).unwrap();
+        assert!(Anvil2CCConverter::fix_section(&mut blocks));
+        assert_eq!(blocks.id(0), 1);
+        assert_eq!(blocks.id(1), 0x107);
+    }
+
+    #[test]
+    fn skip_empty_cubes() {
+        let mut root = chunk_tag(0, 0, &[2, 3, 20, 21]);
+        {
+            let level: &mut NbtCompound = root.get_mut("Level").unwrap();
+            let sections: &mut NbtList = level.get_mut("Sections").unwrap();
+            for idx in 0..3 {
+                let section: &mut NbtCompound = sections.get_mut(idx).unwrap();
+                section.insert("Blocks", vec![0i8; 4096]);
+            }
+            let mut entity = NbtCompound::new();
+            entity.insert("Pos", NbtList::from(vec![0.5f64, 50.0, 0.5]));
+            level.insert("Entities", NbtList::from(vec![entity]));
+        }
+
+        let converter = Anvil2CCConverter::new(
+            false,
+            true,
+            CubePos::new(0, 0, 0),
+            None,
+            PopulationMap::new(Path::new("does_not_exist")),
+        );
+        let cubes = converter.extract_cube_data_from_tag(root).unwrap_or_else(|err| panic!("{err}"));
+
+        // section 20 is all air and is dropped, 2 is empty but required by cubic chunks
+        let mut cube_ys: Vec<_> = cubes.iter().map(|(y, _)| *y).collect();
+        cube_ys.sort();
+        assert_eq!(cube_ys, (0..16).chain([21]).collect::<Vec<_>>());
+
+        let sections_of = |cube_y: i32| {
+            let (_, cube) = cubes.iter().find(|(y, _)| *y == cube_y).unwrap();
+            cube.get::<_, &NbtCompound>("Level").unwrap().contains_key("Sections")
+        };
+        assert!(!sections_of(2));
+        assert!(sections_of(3), "the entity keeps the air section");
+        assert!(sections_of(21));
+    }
+
+    #[cfg(feature = "dfu")]
+    #[test]
+    fn upgrade_chunk_before_splitting() {
+        use crate::convert::anvil2cc::upgrade::{Upgrader, DATA_VERSION_1_12_2};
+        use std::sync::Arc;
+
+        // saved before 1.9, without DataVersion and with numeric item ids
+        let mut root = chunk_tag(0, 0, &[4]);
+        root.inner_mut().remove("DataVersion");
+        {
+            let level: &mut NbtCompound = root.get_mut("Level").unwrap();
+            let mut stack = NbtCompound::new();
+            stack.insert("id", 264i16);
+            stack.insert("Count", 1i8);
+            stack.insert("Damage", 0i16);
+            let mut entity = NbtCompound::new();
+            entity.insert("id", "Item");
+            entity.insert("Pos", NbtList::from(vec![0.5f64, 70.0, 0.5]));
+            entity.insert("Item", stack);
+            level.insert("Entities", NbtList::from(vec![entity]));
+        }
+
+        let converter = converter(CubePos::new(0, 0, 0)).with_upgrader(Arc::new(Upgrader::new(DATA_VERSION_1_12_2)));
+        let converted = converter
+            .convert(Data {
+                position: MinecraftChunkLocation::new(0, 0),
+                data: chunk_data(&root),
+            })
+            .unwrap();
+
+        let column = read_compressed_cc(&converted[0].column_data).unwrap();
+        assert_eq!(column.get::<_, i32>("DataVersion").unwrap(), DATA_VERSION_1_12_2);
+        let (_, cube_data) = converted[0].cube_data.iter().find(|(y, _)| *y == 4).unwrap();
+        let cube = read_compressed_cc(cube_data).unwrap();
+        assert_eq!(cube.get::<_, i32>("DataVersion").unwrap(), DATA_VERSION_1_12_2);
+
+        let level: &NbtCompound = cube.get("Level").unwrap();
+        let entities: &NbtList = level.get("Entities").unwrap();
+        let entity: &NbtCompound = entities.get(0).unwrap();
+        assert_eq!(entity.get::<_, &str>("id").unwrap(), "minecraft:item");
+        let stack: &NbtCompound = entity.get("Item").unwrap();
+        assert_eq!(stack.get::<_, &str>("id").unwrap(), "minecraft:diamond");
+    }
+}
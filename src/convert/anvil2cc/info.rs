@@ -1,3 +1,5 @@
+#[cfg(feature = "dfu")]
+use std::sync::Arc;
 use std::{
     io::Cursor,
     path::{Path, PathBuf},
@@ -5,6 +7,8 @@ use std::{
 
 use quartz_nbt::{NbtCompound, NbtTag};
 
+#[cfg(feature = "dfu")]
+use crate::convert::anvil2cc::upgrade::Upgrader;
 use crate::{
     convert::{converter::ConversionError, info_converter::InfoConverter},
     util::file::copy_everything_except,
@@ -17,6 +21,9 @@ where
     src_dir: PathBuf,
     dst_dir: PathBuf,
     exclude: F,
+    /// Upgrades level.dat and the players in playerdata saved by older versions of the game
+    #[cfg(feature = "dfu")]
+    upgrader: Option<Arc<Upgrader>>,
 }
 
 impl<F: Fn(&Path, &Path) -> bool + Send> Anvil2CCLevelInfoConverter<F> {
@@ -25,8 +32,42 @@ impl<F: Fn(&Path, &Path) -> bool + Send> Anvil2CCLevelInfoConverter<F> {
             src_dir: src_path.to_path_buf(),
             dst_dir: dst_path.to_path_buf(),
             exclude,
+            #[cfg(feature = "dfu")]
+            upgrader: None,
         }
     }
+
+    #[cfg(feature = "dfu")]
+    pub fn with_upgrader(mut self, upgrader: Arc<Upgrader>) -> Self {
+        self.upgrader = Some(upgrader);
+        self
+    }
+
+    fn copy_any_modify_level_dat(&self, src: &Path, dst: &Path) -> Result<(), ConversionError> {
+        let mut data = std::fs::read(src)?;
+        let (mut tag, root_name) = quartz_nbt::io::read_nbt(&mut Cursor::new(&mut data), quartz_nbt::io::Flavor::GzCompressed)?;
+
+        let data_tag: &mut NbtCompound = tag.get_mut("Data")?;
+
+        #[cfg(feature = "dfu")]
+        if let Some(upgrader) = &self.upgrader {
+            upgrader.upgrade_level(data_tag).map_err(ConversionError::Custom)?;
+        }
+
+        let gen_name: &mut NbtTag = data_tag.get_mut("generatorName")?;
+        if let NbtTag::String(name) = gen_name {
+            if name.eq_ignore_ascii_case("default") {
+                *name = "VanillaCubic".to_string();
+            }
+        }
+        data_tag.insert("isCubicWorld", NbtTag::Byte(1i8));
+
+        let mut out_data = Vec::new();
+        quartz_nbt::io::write_nbt(&mut out_data, Some(&root_name), &tag, quartz_nbt::io::Flavor::GzCompressed)?;
+        std::fs::write(dst, out_data)?;
+
+        Ok(())
+    }
 }
 
 impl<F: Fn(&Path, &Path) -> bool + Send> InfoConverter for Anvil2CCLevelInfoConverter<F> {
@@ -37,29 +78,35 @@ impl<F: Fn(&Path, &Path) -> bool + Send> InfoConverter for Anvil2CCLevelInfoConv
 
         let level_dat_path = self.src_dir.join("level.dat");
         if level_dat_path.exists() && level_dat_path.is_file() {
-            copy_any_modify_level_dat(&level_dat_path, &self.dst_dir.join("level.dat"))?;
+            self.copy_any_modify_level_dat(&level_dat_path, &self.dst_dir.join("level.dat"))?;
+        }
+
+        #[cfg(feature = "dfu")]
+        if let Some(upgrader) = &self.upgrader {
+            upgrade_player_data(upgrader, &self.dst_dir.join("playerdata"))?;
         }
         Ok(())
     }
 }
 
-fn copy_any_modify_level_dat(src: &Path, dst: &Path) -> Result<(), ConversionError> {
-    let mut data = std::fs::read(src)?;
-    let (mut tag, root_name) = quartz_nbt::io::read_nbt(&mut Cursor::new(&mut data), quartz_nbt::io::Flavor::GzCompressed)?;
-
-    let data_tag: &mut NbtCompound = tag.get_mut("Data")?;
-
-    let gen_name: &mut NbtTag = data_tag.get_mut("generatorName")?;
-    if let NbtTag::String(name) = gen_name {
-        if name.eq_ignore_ascii_case("default") {
-            *name = "VanillaCubic".to_string();
-        }
+/// Upgrades the players in the playerdata directory, in place
+#[cfg(feature = "dfu")]
+fn upgrade_player_data(upgrader: &Upgrader, dir: &Path) -> Result<(), ConversionError> {
+    if !dir.is_dir() {
+        return Ok(());
     }
-    data_tag.insert("isCubicWorld", NbtTag::Byte(1i8));
-
-    let mut out_data = Vec::new();
-    quartz_nbt::io::write_nbt(&mut out_data, Some(&root_name), &tag, quartz_nbt::io::Flavor::GzCompressed)?;
-    std::fs::write(dst, out_data)?;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "dat") {
+            continue;
+        }
+        let data = std::fs::read(&path)?;
+        let (mut player, root_name) = quartz_nbt::io::read_nbt(&mut Cursor::new(data), quartz_nbt::io::Flavor::GzCompressed)?;
+        upgrader.upgrade_player(&mut player).map_err(ConversionError::Custom)?;
 
+        let mut out_data = Vec::new();
+        quartz_nbt::io::write_nbt(&mut out_data, Some(&root_name), &player, quartz_nbt::io::Flavor::GzCompressed)?;
+        std::fs::write(&path, out_data)?;
+    }
     Ok(())
 }
@@ -1,2 +1,6 @@
 pub mod conv;
 pub mod info;
+pub mod population;
+pub mod section;
+#[cfg(feature = "dfu")]
+pub mod upgrade;
@@ -0,0 +1,235 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use once_cell::sync::OnceCell;
+use quartz_nbt::NbtCompound;
+
+use crate::{
+    convert::entry_location::RegionPos,
+    util::{compress::read_compressed, positions::MinecraftRegionPos},
+};
+
+const SECTOR_SIZE: u64 = 4096;
+/// Regions whose flags are kept, the least recently used region is dropped past this. The regions being converted and
+/// their -x and -z neighbours fit with room to spare, so regions are only dropped once the reader has passed them.
+const CACHED_REGIONS: usize = 16;
+
+/// `TerrainPopulated` flags of the chunks in the source world, loaded lazily one chunk at a time.
+///
+/// Vanilla decorates a chunk at an offset of 8 blocks, so the blocks of a chunk are only final once the chunk itself and its
+/// -x, -z and -x-z neighbours have been populated. CubicChunks tracks that as `fullyPopulated`.
+///
+/// The converter records the flag of every chunk it converts with [`PopulationMap::set_populated`]. Neighbours which haven't
+/// been converted yet, or are outside of the converted part of the world, are read from their region file, which costs
+/// decompressing that chunk a second time.
+pub struct PopulationMap {
+    region_dir: PathBuf,
+    regions: Mutex<RegionCache>,
+}
+
+#[derive(Default)]
+struct RegionCache {
+    regions: HashMap<MinecraftRegionPos, Arc<RegionFlags>>,
+    /// Cached regions from least to most recently used
+    recent: VecDeque<MinecraftRegionPos>,
+}
+
+struct RegionFlags {
+    /// The location of each chunk in the region file, read when the first flag is. None for a missing or broken region.
+    locations: OnceCell<Option<Vec<u32>>>,
+    flags: Vec<OnceCell<bool>>,
+}
+
+impl PopulationMap {
+    pub fn new(src_path: &Path) -> Self {
+        Self {
+            region_dir: src_path.join("region"),
+            regions: Mutex::new(RegionCache::default()),
+        }
+    }
+
+    pub fn is_populated(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        let (region_pos, region, idx) = self.region(chunk_x, chunk_z);
+        *region.flags[idx].get_or_init(|| self.read_flag(region_pos, &region, idx))
+    }
+
+    /// Records the flag of a chunk read elsewhere, so it isn't read again as a neighbour
+    pub fn set_populated(&self, chunk_x: i32, chunk_z: i32, populated: bool) {
+        let (_, region, idx) = self.region(chunk_x, chunk_z);
+        let _ = region.flags[idx].set(populated);
+    }
+
+    pub fn is_fully_populated(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.is_populated(chunk_x, chunk_z)
+            && self.is_populated(chunk_x - 1, chunk_z)
+            && self.is_populated(chunk_x, chunk_z - 1)
+            && self.is_populated(chunk_x - 1, chunk_z - 1)
+    }
+
+    /// The cached region of a chunk, along with the index of the chunk in it
+    fn region(&self, chunk_x: i32, chunk_z: i32) -> (MinecraftRegionPos, Arc<RegionFlags>, usize) {
+        let region_pos = MinecraftRegionPos::new(chunk_x >> 5, chunk_z >> 5);
+        let idx = (chunk_x & 31) as usize + (chunk_z & 31) as usize * MinecraftRegionPos::DIAMETER_IN_CHUNKS;
+
+        let mut cache = self.regions.lock().unwrap();
+        if let Some(recent_idx) = cache.recent.iter().position(|pos| *pos == region_pos) {
+            cache.recent.remove(recent_idx);
+        } else if cache.recent.len() == CACHED_REGIONS {
+            let oldest = cache.recent.pop_front().expect("the cache is full");
+            cache.regions.remove(&oldest);
+        }
+        cache.recent.push_back(region_pos);
+        let region = cache
+            .regions
+            .entry(region_pos)
+            .or_insert_with(|| {
+                Arc::new(RegionFlags {
+                    locations: OnceCell::new(),
+                    flags: (0..MinecraftRegionPos::CHUNKS_COUNT).map(|_| OnceCell::new()).collect(),
+                })
+            })
+            .clone();
+        (region_pos, region, idx)
+    }
+
+    /// Reads the flag of one chunk from its region file. Missing or broken chunks and regions are treated as never populated.
+    fn read_flag(&self, region_pos: MinecraftRegionPos, region: &RegionFlags, idx: usize) -> bool {
+        let path = self.region_dir.join(region_pos.region_key());
+        let location = match region.locations.get_or_init(|| read_locations(&path).ok()) {
+            Some(locations) => locations[idx],
+            None => return false,
+        };
+        if location == 0 {
+            return false;
+        }
+        let tag = match read_chunk(&path, location) {
+            Ok(chunk_data) => read_compressed(&chunk_data),
+            Err(_) => return false,
+        };
+        tag.ok()
+            .and_then(|tag| {
+                tag.get::<_, &NbtCompound>("Level")
+                    .and_then(|level| level.get::<_, i8>("TerrainPopulated"))
+                    .ok()
+            })
+            .unwrap_or(0)
+            != 0
+    }
+}
+
+/// The packed sector offset and count of every chunk in the header of a region file
+fn read_locations(path: &Path) -> std::io::Result<Vec<u32>> {
+    let mut header = vec![0u8; MinecraftRegionPos::CHUNKS_COUNT * 4];
+    File::open(path)?.read_exact(&mut header)?;
+    Ok(header
+        .chunks_exact(4)
+        .map(|location| u32::from_be_bytes(location.try_into().unwrap()))
+        .collect())
+}
+
+/// The compression type and data of the chunk at `location`
+fn read_chunk(path: &Path, location: u32) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(u64::from(location >> 8) * SECTOR_SIZE))?;
+    let mut length = [0u8; 4];
+    file.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length) as u64;
+    if length == 0 || length > u64::from(location & 0xFF) * SECTOR_SIZE {
+        return Err(std::io::ErrorKind::InvalidData.into());
+    }
+    let mut chunk_data = Vec::with_capacity(length as usize);
+    file.take(length).read_to_end(&mut chunk_data)?;
+    Ok(chunk_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use quartz_nbt::NbtCompound;
+
+    use super::{PopulationMap, CACHED_REGIONS};
+    use crate::util::{compress::write_compressed, test_utils};
+
+    /// Writes a region file holding `chunks`, given as chunk x, chunk z and their `TerrainPopulated` flag
+    fn write_region(world: &Path, region_x: i32, region_z: i32, chunks: &[(i32, i32, bool)]) {
+        let mut region = vec![0u8; 8192];
+        for (sector, (chunk_x, chunk_z, populated)) in (2u32..).zip(chunks) {
+            assert_eq!((chunk_x >> 5, chunk_z >> 5), (region_x, region_z));
+            let mut level = NbtCompound::new();
+            level.insert("TerrainPopulated", *populated as i8);
+            let mut root = NbtCompound::new();
+            root.insert("Level", level);
+            let compressed = write_compressed(&root, false).unwrap();
+
+            let idx = ((chunk_x & 31) + (chunk_z & 31) * 32) as usize * 4;
+            region[idx..idx + 4].copy_from_slice(&(sector << 8 | 1).to_be_bytes());
+            let mut chunk = (compressed.len() as u32 + 1).to_be_bytes().to_vec();
+            chunk.push(1);
+            chunk.extend(compressed);
+            chunk.resize(4096, 0);
+            region.extend(chunk);
+        }
+        fs::write(world.join("region").join(format!("r.{region_x}.{region_z}.mca")), region).unwrap();
+    }
+
+    fn world(name: &str) -> std::path::PathBuf {
+        let world = test_utils::test_output_path(name);
+        fs::create_dir(world.join("region")).unwrap();
+        world
+    }
+
+    #[test]
+    fn fully_populated_needs_the_chunk_and_its_neighbours() {
+        let world = world("population_neighbours");
+        write_region(&world, 0, 0, &[(4, 4, true), (3, 4, true), (4, 3, true), (3, 3, true)]);
+        assert!(PopulationMap::new(&world).is_fully_populated(4, 4));
+
+        for missing in [(4, 4), (3, 4), (4, 3), (3, 3)] {
+            let populated = [(4, 4), (3, 4), (4, 3), (3, 3)].map(|(x, z)| (x, z, (x, z) != missing));
+            write_region(&world, 0, 0, &populated);
+            assert!(!PopulationMap::new(&world).is_fully_populated(4, 4), "{missing:?} isn't populated");
+
+            let saved = populated.into_iter().filter(|(x, z, _)| (*x, *z) != missing).collect::<Vec<_>>();
+            write_region(&world, 0, 0, &saved);
+            assert!(!PopulationMap::new(&world).is_fully_populated(4, 4), "{missing:?} isn't saved");
+        }
+    }
+
+    #[test]
+    fn neighbours_in_other_regions() {
+        let world = world("population_regions");
+        write_region(&world, 0, 0, &[(0, 0, true)]);
+        write_region(&world, -1, 0, &[(-1, 0, true)]);
+        write_region(&world, 0, -1, &[(0, -1, true)]);
+        write_region(&world, -1, -1, &[(-1, -1, true)]);
+        assert!(PopulationMap::new(&world).is_fully_populated(0, 0));
+
+        fs::remove_file(world.join("region/r.-1.-1.mca")).unwrap();
+        assert!(!PopulationMap::new(&world).is_fully_populated(0, 0), "missing region");
+
+        write_region(&world, -1, -1, &[(-1, -1, true)]);
+        fs::write(world.join("region/r.-1.0.mca"), [0u8; 100]).unwrap();
+        assert!(!PopulationMap::new(&world).is_fully_populated(0, 0), "region without a header");
+    }
+
+    #[test]
+    fn recorded_flags_are_used_before_region_files() {
+        let world = world("population_recorded");
+        write_region(&world, 0, 0, &[(4, 4, true), (3, 4, true), (4, 3, true), (3, 3, false)]);
+        let population = PopulationMap::new(&world);
+        population.set_populated(3, 3, true);
+        assert!(population.is_fully_populated(4, 4));
+
+        // evicted regions are read from their files again
+        for region_x in 1..=CACHED_REGIONS as i32 {
+            assert!(!population.is_populated(region_x * 32, 0));
+        }
+        assert!(!population.is_fully_populated(4, 4));
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use quartz_nbt::{NbtCompound, NbtTag};
+
+use crate::convert::anvil2cc::conv::{Anvil2CCConversionError, InvalidChunkTagError};
+
+pub const BLOCKS_PER_SECTION: usize = 4096;
+
+/// Block storage layouts an anvil section can be saved in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SectionFormat {
+    /// `Blocks`, `Data` and an optional `Add`, up to 12 bit ids.
+    Vanilla,
+    /// The vanilla layout with an extra `Add2` nibble array for bits 12..16 of the id.
+    Add2,
+    /// NotEnoughIDs: `Blocks16` and `Data16`, big endian 16 bit ids and metadata.
+    /// Vanilla arrays may be stored next to them for compatibility.
+    NotEnoughIds,
+    /// JustEnoughIDs: `Blocks`, `Data` and `Add` hold a 16 bit index into the `Palette` int array,
+    /// palette entries are `id << 4 | meta`.
+    JustEnoughIds,
+}
+
+impl SectionFormat {
+    pub fn detect(section: &NbtCompound) -> Option<Self> {
+        if let Ok(NbtTag::IntArray(_)) = section.get::<_, &NbtTag>("Palette") {
+            Some(Self::JustEnoughIds)
+        } else if section.contains_key("Blocks16") {
+            Some(Self::NotEnoughIds)
+        } else if section.contains_key("Add2") {
+            Some(Self::Add2)
+        } else if section.contains_key("Blocks") {
+            Some(Self::Vanilla)
+        } else {
+            None
+        }
+    }
+}
+
+/// Block ids and metadata of a section, decoded from whichever [`SectionFormat`] it was stored in.
+pub struct SectionBlocks {
+    format: SectionFormat,
+    ids: Vec<u16>,
+    meta: Vec<u16>,
+}
+
+impl SectionBlocks {
+    /// Decodes the blocks of a section, returns `None` if the section has no block data.
+    pub fn read(section: &NbtCompound) -> Result<Option<Self>, Anvil2CCConversionError> {
+        let format = match SectionFormat::detect(section) {
+            Some(format) => format,
+            None => return Ok(None),
+        };
+        let mut ids = vec![0; BLOCKS_PER_SECTION];
+        let mut meta = vec![0; BLOCKS_PER_SECTION];
+
+        match format {
+            SectionFormat::Vanilla | SectionFormat::Add2 => {
+                let blocks = get_array(section, "Blocks", BLOCKS_PER_SECTION)?.ok_or_else(|| missing("Blocks"))?;
+                let data = get_array(section, "Data", BLOCKS_PER_SECTION / 2)?;
+                let add = get_array(section, "Add", BLOCKS_PER_SECTION / 2)?;
+                let add2 = get_array(section, "Add2", BLOCKS_PER_SECTION / 2)?;
+                for i in 0..BLOCKS_PER_SECTION {
+                    ids[i] = blocks[i] as u8 as u16 | opt_nibble(add, i) << 8 | opt_nibble(add2, i) << 12;
+                    meta[i] = opt_nibble(data, i);
+                }
+            }
+            SectionFormat::NotEnoughIds => {
+                let blocks16 = get_array(section, "Blocks16", BLOCKS_PER_SECTION * 2)?.ok_or_else(|| missing("Blocks16"))?;
+                let data16 = get_array(section, "Data16", BLOCKS_PER_SECTION * 2)?;
+                for i in 0..BLOCKS_PER_SECTION {
+                    ids[i] = get_short(blocks16, i);
+                    meta[i] = data16.map_or(0, |data16| get_short(data16, i));
+                }
+            }
+            SectionFormat::JustEnoughIds => {
+                let palette = section.get::<_, &[i32]>("Palette")?;
+                let blocks = get_array(section, "Blocks", BLOCKS_PER_SECTION)?.ok_or_else(|| missing("Blocks"))?;
+                let data = get_array(section, "Data", BLOCKS_PER_SECTION / 2)?;
+                let add = get_array(section, "Add", BLOCKS_PER_SECTION / 2)?;
+                for i in 0..BLOCKS_PER_SECTION {
+                    let palette_idx = blocks[i] as u8 as usize | (opt_nibble(data, i) as usize) << 8 | (opt_nibble(add, i) as usize) << 12;
+                    let state = *palette.get(palette_idx).ok_or_else(|| {
+                        InvalidChunkTagError::new(&format!("Palette index {palette_idx} out of bounds ({} entries)", palette.len()))
+                    })?;
+                    ids[i] = (state >> 4) as u16;
+                    meta[i] = (state & 0xF) as u16;
+                }
+            }
+        }
+
+        Ok(Some(Self { format, ids, meta }))
+    }
+
+    pub fn id(&self, idx: usize) -> u16 {
+        self.ids[idx]
+    }
+
+    pub fn meta(&self, idx: usize) -> u16 {
+        self.meta[idx]
+    }
+
+    pub fn set(&mut self, idx: usize, id: u16, meta: u16) {
+        self.ids[idx] = id;
+        self.meta[idx] = meta;
+    }
+
+    /// Whether every block is air. Metadata of air is ignored
+    pub fn is_air(&self) -> bool {
+        self.ids.iter().all(|&id| id == 0)
+    }
+
+    /// Encodes the blocks back into `section`, in the same format they were read from.
+    pub fn write(&self, section: &mut NbtCompound) {
+        match self.format {
+            SectionFormat::Vanilla => self.write_vanilla(section, false),
+            SectionFormat::Add2 => self.write_vanilla(section, true),
+            SectionFormat::NotEnoughIds => {
+                let mut blocks16 = vec![0i8; BLOCKS_PER_SECTION * 2];
+                let mut data16 = vec![0i8; BLOCKS_PER_SECTION * 2];
+                for i in 0..BLOCKS_PER_SECTION {
+                    set_short(&mut blocks16, i, self.ids[i]);
+                    set_short(&mut data16, i, self.meta[i]);
+                }
+                section.insert("Blocks16", blocks16);
+                section.insert("Data16", data16);
+                // keep the compatibility arrays in sync, if the mod wrote them
+                if section.contains_key("Blocks") {
+                    self.write_vanilla(section, false);
+                }
+            }
+            SectionFormat::JustEnoughIds => {
+                let mut palette = Vec::new();
+                let mut palette_ids = HashMap::new();
+                let mut blocks = vec![0i8; BLOCKS_PER_SECTION];
+                let mut data = vec![0i8; BLOCKS_PER_SECTION / 2];
+                let mut add = vec![0i8; BLOCKS_PER_SECTION / 2];
+                for (i, block) in blocks.iter_mut().enumerate() {
+                    let state = (self.ids[i] as i32) << 4 | (self.meta[i] & 0xF) as i32;
+                    let palette_idx = *palette_ids.entry(state).or_insert_with(|| {
+                        palette.push(state);
+                        palette.len() - 1
+                    });
+                    *block = palette_idx as u8 as i8;
+                    set_nibble(&mut data, i, (palette_idx >> 8) as u16);
+                    set_nibble(&mut add, i, (palette_idx >> 12) as u16);
+                }
+                section.insert("Blocks", blocks);
+                section.insert("Data", data);
+                section.insert("Add", add);
+                section.insert("Palette", palette);
+            }
+        }
+    }
+
+    fn write_vanilla(&self, section: &mut NbtCompound, with_add2: bool) {
+        let mut blocks = vec![0i8; BLOCKS_PER_SECTION];
+        let mut data = vec![0i8; BLOCKS_PER_SECTION / 2];
+        let mut add = vec![0i8; BLOCKS_PER_SECTION / 2];
+        let mut add2 = vec![0i8; BLOCKS_PER_SECTION / 2];
+        for (i, block) in blocks.iter_mut().enumerate() {
+            *block = self.ids[i] as u8 as i8;
+            set_nibble(&mut data, i, self.meta[i]);
+            set_nibble(&mut add, i, self.ids[i] >> 8);
+            set_nibble(&mut add2, i, self.ids[i] >> 12);
+        }
+        section.insert("Blocks", blocks);
+        section.insert("Data", data);
+        if section.contains_key("Add") || add.iter().any(|v| *v != 0) {
+            section.insert("Add", add);
+        }
+        if with_add2 {
+            section.insert("Add2", add2);
+        }
+    }
+}
+
+/// Vanilla nibble order: even indices are stored in the low half of the byte, odd indices in the high half.
+fn get_nibble(array: &[i8], i: usize) -> u16 {
+    let v = array[i >> 1] as u8;
+    let shifted_value = if (i & 1) == 0 { v } else { v >> 4 };
+    (shifted_value & 0xF) as u16
+}
+
+fn set_nibble(array: &mut [i8], i: usize, value: u16) {
+    let v = array[i >> 1] as u8;
+    let value = (value & 0xF) as u8;
+    array[i >> 1] = if (i & 1) == 0 {
+        (v & 0xF0) | value
+    } else {
+        (v & 0x0F) | value << 4
+    } as i8;
+}
+
+fn opt_nibble(array: Option<&[i8]>, i: usize) -> u16 {
+    array.map_or(0, |array| get_nibble(array, i))
+}
+
+fn get_short(array: &[i8], i: usize) -> u16 {
+    (array[i * 2] as u8 as u16) << 8 | array[i * 2 + 1] as u8 as u16
+}
+
+fn set_short(array: &mut [i8], i: usize, value: u16) {
+    array[i * 2] = (value >> 8) as u8 as i8;
+    array[i * 2 + 1] = value as u8 as i8;
+}
+
+fn get_array<'a>(section: &'a NbtCompound, key: &str, expected_len: usize) -> Result<Option<&'a [i8]>, Anvil2CCConversionError> {
+    match section.get::<_, &[i8]>(key) {
+        Ok(array) if array.len() == expected_len => Ok(Some(array)),
+        Ok(array) => Err(InvalidChunkTagError::new(&format!("Section {key} has length {}, expected {expected_len}", array.len())).into()),
+        Err(_) if !section.contains_key(key) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn missing(key: &str) -> InvalidChunkTagError {
+    InvalidChunkTagError::new(&format!("Section is missing {key}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use quartz_nbt::NbtCompound;
+
+    use super::{get_nibble, SectionBlocks, SectionFormat, BLOCKS_PER_SECTION};
+
+    fn round_trip(section: &NbtCompound, format: SectionFormat) -> SectionBlocks {
+        let blocks = SectionBlocks::read(section).unwrap_or_else(|err| panic!("{err}")).unwrap();
+        assert_eq!(blocks.format, format);
+
+        let mut written = NbtCompound::new();
+        blocks.write(&mut written);
+        let reread = SectionBlocks::read(&written).unwrap_or_else(|err| panic!("{err}")).unwrap();
+        assert_eq!(reread.format, format);
+        assert_eq!(reread.ids, blocks.ids);
+        assert_eq!(reread.meta, blocks.meta);
+        blocks
+    }
+
+    #[test]
+    fn vanilla_with_add() {
+        let mut blocks = vec![0i8; BLOCKS_PER_SECTION];
+        blocks[0] = 0xB6u8 as i8;
+        blocks[1] = 7;
+        let mut data = vec![0i8; BLOCKS_PER_SECTION / 2];
+        data[0] = 0x53;
+        let mut add = vec![0i8; BLOCKS_PER_SECTION / 2];
+        add[0] = 0x01;
+
+        let mut section = NbtCompound::new();
+        section.insert("Blocks", blocks);
+        section.insert("Data", data);
+        section.insert("Add", add);
+
+        let blocks = round_trip(&section, SectionFormat::Vanilla);
+        assert_eq!((blocks.id(0), blocks.meta(0)), (0x1B6, 3));
+        assert_eq!((blocks.id(1), blocks.meta(1)), (7, 5));
+    }
+
+    #[test]
+    fn add2() {
+        let mut blocks = vec![0i8; BLOCKS_PER_SECTION];
+        blocks[1] = 0x34;
+        let mut add = vec![0i8; BLOCKS_PER_SECTION / 2];
+        add[0] = 0x20;
+        let mut add2 = vec![0i8; BLOCKS_PER_SECTION / 2];
+        add2[0] = 0x10;
+
+        let mut section = NbtCompound::new();
+        section.insert("Blocks", blocks);
+        section.insert("Data", vec![0i8; BLOCKS_PER_SECTION / 2]);
+        section.insert("Add", add);
+        section.insert("Add2", add2);
+
+        let blocks = round_trip(&section, SectionFormat::Add2);
+        assert_eq!(blocks.id(1), 0x1234);
+    }
+
+    #[test]
+    fn not_enough_ids() {
+        let mut blocks16 = vec![0i8; BLOCKS_PER_SECTION * 2];
+        blocks16[2] = 0x12;
+        blocks16[3] = 0x07;
+        let mut data16 = vec![0i8; BLOCKS_PER_SECTION * 2];
+        data16[3] = 0x0E;
+
+        let mut section = NbtCompound::new();
+        section.insert("Blocks16", blocks16);
+        section.insert("Data16", data16);
+        // compatibility arrays only hold the low bits
+        section.insert("Blocks", vec![0i8; BLOCKS_PER_SECTION]);
+
+        let blocks = round_trip(&section, SectionFormat::NotEnoughIds);
+        assert_eq!((blocks.id(1), blocks.meta(1)), (0x1207, 0xE));
+    }
+
+    #[test]
+    fn just_enough_ids() {
+        let mut blocks = vec![0i8; BLOCKS_PER_SECTION];
+        blocks[5] = 1;
+
+        let mut section = NbtCompound::new();
+        section.insert("Blocks", blocks);
+        section.insert("Data", vec![0i8; BLOCKS_PER_SECTION / 2]);
+        section.insert("Palette", vec![0, 5000 << 4 | 3]);
+
+        let blocks = round_trip(&section, SectionFormat::JustEnoughIds);
+        assert_eq!((blocks.id(0), blocks.meta(0)), (0, 0));
+        assert_eq!((blocks.id(5), blocks.meta(5)), (5000, 3));
+    }
+
+    #[test]
+    fn get_nibble_matches_vanilla_order() {
+        let array = [0x21u8 as i8, 0xF3u8 as i8];
+        assert_eq!(get_nibble(&array, 0), 0x1);
+        assert_eq!(get_nibble(&array, 1), 0x2);
+        assert_eq!(get_nibble(&array, 2), 0x3);
+        assert_eq!(get_nibble(&array, 3), 0xF);
+    }
+}
@@ -0,0 +1,126 @@
+use std::{error::Error, io::Cursor, path::Path};
+
+use dfu::{DataFixer, ModdedItemIds, TypeReference};
+use quartz_nbt::{io::Flavor, NbtCompound};
+
+/// The DataVersion of 1.12.2, which cubic chunks runs on
+pub const DATA_VERSION_1_12_2: i32 = 1343;
+
+/// Upgrades data saved by older versions of the game to `data_version` before it is converted. Data at or above that
+/// version is left as it is.
+pub struct Upgrader {
+    fixer: DataFixer,
+    data_version: i32,
+}
+
+impl Upgrader {
+    pub fn new(data_version: i32) -> Self {
+        Self {
+            fixer: DataFixer::new(),
+            data_version,
+        }
+    }
+
+    /// An upgrader for the world in `world_dir`, which converts numeric item ids with the ids of its mods if Forge saved
+    /// them in its level.dat
+    pub fn for_world(world_dir: &Path, data_version: i32) -> Self {
+        match read_modded_item_ids(&world_dir.join("level.dat")) {
+            Some(ids) => Self {
+                fixer: DataFixer::with_modded_item_ids(ids),
+                data_version,
+            },
+            None => Self::new(data_version),
+        }
+    }
+
+    /// Upgrades a chunk as stored in a region file
+    pub fn upgrade_chunk(&self, chunk: &mut NbtCompound) -> Result<(), Box<dyn Error>> {
+        let from = data_version(chunk);
+        self.upgrade(TypeReference::Chunk, chunk, from)
+    }
+
+    /// Upgrades the `Data` compound of level.dat, along with the singleplayer player stored in it
+    pub fn upgrade_level(&self, data: &mut NbtCompound) -> Result<(), Box<dyn Error>> {
+        let from = data_version(data);
+        if let Ok(player) = data.get_mut::<_, &mut NbtCompound>("Player") {
+            // older players don't store a version of their own, they are as old as the level
+            let player_from = player.get::<_, i32>("DataVersion").unwrap_or(from);
+            self.upgrade(TypeReference::Player, player, player_from)?;
+        }
+        self.upgrade(TypeReference::Level, data, from)
+    }
+
+    /// Upgrades a player from the playerdata directory
+    pub fn upgrade_player(&self, player: &mut NbtCompound) -> Result<(), Box<dyn Error>> {
+        let from = data_version(player);
+        self.upgrade(TypeReference::Player, player, from)
+    }
+
+    fn upgrade(&self, type_ref: TypeReference, data: &mut NbtCompound, from: i32) -> Result<(), Box<dyn Error>> {
+        if from >= self.data_version {
+            return Ok(());
+        }
+        self.fixer
+            .update(type_ref, data, from.max(0) as usize, self.data_version as usize)?;
+        data.insert("DataVersion", self.data_version);
+        Ok(())
+    }
+}
+
+fn read_modded_item_ids(level_dat: &Path) -> Option<ModdedItemIds> {
+    let data = std::fs::read(level_dat).ok()?;
+    let (root, _) = quartz_nbt::io::read_nbt(&mut Cursor::new(data), Flavor::GzCompressed).ok()?;
+    let fml = root.get::<_, &NbtCompound>("FML").ok()?;
+    Some(ModdedItemIds::from_forge_registry(fml))
+}
+
+/// Data saved before 1.9 has no DataVersion
+fn data_version(data: &NbtCompound) -> i32 {
+    data.get::<_, i32>("DataVersion").unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use quartz_nbt::{io::Flavor, snbt, NbtCompound, NbtList};
+
+    use super::{Upgrader, DATA_VERSION_1_12_2};
+    use crate::util::test_utils;
+
+    #[test]
+    fn upgrade_level_and_player() {
+        let upgrader = Upgrader::new(DATA_VERSION_1_12_2);
+
+        // saved before 1.9, without DataVersion
+        let mut data = snbt::parse(r#"{Player: {Inventory: [{id: 264s, Count: 1b, Damage: 0s}]}}"#).unwrap();
+        upgrader.upgrade_level(&mut data).unwrap();
+        assert_eq!(data.get::<_, i32>("DataVersion").unwrap(), DATA_VERSION_1_12_2);
+        let player: &NbtCompound = data.get("Player").unwrap();
+        assert_eq!(player.get::<_, i32>("DataVersion").unwrap(), DATA_VERSION_1_12_2);
+        let inventory: &NbtList = player.get("Inventory").unwrap();
+        let diamond: &NbtCompound = inventory.get(0).unwrap();
+        assert_eq!(diamond.get::<_, &str>("id").unwrap(), "minecraft:diamond");
+
+        // data newer than the target is not touched
+        let mut player = snbt::parse(r#"{DataVersion: 1451, Inventory: [{id: "minecraft:stone", Count: 1b}]}"#).unwrap();
+        let expected = player.clone();
+        upgrader.upgrade_player(&mut player).unwrap();
+        assert_eq!(player, expected);
+    }
+
+    #[test]
+    fn modded_item_ids_from_level_dat() {
+        let world = test_utils::test_output_path("upgrade_modded_item_ids");
+        let level = snbt::parse(r#"{Data: {}, FML: {Registries: {"minecraft:items": {ids: [{K: "ic2:wrench", V: 4100}]}}}}"#).unwrap();
+        let mut level_dat = Vec::new();
+        quartz_nbt::io::write_nbt(&mut level_dat, None, &level, Flavor::GzCompressed).unwrap();
+        std::fs::write(world.join("level.dat"), level_dat).unwrap();
+
+        let mut player = snbt::parse(r#"{Inventory: [{id: 4100s, Count: 1b, Damage: 0s}]}"#).unwrap();
+        Upgrader::for_world(&world, DATA_VERSION_1_12_2)
+            .upgrade_player(&mut player)
+            .unwrap();
+        let inventory: &NbtList = player.get("Inventory").unwrap();
+        let wrench: &NbtCompound = inventory.get(0).unwrap();
+        assert_eq!(wrench.get::<_, &str>("id").unwrap(), "ic2:wrench");
+    }
+}
@@ -0,0 +1,479 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    io::Cursor,
+    path::Path,
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use quartz_nbt::{io::NbtIoError, NbtCompound, NbtList, NbtReprError, NbtTag};
+use uuid::Uuid;
+
+use crate::{
+    convert::{
+        converter::{WriteError, Writer},
+        entry_location::{EntryLocation2d, EntryLocation3d},
+        relocate::{offset_entities, offset_tile_entities, offset_tile_ticks},
+    },
+    io::{
+        cubic::{reader::CubicRegionReader, writer::CubicRegionWriter},
+        region_reader::RegionReadError,
+        write_region::RegionWriteError,
+    },
+    util::{
+        aabb::CubeAabb,
+        compress::{read_compressed_cc, write_compressed},
+        errors::error_from,
+        positions::CubePos,
+        reinterpret::{vec_i8_into_u8, vec_u8_into_i8},
+    },
+};
+
+const MAX_CACHED_REGIONS: usize = 64;
+/// The height cubic chunks stores for block columns without any blocks
+const NO_HEIGHT: i32 = i32::MIN + 32;
+
+pub struct CopyCubesConfig {
+    /// Cubes to copy, in source world coordinates. x and z are chunk coordinates
+    pub source: CubeAabb,
+    /// Horizontal shift of the copied cubes, in chunks
+    pub x_offset: i32,
+    /// Vertical shift of the copied cubes, in cubes
+    pub y_offset: i32,
+    /// Horizontal shift of the copied cubes, in chunks
+    pub z_offset: i32,
+    /// Remove the cubes from the source box after copying them, turning the copy into a move
+    pub clear_source: bool,
+}
+
+#[derive(Debug)]
+pub enum CopyCubesError {
+    RegionRead(RegionReadError),
+    RegionWrite(RegionWriteError),
+    Write(WriteError),
+    NbtRepr(NbtReprError),
+    NbtIo(NbtIoError),
+    StdIo(std::io::Error),
+}
+
+error_from!(CopyCubesError, RegionReadError, Self::RegionRead);
+error_from!(CopyCubesError, RegionWriteError, Self::RegionWrite);
+error_from!(CopyCubesError, WriteError, Self::Write);
+error_from!(CopyCubesError, NbtReprError, Self::NbtRepr);
+error_from!(CopyCubesError, NbtIoError, Self::NbtIo);
+error_from!(CopyCubesError, std::io::Error, Self::StdIo);
+
+impl Display for CopyCubesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RegionRead(err) => f.write_str(&format!("{}", err)),
+            Self::RegionWrite(err) => f.write_str(&format!("{}", err)),
+            Self::Write(err) => f.write_str(&format!("{:?}", err)),
+            Self::NbtRepr(err) => f.write_str(&format!("{}", err)),
+            Self::NbtIo(err) => f.write_str(&format!("{}", err)),
+            Self::StdIo(err) => f.write_str(&format!("{}", err)),
+        }
+    }
+}
+
+impl Error for CopyCubesError {}
+
+/// Copies the cubes of `config.source` from the world at `src_path` into the world at `dst_path`, or within the source world when
+/// `dst_path` is `None`. Returns the number of copied cubes.
+///
+/// Every selected cube is read before anything is written, so the source and destination boxes may overlap.
+/// Destination columns which already exist are kept, missing ones are copied from the source.
+/// Entities copied within the same world get new UUIDs, moved entities and entities copied into another world keep theirs.
+pub fn copy_cubes(src_path: &Path, dst_path: Option<&Path>, config: CopyCubesConfig) -> Result<usize, CopyCubesError> {
+    let offset = CubePos::new(config.x_offset, config.y_offset, config.z_offset);
+    let new_uuids = dst_path.is_none() && !config.clear_source;
+
+    let mut src_reader = CubicRegionReader::new(src_path, MAX_CACHED_REGIONS);
+    let mut cubes = Vec::new();
+    for region_pos in config.source.regions_3d() {
+        for pos in src_reader.cubes_in_region(region_pos)? {
+            if !config.source.contains(CubePos::new(pos.x, pos.y, pos.z)) {
+                continue;
+            }
+            if let Some(data) = src_reader.read_cube(pos)? {
+                let mut tag = read_compressed_cc(data)?;
+                offset_cube(&mut tag, offset)?;
+                if new_uuids {
+                    renew_entity_uuids(&mut tag)?;
+                }
+                cubes.push((pos, tag));
+            }
+        }
+    }
+
+    let mut dst_reader = dst_path.map(|path| CubicRegionReader::new(path, MAX_CACHED_REGIONS));
+    let mut columns = Vec::new();
+    for chunk_pos in config.source.chunks() {
+        let dst_pos = EntryLocation2d::new(chunk_pos.x + offset.x, chunk_pos.z + offset.z);
+        let dst_has_column = match &mut dst_reader {
+            Some(dst_reader) => dst_reader.read_column(dst_pos)?.is_some(),
+            None => src_reader.read_column(dst_pos)?.is_some(),
+        };
+        if dst_has_column {
+            continue;
+        }
+        if let Some(data) = src_reader.read_column(EntryLocation2d::new(chunk_pos.x, chunk_pos.z))? {
+            let mut tag = read_compressed_cc(data)?;
+            offset_column(&mut tag, offset)?;
+            columns.push((dst_pos, tag));
+        }
+    }
+
+    let mut dst_writer = CubicRegionWriter::new(dst_path.unwrap_or(src_path), MAX_CACHED_REGIONS)?;
+    if config.clear_source {
+        // in the same world, cleared cubes overlapping the destination are written again below
+        let mut src_writer = match dst_path {
+            Some(_) => Some(CubicRegionWriter::new(src_path, MAX_CACHED_REGIONS)?),
+            None => None,
+        };
+        let writer = src_writer.as_mut().unwrap_or(&mut dst_writer);
+        for (pos, _) in &cubes {
+            writer.remove_cube(*pos)?;
+        }
+        if let Some(mut src_writer) = src_writer {
+            src_writer.flush()?;
+        }
+    }
+
+    for (pos, tag) in &columns {
+        dst_writer.write_column(*pos, &write_compressed(tag, false)?)?;
+    }
+    for (pos, tag) in &cubes {
+        let dst_pos = EntryLocation3d::new(pos.x + offset.x, pos.y + offset.y, pos.z + offset.z);
+        dst_writer.write_cube(dst_pos, &write_compressed(tag, false)?)?;
+    }
+    dst_writer.flush()?;
+    Ok(cubes.len())
+}
+
+/// Moves a cube and everything in it by `offset` cubes
+pub fn offset_cube(tag: &mut NbtCompound, offset: CubePos) -> Result<(), NbtReprError> {
+    let block_offset = offset.to_block_pos();
+    let level = tag.get_mut::<_, &mut NbtCompound>("Level")?;
+    for (key, offset) in [("x", offset.x), ("y", offset.y), ("z", offset.z)] {
+        *level.get_mut::<_, &mut i32>(key)? += offset;
+    }
+
+    if let Ok(entities) = level.get_mut::<_, &mut NbtList>("Entities") {
+        offset_entities(entities, block_offset)?;
+    }
+    if let Ok(tile_entities) = level.get_mut::<_, &mut NbtList>("TileEntities") {
+        offset_tile_entities(tile_entities, block_offset)?;
+    }
+    if let Ok(tile_ticks) = level.get_mut::<_, &mut NbtList>("TileTicks") {
+        offset_tile_ticks(tile_ticks, block_offset)?;
+    }
+
+    if let Ok(lighting_info) = level.get_mut::<_, &mut NbtCompound>("LightingInfo") {
+        if let Ok(NbtTag::IntArray(heightmap)) = lighting_info.get_mut::<_, &mut NbtTag>("LastHeightMap") {
+            heightmap.iter_mut().for_each(|height| *height += block_offset.y);
+        }
+    }
+    // surface tracking data is relative to the column the cube was in, let cubic chunks redo it
+    level.insert("isSurfaceTracked", NbtTag::Byte(0));
+    Ok(())
+}
+
+/// Gives the entities of a cube, and their passengers, new UUIDs so copies don't share them with the originals
+fn renew_entity_uuids(tag: &mut NbtCompound) -> Result<(), NbtReprError> {
+    fn renew(entities: &mut NbtList) -> Result<(), NbtReprError> {
+        for entity in entities.iter_mut() {
+            let entity: &mut NbtCompound = entity.try_into()?;
+            if entity.contains_key("UUIDMost") || entity.contains_key("UUIDLeast") {
+                let (most, least) = Uuid::new_v4().as_u64_pair();
+                entity.insert("UUIDMost", most as i64);
+                entity.insert("UUIDLeast", least as i64);
+            }
+            if let Ok(passengers) = entity.get_mut::<_, &mut NbtList>("Passengers") {
+                renew(passengers)?;
+            }
+        }
+        Ok(())
+    }
+
+    let level = tag.get_mut::<_, &mut NbtCompound>("Level")?;
+    match level.get_mut::<_, &mut NbtList>("Entities") {
+        Ok(entities) => renew(entities),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Moves a column by `offset`. Vertically only its opacity index moves.
+fn offset_column(tag: &mut NbtCompound, offset: CubePos) -> Result<(), CopyCubesError> {
+    let level = tag.get_mut::<_, &mut NbtCompound>("Level")?;
+    *level.get_mut::<_, &mut i32>("x")? += offset.x;
+    *level.get_mut::<_, &mut i32>("z")? += offset.z;
+
+    if offset.y != 0 {
+        if let Ok(NbtTag::ByteArray(opacity_index)) = level.get_mut::<_, &mut NbtTag>("OpacityIndex") {
+            *opacity_index = offset_opacity_index(opacity_index, offset.to_block_pos().y)?;
+        }
+    }
+    Ok(())
+}
+
+/// Shifts the lowest and highest block of every block column in an opacity index by `y_offset`. The segments between
+/// them are dropped, like in the index anvil2cc writes, and cubic chunks finds them again as the cubes load.
+fn offset_opacity_index(opacity_index: &[i8], y_offset: i32) -> Result<Vec<i8>, std::io::Error> {
+    let mut reader = Cursor::new(vec_i8_into_u8(opacity_index.to_vec()));
+    let mut out = Vec::new();
+    let shift = |height: i32| if height <= NO_HEIGHT { height } else { height + y_offset };
+    for _ in 0..256 {
+        let min_y = reader.read_i32::<BigEndian>()?;
+        let max_y = reader.read_i32::<BigEndian>()?;
+        let segments = reader.read_i16::<BigEndian>()?;
+        for _ in 0..segments {
+            reader.read_i32::<BigEndian>()?;
+        }
+        out.write_i32::<BigEndian>(shift(min_y))?;
+        out.write_i32::<BigEndian>(shift(max_y))?;
+        out.write_i16::<BigEndian>(0)?;
+    }
+    Ok(vec_u8_into_i8(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, path::Path};
+
+    use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+    use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+    use super::{copy_cubes, CopyCubesConfig, NO_HEIGHT};
+    use crate::{
+        convert::{
+            converter::Writer,
+            entry_location::{EntryLocation2d, EntryLocation3d},
+        },
+        io::cubic::{reader::CubicRegionReader, writer::CubicRegionWriter},
+        util::{
+            aabb::CubeAabb,
+            compress::{read_compressed_cc, write_compressed},
+            reinterpret::{vec_i8_into_u8, vec_u8_into_i8},
+            test_utils,
+        },
+    };
+
+    fn cube_tag(x: i32, y: i32, z: i32) -> NbtCompound {
+        let mut entity = NbtCompound::new();
+        entity.insert(
+            "Pos",
+            NbtList::from(vec![x as f64 * 16.0 + 1.5, y as f64 * 16.0 + 2.0, z as f64 * 16.0 + 3.5]),
+        );
+        entity.insert("UUIDMost", 1i64);
+        entity.insert("UUIDLeast", 2i64);
+        let mut passenger = NbtCompound::new();
+        passenger.insert("UUIDMost", 3i64);
+        passenger.insert("UUIDLeast", 4i64);
+        entity.insert("Passengers", NbtList::from(vec![passenger]));
+        let mut tile_entity = NbtCompound::new();
+        tile_entity.insert("x", x * 16 + 1);
+        tile_entity.insert("y", y * 16 + 2);
+        tile_entity.insert("z", z * 16 + 3);
+
+        let mut level = NbtCompound::new();
+        level.insert("x", x);
+        level.insert("y", y);
+        level.insert("z", z);
+        level.insert("Entities", NbtList::from(vec![entity]));
+        level.insert("TileEntities", NbtList::from(vec![tile_entity]));
+        let mut root = NbtCompound::new();
+        root.insert("Level", level);
+        root
+    }
+
+    /// The lowest and highest block of a block column, and its segments
+    type OpacityEntry = (i32, i32, Vec<i32>);
+
+    /// An opacity index with one block column of blocks between y 1 and 20, one without blocks, and the rest at y 0
+    fn column_tag(x: i32, z: i32) -> NbtCompound {
+        let mut opacity_index = Vec::new();
+        for i in 0..256 {
+            let (min_y, max_y, segments) = match i {
+                0 => (1, 20, vec![1, 10]),
+                1 => (NO_HEIGHT, NO_HEIGHT, vec![]),
+                _ => (0, 0, vec![]),
+            };
+            opacity_index.write_i32::<BigEndian>(min_y).unwrap();
+            opacity_index.write_i32::<BigEndian>(max_y).unwrap();
+            opacity_index.write_i16::<BigEndian>(segments.len() as i16).unwrap();
+            for segment in segments {
+                opacity_index.write_i32::<BigEndian>(segment).unwrap();
+            }
+        }
+
+        let mut level = NbtCompound::new();
+        level.insert("x", x);
+        level.insert("z", z);
+        level.insert("OpacityIndex", vec_u8_into_i8(opacity_index));
+        let mut root = NbtCompound::new();
+        root.insert("Level", level);
+        root
+    }
+
+    fn make_world(path: &Path, cubes: &[(i32, i32, i32)]) {
+        let mut writer = CubicRegionWriter::new(path, 16).unwrap();
+        for &(x, y, z) in cubes {
+            let column = write_compressed(&column_tag(x, z), false).unwrap();
+            writer.write_column(EntryLocation2d::new(x, z), &column).unwrap();
+            let cube = write_compressed(&cube_tag(x, y, z), false).unwrap();
+            writer.write_cube(EntryLocation3d::new(x, y, z), &cube).unwrap();
+        }
+        writer.flush().unwrap();
+    }
+
+    fn read_cube(path: &Path, x: i32, y: i32, z: i32) -> Option<NbtCompound> {
+        let mut reader = CubicRegionReader::new(path, 16);
+        let data = reader.read_cube(EntryLocation3d::new(x, y, z)).unwrap();
+        data.map(|data| read_compressed_cc(data).unwrap())
+    }
+
+    fn level(tag: &NbtCompound) -> &NbtCompound {
+        tag.get::<_, &NbtCompound>("Level").unwrap()
+    }
+
+    fn opacity_index(column: &NbtCompound) -> Vec<OpacityEntry> {
+        let opacity_index = level(column).get::<_, &[i8]>("OpacityIndex").unwrap();
+        let mut reader = Cursor::new(vec_i8_into_u8(opacity_index.to_vec()));
+        (0..256)
+            .map(|_| {
+                let min_y = reader.read_i32::<BigEndian>().unwrap();
+                let max_y = reader.read_i32::<BigEndian>().unwrap();
+                let segments = reader.read_i16::<BigEndian>().unwrap();
+                let segments = (0..segments).map(|_| reader.read_i32::<BigEndian>().unwrap()).collect();
+                (min_y, max_y, segments)
+            })
+            .collect()
+    }
+
+    /// UUIDMost and UUIDLeast of the first entity in a cube and of its passenger
+    fn entity_uuids(cube: &NbtCompound) -> [(i64, i64); 2] {
+        let entity: &NbtCompound = level(cube).get::<_, &NbtList>("Entities").unwrap().get(0).unwrap();
+        let passenger: &NbtCompound = entity.get::<_, &NbtList>("Passengers").unwrap().get(0).unwrap();
+        [entity, passenger].map(|entity| {
+            (
+                entity.get::<_, i64>("UUIDMost").unwrap(),
+                entity.get::<_, i64>("UUIDLeast").unwrap(),
+            )
+        })
+    }
+
+    #[test]
+    fn move_within_world() {
+        let path = test_utils::test_output_path("move_within_world");
+        make_world(&path, &[(0, 0, 0), (0, 1, 0), (1, 0, 0)]);
+
+        let copied = copy_cubes(
+            &path,
+            None,
+            CopyCubesConfig {
+                source: CubeAabb::from_coords(0, 0, 0, 0, 1, 0),
+                x_offset: 2,
+                y_offset: 1,
+                z_offset: 0,
+                clear_source: true,
+            },
+        )
+        .unwrap();
+        assert_eq!(copied, 2);
+
+        // (0, 1, 0) is both a source and a destination
+        assert!(read_cube(&path, 0, 0, 0).is_none());
+        assert!(read_cube(&path, 0, 1, 0).is_none());
+        assert!(read_cube(&path, 1, 0, 0).is_some(), "cubes outside of the box are kept");
+        assert!(read_cube(&path, 2, 1, 0).is_some());
+
+        let moved = read_cube(&path, 2, 2, 0).unwrap();
+        let moved = level(&moved);
+        assert_eq!(moved.get::<_, i32>("x").unwrap(), 2);
+        assert_eq!(moved.get::<_, i32>("y").unwrap(), 2);
+        let entity: &NbtCompound = moved.get::<_, &NbtList>("Entities").unwrap().get(0).unwrap();
+        let pos = entity.get::<_, &NbtList>("Pos").unwrap();
+        assert_eq!(pos.get::<f64>(0).unwrap(), 32.0 + 1.5);
+        assert_eq!(pos.get::<f64>(1).unwrap(), 32.0 + 2.0);
+        let tile_entity: &NbtCompound = moved.get::<_, &NbtList>("TileEntities").unwrap().get(0).unwrap();
+        assert_eq!(tile_entity.get::<_, i32>("x").unwrap(), 32 + 1);
+        assert_eq!(tile_entity.get::<_, i32>("y").unwrap(), 32 + 2);
+        assert_eq!(moved.get::<_, &NbtTag>("isSurfaceTracked").unwrap(), &NbtTag::Byte(0));
+        assert_eq!(entity_uuids(&read_cube(&path, 2, 2, 0).unwrap()), [(1, 2), (3, 4)]);
+
+        let mut reader = CubicRegionReader::new(&path, 16);
+        let column = read_compressed_cc(reader.read_column(EntryLocation2d::new(2, 0)).unwrap().unwrap()).unwrap();
+        assert_eq!(level(&column).get::<_, i32>("x").unwrap(), 2);
+        let opacity_index = opacity_index(&column);
+        assert_eq!(opacity_index[0], (17, 36, vec![]));
+        assert_eq!(opacity_index[1], (NO_HEIGHT, NO_HEIGHT, vec![]));
+        assert_eq!(opacity_index[2], (16, 16, vec![]));
+    }
+
+    #[test]
+    fn copy_within_world() {
+        let path = test_utils::test_output_path("copy_within_world");
+        make_world(&path, &[(0, 0, 0)]);
+
+        let copied = copy_cubes(
+            &path,
+            None,
+            CopyCubesConfig {
+                source: CubeAabb::from_coords(0, 0, 0, 0, 0, 0),
+                x_offset: 1,
+                y_offset: 0,
+                z_offset: 0,
+                clear_source: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(copied, 1);
+
+        assert_eq!(entity_uuids(&read_cube(&path, 0, 0, 0).unwrap()), [(1, 2), (3, 4)]);
+        let uuids = entity_uuids(&read_cube(&path, 1, 0, 0).unwrap());
+        assert!(
+            !uuids.contains(&(1, 2)) && !uuids.contains(&(3, 4)),
+            "copies get new UUIDs: {uuids:?}"
+        );
+        assert_ne!(uuids[0], uuids[1]);
+
+        let mut reader = CubicRegionReader::new(&path, 16);
+        let column = read_compressed_cc(reader.read_column(EntryLocation2d::new(1, 0)).unwrap().unwrap()).unwrap();
+        assert_eq!(
+            opacity_index(&column)[0],
+            (1, 20, vec![1, 10]),
+            "the index only changes with a y offset"
+        );
+    }
+
+    #[test]
+    fn copy_to_other_world() {
+        let src = test_utils::test_output_path("copy_to_other_world_src");
+        let dst = test_utils::test_output_path("copy_to_other_world_dst");
+        make_world(&src, &[(0, 0, 0), (0, 1, 0)]);
+        make_world(&dst, &[(-20, 5, 0)]);
+
+        let copied = copy_cubes(
+            &src,
+            Some(&dst),
+            CopyCubesConfig {
+                source: CubeAabb::from_coords(0, 1, 0, 0, 1, 0),
+                x_offset: -20,
+                y_offset: 0,
+                z_offset: 0,
+                clear_source: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(copied, 1);
+
+        assert!(read_cube(&src, 0, 1, 0).is_some());
+        assert!(read_cube(&dst, -20, 5, 0).is_some());
+        assert!(read_cube(&dst, -20, 0, 0).is_none());
+        let copied = read_cube(&dst, -20, 1, 0).unwrap();
+        let tile_entity: &NbtCompound = level(&copied).get::<_, &NbtList>("TileEntities").unwrap().get(0).unwrap();
+        assert_eq!(tile_entity.get::<_, i32>("x").unwrap(), -320 + 1);
+        assert_eq!(entity_uuids(&copied), [(1, 2), (3, 4)], "other worlds don't have the originals");
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    path::Path,
+};
+
+use crate::{
+    convert::entry_location::{EntryLocation2d, EntryLocation3d, Key, RegionPos},
+    io::{
+        cubic::reader::read_region_entries,
+        region_reader::RegionReadError,
+        write_region::{RegionWriteError, WriteRegion},
+    },
+    util::{
+        aabb::CubeAabb,
+        errors::error_from,
+        positions::{RegionPos2d, RegionPos3d},
+    },
+};
+
+pub struct PruneCubesConfig {
+    /// Lowest cube y to keep
+    pub min_y: i32,
+    /// Highest cube y to keep
+    pub max_y: i32,
+    /// When set, cubes outside of all boxes are removed as well. x and z are chunk coordinates
+    pub keep: Option<Vec<CubeAabb>>,
+}
+
+impl PruneCubesConfig {
+    fn keeps(&self, aabb: &CubeAabb) -> bool {
+        let in_y_range = aabb.min.y >= self.min_y && aabb.max.y <= self.max_y;
+        in_y_range
+            && self
+                .keep
+                .as_ref()
+                .is_none_or(|keep| keep.iter().any(|keep| keep.contains_aabb(aabb)))
+    }
+
+    /// Whether any cube of the column at chunk `x`, `z` can be kept
+    fn keeps_column(&self, x: i32, z: i32) -> bool {
+        let column = CubeAabb::from_coords(x, self.min_y, z, x, self.max_y, z);
+        self.keep
+            .as_ref()
+            .is_none_or(|keep| keep.iter().any(|keep| keep.intersects(&column)))
+    }
+}
+
+#[derive(Debug)]
+pub enum PruneCubesError {
+    RegionRead(RegionReadError),
+    RegionWrite(RegionWriteError),
+    StdIo(std::io::Error),
+}
+
+error_from!(PruneCubesError, RegionReadError, Self::RegionRead);
+error_from!(PruneCubesError, RegionWriteError, Self::RegionWrite);
+error_from!(PruneCubesError, std::io::Error, Self::StdIo);
+
+impl Display for PruneCubesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RegionRead(err) => f.write_str(&format!("{}", err)),
+            Self::RegionWrite(err) => f.write_str(&format!("{}", err)),
+            Self::StdIo(err) => f.write_str(&format!("{}", err)),
+        }
+    }
+}
+
+impl Error for PruneCubesError {}
+
+/// Removes every cube of the cubic chunks world at `path` which `config` doesn't keep, deleting region files which end up empty.
+/// Columns outside of all `keep` boxes are removed too, the y range alone leaves columns untouched. Returns the number of
+/// removed cubes.
+pub fn prune_cubes(path: &Path, config: PruneCubesConfig) -> Result<usize, PruneCubesError> {
+    let region_dir = path.join("region3d");
+    if !region_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for dir_entry in std::fs::read_dir(&region_dir)? {
+        let region_path = dir_entry?.path();
+        let region_pos = match region_path
+            .file_name()
+            .and_then(std::ffi::OsStr::to_str)
+            .and_then(RegionPos3d::from_file_name)
+        {
+            Some(region_pos) => region_pos,
+            None => continue,
+        };
+        let last = RegionPos3d::DIAMETER_IN_CUBES as i32 - 1;
+        let region_cubes = CubeAabb::new(region_pos.to_cube_pos(), region_pos.to_cube_pos_offset(last, last, last));
+        if config.keeps(&region_cubes) {
+            continue;
+        }
+
+        let entries = read_region_entries(&region_path, EntryLocation3d::SECTOR_SIZE, EntryLocation3d::ENTRIES_PER_REGION)?;
+        let mut remaining = 0;
+        let mut to_remove = Vec::new();
+        for cube_pos in region_cubes.iter() {
+            let pos = EntryLocation3d::new(cube_pos.x, cube_pos.y, cube_pos.z);
+            if entries[pos.id()].is_none() {
+                continue;
+            }
+            if config.keeps(&CubeAabb::new(cube_pos, cube_pos)) {
+                remaining += 1;
+            } else {
+                to_remove.push(pos);
+            }
+        }
+        removed += to_remove.len();
+        remove_entries(
+            &region_path,
+            EntryLocation3d::SECTOR_SIZE,
+            EntryLocation3d::ENTRIES_PER_REGION,
+            &to_remove,
+            remaining,
+        )?;
+    }
+
+    if config.keep.is_some() {
+        prune_columns(path, &config)?;
+    }
+    Ok(removed)
+}
+
+/// Removes every column of the world at `path` which `config` keeps no cube of
+fn prune_columns(path: &Path, config: &PruneCubesConfig) -> Result<(), PruneCubesError> {
+    let region_dir = path.join("region2d");
+    if !region_dir.exists() {
+        return Ok(());
+    }
+
+    for dir_entry in std::fs::read_dir(&region_dir)? {
+        let region_path = dir_entry?.path();
+        let region_pos = match region_path
+            .file_name()
+            .and_then(std::ffi::OsStr::to_str)
+            .and_then(RegionPos2d::from_file_name)
+        {
+            Some(region_pos) => region_pos,
+            None => continue,
+        };
+
+        let entries = read_region_entries(&region_path, EntryLocation2d::SECTOR_SIZE, EntryLocation2d::ENTRIES_PER_REGION)?;
+        let mut remaining = 0;
+        let mut to_remove = Vec::new();
+        let diameter = RegionPos2d::DIAMETER_IN_CHUNKS as i32;
+        for (local_x, local_z) in (0..diameter).flat_map(|x| (0..diameter).map(move |z| (x, z))) {
+            let chunk_pos = region_pos.to_chunk_pos_offset(local_x, local_z);
+            let pos = EntryLocation2d::new(chunk_pos.x, chunk_pos.z);
+            if entries[pos.id()].is_none() {
+                continue;
+            }
+            if config.keeps_column(chunk_pos.x, chunk_pos.z) {
+                remaining += 1;
+            } else {
+                to_remove.push(pos);
+            }
+        }
+        remove_entries(
+            &region_path,
+            EntryLocation2d::SECTOR_SIZE,
+            EntryLocation2d::ENTRIES_PER_REGION,
+            &to_remove,
+            remaining,
+        )?;
+    }
+    Ok(())
+}
+
+/// Removes `to_remove` from the region file at `path`, deleting the file instead when no entries remain
+fn remove_entries<K, R>(
+    path: &Path,
+    sector_size: usize,
+    entries_per_region: usize,
+    to_remove: &[K],
+    remaining: usize,
+) -> Result<(), PruneCubesError>
+where
+    K: Key<R>,
+{
+    if remaining == 0 {
+        std::fs::remove_file(path)?;
+    } else if !to_remove.is_empty() {
+        let mut region = WriteRegion::new(path, sector_size, entries_per_region);
+        for pos in to_remove {
+            region.remove(pos)?;
+        }
+        region.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prune_cubes, PruneCubesConfig};
+    use crate::{
+        convert::{
+            converter::Writer,
+            entry_location::{EntryLocation2d, EntryLocation3d},
+        },
+        io::cubic::{reader::CubicRegionReader, writer::CubicRegionWriter},
+        util::{aabb::CubeAabb, test_utils},
+    };
+
+    #[test]
+    fn prune_y_range_and_boxes() {
+        let path = test_utils::test_output_path("prune_y_range_and_boxes");
+        let cubes = [(0, -20, 0), (0, 0, 0), (0, 5, 0), (0, 15, 0), (0, 16, 0), (1, 5, 0), (40, 5, 40)];
+        let mut writer = CubicRegionWriter::new(&path, 16).unwrap();
+        for (x, y, z) in cubes {
+            writer
+                .write_cube(EntryLocation3d::new(x, y, z), &[x as u8, y as u8, z as u8])
+                .unwrap();
+        }
+        writer.flush().unwrap();
+
+        let removed = prune_cubes(
+            &path,
+            PruneCubesConfig {
+                min_y: 0,
+                max_y: 15,
+                keep: Some(vec![CubeAabb::from_coords(0, i32::MIN, 0, 0, i32::MAX, 0)]),
+            },
+        )
+        .unwrap();
+        assert_eq!(removed, 4);
+
+        let mut reader = CubicRegionReader::new(&path, 16);
+        for (x, y, z) in cubes {
+            let kept = x == 0 && (0..=15).contains(&y);
+            let data = reader.read_cube(EntryLocation3d::new(x, y, z)).unwrap();
+            assert_eq!(data, kept.then_some(&[x as u8, y as u8, z as u8][..]), "cube {x}, {y}, {z}");
+        }
+
+        let region3d = path.join("region3d");
+        assert!(region3d.join("0.0.0.3dr").exists());
+        assert!(!region3d.join("0.-2.0.3dr").exists());
+        assert!(!region3d.join("0.1.0.3dr").exists());
+        assert!(!region3d.join("2.0.2.3dr").exists());
+    }
+
+    #[test]
+    fn prune_columns_outside_of_boxes() {
+        let path = test_utils::test_output_path("prune_columns_outside_of_boxes");
+        let columns = [(0, 0), (1, 0), (40, 40)];
+        let mut writer = CubicRegionWriter::new(&path, 16).unwrap();
+        for (x, z) in columns {
+            writer.write_column(EntryLocation2d::new(x, z), &[x as u8, z as u8]).unwrap();
+            writer.write_cube(EntryLocation3d::new(x, 0, z), &[x as u8, z as u8]).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let config = |keep| PruneCubesConfig {
+            min_y: i32::MIN,
+            max_y: i32::MAX,
+            keep,
+        };
+        assert_eq!(prune_cubes(&path, config(None)).unwrap(), 0);
+        assert_eq!(
+            prune_cubes(&path, config(Some(vec![CubeAabb::from_coords(0, 5, 0, 0, 5, 0)]))).unwrap(),
+            3
+        );
+
+        let mut reader = CubicRegionReader::new(&path, 16);
+        for (x, z) in columns {
+            let data = reader.read_column(EntryLocation2d::new(x, z)).unwrap();
+            let kept = (x, z) == (0, 0);
+            assert_eq!(data, kept.then_some(&[x as u8, z as u8][..]), "column {x}, {z}");
+        }
+
+        let region2d = path.join("region2d");
+        assert!(region2d.join("0.0.2dr").exists());
+        assert!(!region2d.join("1.1.2dr").exists());
+    }
+}
@@ -12,12 +12,16 @@ use self::{
     info_converter::InfoConverter,
     waiter::ConverterWaiter,
 };
+use crate::log_targets;
 
 pub mod anvil2cc;
+pub mod cc2cc;
 pub mod converter;
 pub mod data;
 pub mod entry_location;
 pub mod info_converter;
+pub mod relocate;
+pub mod selection;
 pub mod waiter;
 
 #[derive(Copy, Clone, Debug)]
@@ -51,6 +55,7 @@ where
     WRITE: Writer<OUT> + 'static,
 {
     let tasks_sent = Arc::new(AtomicUsize::new(0));
+    let tasks_skipped = Arc::new(AtomicUsize::new(0));
     let convert_queue_fill = Arc::new(AtomicUsize::new(0));
     let write_queue_fill = Arc::new(AtomicUsize::new(0));
 
@@ -60,7 +65,7 @@ where
     let tasks_fill = tasks_sent.clone();
     let convert_fill = convert_queue_fill.clone();
     let read_thread = std::thread::spawn(move || {
-        println!("Read thread start");
+        log::debug!(target: log_targets::READER, "Read thread start");
         reader
             .load_all_chunks(|mut data| {
                 tasks_fill.fetch_add(1, Ordering::Relaxed);
@@ -83,7 +88,7 @@ where
                 }
             })
             .unwrap();
-        println!("Read thread end");
+        log::debug!(target: log_targets::READER, "Read thread end");
     });
 
     let converter = Arc::new(converter);
@@ -95,11 +100,21 @@ where
         let write_sender = write_sender.clone();
         let convert_fill = convert_queue_fill.clone();
         let write_fill = write_queue_fill.clone();
+        let skipped = tasks_skipped.clone();
         convert_threads.push(std::thread::spawn(move || {
-            println!("Convert thread start");
+            log::debug!(target: log_targets::CONVERTER, "Convert thread start");
             while let Ok(data) = convert_receiver.recv() {
                 convert_fill.fetch_sub(1, Ordering::Relaxed);
-                let converted = converter.convert(data).unwrap();
+                let converted = match converter.convert(data) {
+                    Ok(converted) => converted,
+                    Err(err) => {
+                        // one broken chunk, e.g. one the data fixer can't upgrade, shouldn't stop the others.
+                        // join_all reports how many were skipped
+                        log::warn!(target: log_targets::CONVERTER, "Skipping data which couldn't be converted: {err:?}");
+                        skipped.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                };
 
                 for mut data in converted {
                     while let Err(err) = write_sender.try_send(data) {
@@ -117,7 +132,7 @@ where
                 }
             }
 
-            println!("Convert thread end");
+            log::debug!(target: log_targets::CONVERTER, "Convert thread end");
         }));
     }
     convert_receiver.unsubscribe();
@@ -125,14 +140,14 @@ where
 
     let write_fill = write_queue_fill.clone();
     let write_thread = std::thread::spawn(move || {
-        println!("Write thread start");
+        log::debug!(target: log_targets::WRITER, "Write thread start");
 
         while let Ok(data) = write_receiver.recv() {
             write_fill.fetch_sub(1, Ordering::Relaxed);
             writer.write(data).unwrap();
         }
         writer.flush().unwrap();
-        println!("Write thread end");
+        log::debug!(target: log_targets::WRITER, "Write thread end");
     });
 
     let info_converter_thread = std::thread::spawn(move || info_converter.convert().unwrap());
@@ -143,9 +158,91 @@ where
         info_converter_thread,
         write_thread,
         tasks_sent,
+        tasks_skipped,
         convert_queue_fill,
         convert_queue_size: ctx.convert_queue_size,
         write_queue_fill,
         write_queue_size: ctx.write_queue_size,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{
+        converter::{ConversionError, Converter, ReadError, Reader, WriteError, Writer},
+        info_converter::InfoConverter,
+        run_conversion,
+        waiter::ErrorCause,
+        ConverterCreateCtx,
+    };
+
+    struct NumberReader;
+
+    impl Reader<i32, i32> for NumberReader {
+        fn load_all_chunks<F>(&mut self, f: F) -> Result<(), ReadError>
+        where
+            F: Fn(i32),
+        {
+            (0..10).for_each(f);
+            Ok(())
+        }
+    }
+
+    /// Fails on odd numbers
+    struct EvenConverter;
+
+    impl Converter<i32, i32> for EvenConverter {
+        fn convert(&self, in_data: i32) -> Result<Vec<i32>, ConversionError> {
+            if in_data % 2 == 1 {
+                return Err(std::io::Error::other("odd").into());
+            }
+            Ok(vec![in_data])
+        }
+    }
+
+    struct NoInfo;
+
+    impl InfoConverter for NoInfo {
+        fn convert(&self) -> Result<(), ConversionError> {
+            Ok(())
+        }
+    }
+
+    struct CollectWriter(Arc<Mutex<Vec<i32>>>);
+
+    impl Writer<i32> for CollectWriter {
+        fn write(&mut self, out_data: i32) -> Result<(), WriteError> {
+            self.0.lock().unwrap().push(out_data);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), WriteError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn data_which_fails_to_convert_is_skipped() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let waiter = run_conversion(
+            ConverterCreateCtx::default(),
+            NumberReader,
+            EvenConverter,
+            NoInfo,
+            CollectWriter(written.clone()),
+        );
+        let errors = waiter.join_all().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].cause, ErrorCause::Skipped));
+        assert_eq!(
+            errors[0].to_string(),
+            "Conversion failed: 5 inputs couldn't be converted and were skipped"
+        );
+
+        let mut written = written.lock().unwrap().clone();
+        written.sort();
+        assert_eq!(written, vec![0, 2, 4, 6, 8]);
+    }
+}
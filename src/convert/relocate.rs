@@ -0,0 +1,88 @@
+use quartz_nbt::{NbtCompound, NbtList, NbtReprError};
+
+use crate::util::positions::BlockPos;
+
+/// Integer block coordinates stored directly in entities, as `(x, y, z)` key triples
+const ENTITY_BLOCK_COORDS: [[&str; 3]; 3] = [
+    ["TileX", "TileY", "TileZ"], // hanging entities
+    ["xTile", "yTile", "zTile"], // arrows and other projectiles
+    ["APX", "APY", "APZ"],       // shulker attachment
+];
+
+/// Compounds within entities holding a block position as `X`, `Y` and `Z`
+const ENTITY_BLOCK_POS_COMPOUNDS: [&str; 2] = ["Leash", "BeamTarget"];
+
+/// Compounds within tile entities holding a block position as `X`, `Y` and `Z`
+const TILE_ENTITY_BLOCK_POS_COMPOUNDS: [&str; 1] = ["ExitPortal"];
+
+/// Moves an entity and its passengers by `offset` blocks
+pub fn offset_entity(entity: &mut NbtCompound, offset: BlockPos) -> Result<(), NbtReprError> {
+    if let Ok(pos) = entity.get_mut::<_, &mut NbtList>("Pos") {
+        *pos.get_mut::<&mut f64>(0)? += offset.x as f64;
+        *pos.get_mut::<&mut f64>(1)? += offset.y as f64;
+        *pos.get_mut::<&mut f64>(2)? += offset.z as f64;
+    }
+    for keys in ENTITY_BLOCK_COORDS {
+        offset_coords(entity, keys, offset)?;
+    }
+    for key in ENTITY_BLOCK_POS_COMPOUNDS {
+        if let Ok(pos) = entity.get_mut::<_, &mut NbtCompound>(key) {
+            offset_coords(pos, ["X", "Y", "Z"], offset)?;
+        }
+    }
+    if let Ok(passengers) = entity.get_mut::<_, &mut NbtList>("Passengers") {
+        for passenger in passengers.iter_mut() {
+            offset_entity(passenger.try_into()?, offset)?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves a tile entity by `offset` blocks
+pub fn offset_tile_entity(tile_entity: &mut NbtCompound, offset: BlockPos) -> Result<(), NbtReprError> {
+    offset_coords(tile_entity, ["x", "y", "z"], offset)?;
+    for key in TILE_ENTITY_BLOCK_POS_COMPOUNDS {
+        if let Ok(pos) = tile_entity.get_mut::<_, &mut NbtCompound>(key) {
+            offset_coords(pos, ["X", "Y", "Z"], offset)?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves a scheduled block update by `offset` blocks
+pub fn offset_tile_tick(tile_tick: &mut NbtCompound, offset: BlockPos) -> Result<(), NbtReprError> {
+    offset_coords(tile_tick, ["x", "y", "z"], offset)
+}
+
+/// Applies [`offset_entity`] to every entity of a list
+pub fn offset_entities(entities: &mut NbtList, offset: BlockPos) -> Result<(), NbtReprError> {
+    for entity in entities.iter_mut() {
+        offset_entity(entity.try_into()?, offset)?;
+    }
+    Ok(())
+}
+
+/// Applies [`offset_tile_entity`] to every tile entity of a list
+pub fn offset_tile_entities(tile_entities: &mut NbtList, offset: BlockPos) -> Result<(), NbtReprError> {
+    for tile_entity in tile_entities.iter_mut() {
+        offset_tile_entity(tile_entity.try_into()?, offset)?;
+    }
+    Ok(())
+}
+
+/// Applies [`offset_tile_tick`] to every scheduled block update of a list
+pub fn offset_tile_ticks(tile_ticks: &mut NbtList, offset: BlockPos) -> Result<(), NbtReprError> {
+    for tile_tick in tile_ticks.iter_mut() {
+        offset_tile_tick(tile_tick.try_into()?, offset)?;
+    }
+    Ok(())
+}
+
+fn offset_coords(compound: &mut NbtCompound, keys: [&str; 3], offset: BlockPos) -> Result<(), NbtReprError> {
+    for (key, offset) in keys.iter().zip([offset.x, offset.y, offset.z]) {
+        if compound.contains_key(*key) {
+            *compound.get_mut::<_, &mut i32>(*key)? += offset;
+        }
+    }
+    Ok(())
+}
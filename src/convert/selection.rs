@@ -0,0 +1,57 @@
+use crate::{
+    convert::entry_location::MinecraftChunkLocation,
+    util::{
+        aabb::{BlockAabb, CubeAabb},
+        positions::{CubePos, MinecraftRegionPos},
+    },
+};
+
+/// Part of a source world to convert, as a union of boxes in source world coordinates.
+#[derive(Clone, Debug)]
+pub struct Selection {
+    boxes: Vec<BlockAabb>,
+}
+
+impl Selection {
+    pub fn from_blocks(boxes: Vec<BlockAabb>) -> Self {
+        Self { boxes }
+    }
+
+    /// x and z of the cube boxes are chunk coordinates
+    pub fn from_cubes(boxes: Vec<CubeAabb>) -> Self {
+        Self {
+            boxes: boxes.iter().map(CubeAabb::to_block_aabb).collect(),
+        }
+    }
+
+    pub fn boxes(&self) -> &[BlockAabb] {
+        &self.boxes
+    }
+
+    pub fn contains_region(&self, region_pos: MinecraftRegionPos) -> bool {
+        let min = region_pos.to_chunk_pos();
+        let max = region_pos.to_chunk_pos_offset(
+            MinecraftRegionPos::DIAMETER_IN_CHUNKS as i32 - 1,
+            MinecraftRegionPos::DIAMETER_IN_CHUNKS as i32 - 1,
+        );
+        self.intersects(&BlockAabb::column(min.x << 4, min.z << 4, (max.x << 4) + 15, (max.z << 4) + 15))
+    }
+
+    pub fn contains_chunk(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        let (min_x, min_z) = (chunk_x << 4, chunk_z << 4);
+        self.intersects(&BlockAabb::column(min_x, min_z, min_x + 15, min_z + 15))
+    }
+
+    pub fn contains_cube(&self, cube_x: i32, cube_y: i32, cube_z: i32) -> bool {
+        let cube = CubePos::new(cube_x, cube_y, cube_z);
+        self.intersects(&CubeAabb::new(cube, cube).to_block_aabb())
+    }
+
+    pub fn contains_minecraft_chunk(&self, location: MinecraftChunkLocation) -> bool {
+        self.contains_chunk(location.x, location.z)
+    }
+
+    fn intersects(&self, aabb: &BlockAabb) -> bool {
+        self.boxes.iter().any(|b| b.intersects(aabb))
+    }
+}
@@ -1,6 +1,10 @@
 use std::{
     any::Any,
-    sync::{atomic::AtomicUsize, Arc},
+    fmt::{Display, Formatter},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     thread::JoinHandle,
 };
 
@@ -8,6 +12,8 @@ use std::{
 pub enum ErrorCause {
     Reader,
     Converter(usize),
+    /// Inputs the converter returned an error for, which are missing from the output
+    Skipped,
     InfoConverter,
     Writer,
 }
@@ -18,6 +24,30 @@ pub struct ConverterError {
     pub error: Box<dyn Any + Send>,
 }
 
+impl Display for ErrorCause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Reader => f.write_str("Read thread"),
+            Self::Converter(idx) => f.write_str(&format!("Convert thread {}", idx)),
+            Self::Skipped => f.write_str("Conversion"),
+            Self::InfoConverter => f.write_str("Info converter thread"),
+            Self::Writer => f.write_str("Write thread"),
+        }
+    }
+}
+
+impl Display for ConverterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // threads stop by panicking, with a message from panic! or unwrap
+        let message = match (self.error.downcast_ref::<&str>(), self.error.downcast_ref::<String>()) {
+            (Some(message), _) => message,
+            (_, Some(message)) => message.as_str(),
+            _ => "unknown error",
+        };
+        f.write_str(&format!("{} failed: {}", self.cause, message))
+    }
+}
+
 pub struct ConverterWaiter {
     pub read_thread: JoinHandle<()>,
     pub convert_threads: Vec<JoinHandle<()>>,
@@ -25,6 +55,8 @@ pub struct ConverterWaiter {
     pub write_thread: JoinHandle<()>,
 
     pub tasks_sent: Arc<AtomicUsize>,
+    /// Inputs which couldn't be converted and were left out
+    pub tasks_skipped: Arc<AtomicUsize>,
     pub convert_queue_fill: Arc<AtomicUsize>,
     pub convert_queue_size: usize,
     pub write_queue_fill: Arc<AtomicUsize>,
@@ -66,6 +98,13 @@ impl ConverterWaiter {
                 });
             }
         }
+        let skipped = self.tasks_skipped.load(Ordering::Relaxed);
+        if skipped > 0 {
+            errors.push(ConverterError {
+                cause: ErrorCause::Skipped,
+                error: Box::new(format!("{skipped} inputs couldn't be converted and were skipped")),
+            });
+        }
         if let Err(err) = self.info_converter_thread.join() {
             errors.push(ConverterError {
                 cause: ErrorCause::InfoConverter,
@@ -1,32 +1,57 @@
 use std::path::Path;
 
 use crate::{
-    convert::data::anvil::Data,
+    convert::{data::anvil::Data, entry_location::MinecraftChunkLocation, selection::Selection},
     io::region_reader::{RegionData, RegionReader},
     util::positions::MinecraftRegionPos,
 };
 
 const ANVIL_SECTOR_SIZE: usize = 4096;
 
-pub type AnvilRegionReader = RegionReader<MinecraftRegionPos, fn(MinecraftRegionPos, RegionData) -> Vec<Data>, ANVIL_SECTOR_SIZE>;
+pub type AnvilRegionReader =
+    RegionReader<MinecraftRegionPos, Box<dyn Fn(MinecraftRegionPos, RegionData) -> Vec<Data> + Send + Sync>, ANVIL_SECTOR_SIZE>;
 
 pub fn create_anvil_region_reader(path: &Path) -> AnvilRegionReader {
-    RegionReader::new(&path.join("region"), |region_pos, region_data| {
-        let mut data_out = Vec::with_capacity(MinecraftRegionPos::CHUNKS_COUNT);
-        let data = &region_data.data;
-        let indices = &region_data.chunk_indices;
+    RegionReader::new(
+        &path.join("region"),
+        Box::new(|region_pos, region_data| extract_chunks(region_pos, region_data, |_| true)),
+    )
+}
+
+/// Only reads the regions and chunks which intersect `selection`
+pub fn create_selective_anvil_region_reader(path: &Path, selection: Selection) -> AnvilRegionReader {
+    let region_selection = selection.clone();
+    let reader: AnvilRegionReader = RegionReader::new(
+        &path.join("region"),
+        Box::new(move |region_pos, region_data| {
+            extract_chunks(region_pos, region_data, |location| selection.contains_minecraft_chunk(location))
+        }),
+    );
+    reader.with_region_filter(Box::new(move |region_pos| region_selection.contains_region(*region_pos)))
+}
+
+fn extract_chunks<F>(region_pos: MinecraftRegionPos, region_data: RegionData, include: F) -> Vec<Data>
+where
+    F: Fn(MinecraftChunkLocation) -> bool,
+{
+    let mut data_out = Vec::with_capacity(MinecraftRegionPos::CHUNKS_COUNT);
+    let data = &region_data.data;
+    let indices = &region_data.chunk_indices;
 
-        for x in 0..MinecraftRegionPos::DIAMETER_IN_CHUNKS {
-            for z in 0..MinecraftRegionPos::DIAMETER_IN_CHUNKS {
-                let i = x + z * MinecraftRegionPos::DIAMETER_IN_CHUNKS;
-                if let Some((start, end)) = indices[i] {
-                    data_out.push(Data {
-                        position: region_pos.to_minecraft_chunk_location_offset(x as i32, z as i32),
-                        data: data[start..end].to_vec(),
-                    });
+    for x in 0..MinecraftRegionPos::DIAMETER_IN_CHUNKS {
+        for z in 0..MinecraftRegionPos::DIAMETER_IN_CHUNKS {
+            let i = x + z * MinecraftRegionPos::DIAMETER_IN_CHUNKS;
+            if let Some((start, end)) = indices[i] {
+                let position = region_pos.to_minecraft_chunk_location_offset(x as i32, z as i32);
+                if !include(position) {
+                    continue;
                 }
+                data_out.push(Data {
+                    position,
+                    data: data[start..end].to_vec(),
+                });
             }
         }
-        data_out
-    })
+    }
+    data_out
 }
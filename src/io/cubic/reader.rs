@@ -0,0 +1,140 @@
+use std::{
+    collections::HashMap,
+    fs,
+    hash::Hash,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    convert::entry_location::{EntryLocation2d, EntryLocation3d, Key},
+    io::region_reader::RegionReadError,
+    util::positions::{RegionPos2d, RegionPos3d},
+};
+
+/// Entries of a single region, `None` where nothing is stored
+type RegionEntries = Vec<Option<Vec<u8>>>;
+
+/// Random access reader for the region2d and region3d files of a cubic chunks world
+pub struct CubicRegionReader {
+    inner_2d: CachingRegionReader<RegionPos2d>,
+    inner_3d: CachingRegionReader<RegionPos3d>,
+}
+
+impl CubicRegionReader {
+    pub fn new(path: &Path, max_cache_size: usize) -> Self {
+        Self {
+            inner_2d: CachingRegionReader::new(
+                &path.join("region2d"),
+                EntryLocation2d::SECTOR_SIZE,
+                EntryLocation2d::ENTRIES_PER_REGION,
+                max_cache_size,
+            ),
+            inner_3d: CachingRegionReader::new(
+                &path.join("region3d"),
+                EntryLocation3d::SECTOR_SIZE,
+                EntryLocation3d::ENTRIES_PER_REGION,
+                max_cache_size,
+            ),
+        }
+    }
+
+    pub fn read_column(&mut self, pos: EntryLocation2d) -> Result<Option<&[u8]>, RegionReadError> {
+        self.inner_2d.read(pos)
+    }
+
+    pub fn read_cube(&mut self, pos: EntryLocation3d) -> Result<Option<&[u8]>, RegionReadError> {
+        self.inner_3d.read(pos)
+    }
+
+    /// Positions of all cubes stored in a 3d region
+    pub fn cubes_in_region(&mut self, region_pos: RegionPos3d) -> Result<Vec<EntryLocation3d>, RegionReadError> {
+        let size = RegionPos3d::DIAMETER_IN_CUBES as i32;
+        let min = region_pos.to_cube_pos();
+        let entries = self.inner_3d.region(region_pos, &region_pos.region_key())?;
+
+        let mut cubes = Vec::new();
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    let pos = EntryLocation3d::new(min.x + x, min.y + y, min.z + z);
+                    if entries[pos.id()].is_some() {
+                        cubes.push(pos);
+                    }
+                }
+            }
+        }
+        Ok(cubes)
+    }
+}
+
+struct CachingRegionReader<REGION> {
+    path: PathBuf,
+    sector_size: usize,
+    entries_per_region: usize,
+    max_cache_size: usize,
+
+    region_cache: HashMap<REGION, RegionEntries>,
+}
+
+impl<REGION: Eq + Hash + Copy> CachingRegionReader<REGION> {
+    fn new(path: &Path, sector_size: usize, entries_per_region: usize, max_cache_size: usize) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            sector_size,
+            entries_per_region,
+            max_cache_size,
+            region_cache: HashMap::new(),
+        }
+    }
+
+    fn read<KEY>(&mut self, entry_location: KEY) -> Result<Option<&[u8]>, RegionReadError>
+    where
+        KEY: Key<REGION> + Copy,
+    {
+        let entries = self.region(entry_location.to_region_pos(), &entry_location.region_key())?;
+        Ok(entries[entry_location.id()].as_deref())
+    }
+
+    fn region(&mut self, region_pos: REGION, region_key: &str) -> Result<&RegionEntries, RegionReadError> {
+        if !self.region_cache.contains_key(&region_pos) {
+            if self.region_cache.len() >= self.max_cache_size {
+                self.region_cache.clear();
+            }
+            let entries = read_region_entries(&self.path.join(region_key), self.sector_size, self.entries_per_region)?;
+            self.region_cache.insert(region_pos, entries);
+        }
+        Ok(&self.region_cache[&region_pos])
+    }
+}
+
+/// Reads every entry of a cubic chunks region file. A missing file has no entries.
+pub fn read_region_entries(path: &Path, sector_size: usize, entries_per_region: usize) -> Result<RegionEntries, RegionReadError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![None; entries_per_region]),
+        Err(err) => return Err(RegionReadError::StdIo(err)),
+    };
+    if bytes.len() < entries_per_region * 4 {
+        return Err(RegionReadError::MissingHeader);
+    }
+
+    let mut entries = vec![None; entries_per_region];
+    for (idx, entry) in entries.iter_mut().enumerate() {
+        let header = u32::from_be_bytes(bytes[idx * 4..idx * 4 + 4].try_into().unwrap());
+        let offset = (header >> 8) as usize * sector_size;
+        let sectors = (header & 0xFF) as usize;
+        if offset == 0 || sectors == 0 || offset + 4 > bytes.len() {
+            continue;
+        }
+
+        // each entry starts with its length, the rest of the last sector is padding
+        let size = u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        let end = offset + 4 + size;
+        if end > bytes.len() || end > offset + sectors * sector_size {
+            continue;
+        }
+        entry.replace(bytes[offset + 4..end].to_vec());
+    }
+    Ok(entries)
+}
@@ -6,7 +6,7 @@ use crate::{
         data::cc_1_12::CubicChunks112Data,
         entry_location::{EntryLocation2d, EntryLocation3d},
     },
-    io::region_writer::CachingRegionWriter,
+    io::{region_writer::CachingRegionWriter, write_region::RegionWriteError},
     util::positions::{RegionPos2d, RegionPos3d},
 };
 
@@ -33,6 +33,18 @@ impl CubicRegionWriter {
             )?,
         })
     }
+
+    pub fn write_column(&mut self, pos: EntryLocation2d, data: &[u8]) -> Result<(), RegionWriteError> {
+        self.inner_2d.write(pos, data)
+    }
+
+    pub fn write_cube(&mut self, pos: EntryLocation3d, data: &[u8]) -> Result<(), RegionWriteError> {
+        self.inner_3d.write(pos, data)
+    }
+
+    pub fn remove_cube(&mut self, pos: EntryLocation3d) -> Result<(), RegionWriteError> {
+        self.inner_3d.remove(pos)
+    }
 }
 
 impl Writer<CubicChunks112Data> for CubicRegionWriter {
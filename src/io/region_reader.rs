@@ -10,6 +10,7 @@ use crate::{
         converter::{ReadError, Reader},
         entry_location::RegionPos,
     },
+    log_targets,
     util::errors::error_from,
 };
 
@@ -18,13 +19,17 @@ pub struct RegionData {
     pub chunk_indices: Vec<Option<(usize, usize)>>,
 }
 
+pub type RegionFilter<POS> = Box<dyn Fn(&POS) -> bool + Send + Sync>;
+
 pub struct RegionReader<POS, EXTRACT, const SECTOR_SIZE: usize> {
     region_location: PathBuf,
     extract_chunks_function: EXTRACT,
+    region_filter: Option<RegionFilter<POS>>,
 
     marker_pos: PhantomData<POS>,
 }
 
+#[derive(Debug)]
 pub enum RegionReadError {
     StdIo(std::io::Error),
     MissingHeader,
@@ -52,10 +57,17 @@ where
         Self {
             region_location: region_location.to_path_buf(),
             extract_chunks_function: f,
+            region_filter: None,
             marker_pos: PhantomData::default(),
         }
     }
 
+    /// Region files for which `filter` returns false are skipped without being read
+    pub fn with_region_filter(mut self, filter: RegionFilter<POS>) -> Self {
+        self.region_filter = Some(filter);
+        self
+    }
+
     pub fn read_region(&self, position: &POS) -> Result<RegionData, RegionReadError>
     where
         POS: RegionPos,
@@ -124,6 +136,11 @@ where
                         None => return,
                     };
                     if let Some(region_pos) = POS::from_file_name(file_name) {
+                        if let Some(region_filter) = &self.region_filter {
+                            if !region_filter(&region_pos) {
+                                return;
+                            }
+                        }
                         match self.read_region(&region_pos) {
                             Ok(region_data) => {
                                 let chunks_data = (self.extract_chunks_function)(region_pos, region_data);
@@ -132,7 +149,7 @@ where
                                     data_consumer(data);
                                 }
                             }
-                            Err(err) => println!("Error reading region {file_name}, skipping it.\n{err}"),
+                            Err(err) => log::warn!(target: log_targets::READER, "Error reading region {file_name}, skipping it. {err}"),
                         }
                     }
                 }
@@ -32,6 +32,22 @@ impl<REGION> CachingRegionWriter<REGION> {
     }
 
     pub fn write<KEY>(&mut self, entry_location: KEY, data: &[u8]) -> Result<(), RegionWriteError>
+    where
+        REGION: Eq + Hash,
+        KEY: Key<REGION> + Copy,
+    {
+        self.region(entry_location)?.write(&entry_location, data)
+    }
+
+    pub fn remove<KEY>(&mut self, entry_location: KEY) -> Result<(), RegionWriteError>
+    where
+        REGION: Eq + Hash,
+        KEY: Key<REGION> + Copy,
+    {
+        self.region(entry_location)?.remove(&entry_location)
+    }
+
+    fn region<KEY>(&mut self, entry_location: KEY) -> Result<&mut WriteRegion, RegionWriteError>
     where
         REGION: Eq + Hash,
         KEY: Key<REGION> + Copy,
@@ -40,16 +56,13 @@ impl<REGION> CachingRegionWriter<REGION> {
             self.flush()?;
         }
 
-        let region = self.region_cache.entry(entry_location.to_region_pos()).or_insert_with(|| {
+        Ok(self.region_cache.entry(entry_location.to_region_pos()).or_insert_with(|| {
             WriteRegion::new(
                 &self.path.join(entry_location.region_key()),
                 self.sector_size,
                 self.entries_per_region,
             )
-        });
-
-        region.write(&entry_location, data)?;
-        Ok(())
+        }))
     }
 
     pub fn flush(&mut self) -> Result<(), RegionWriteError> {
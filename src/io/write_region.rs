@@ -73,6 +73,21 @@ impl WriteRegion {
         Ok(())
     }
 
+    /// Removes the entry at `key`, if there is one
+    pub fn remove<K, R>(&mut self, key: &K) -> Result<(), RegionWriteError>
+    where
+        K: Key<R>,
+    {
+        if self.write_entries.is_none() {
+            self.initialize()?;
+        }
+
+        self.write_entries
+            .as_mut()
+            .expect("Initialize is complete, this should be unreachable")[key.id()] = None;
+        Ok(())
+    }
+
     fn initialize(&mut self) -> Result<(), RegionWriteError> {
         let bytes = match fs::read(&self.path) {
             Ok(b) => b,
@@ -101,7 +116,8 @@ impl WriteRegion {
 
             let byte_offset = offset * self.sector_size as u32;
 
-            let slice_start = (byte_offset + 4) as usize;
+            // keep the length prefix, entries are written back as they are stored
+            let slice_start = byte_offset as usize;
             let slice_end = (byte_offset + (size * self.sector_size as u32)) as usize;
 
             entry.replace(bytes[slice_start..slice_end].to_vec());
@@ -162,3 +178,46 @@ impl WriteRegion {
         (size, offset)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::WriteRegion;
+    use crate::convert::entry_location::EntryLocation2d;
+    use crate::util::test_utils;
+
+    /// The payload of every entry stored in a region file
+    fn stored_entries(bytes: &[u8]) -> Vec<Option<Vec<u8>>> {
+        (0..EntryLocation2d::ENTRIES_PER_REGION)
+            .map(|idx| {
+                let (size, offset) = WriteRegion::unpack_size_offset(bytes, idx * 4);
+                if size == 0 {
+                    return None;
+                }
+                let start = offset as usize * EntryLocation2d::SECTOR_SIZE;
+                let length = u32::from_be_bytes(bytes[start..start + 4].try_into().unwrap()) as usize;
+                Some(bytes[start + 4..start + 4 + length].to_vec())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn entries_survive_reopening_the_region() {
+        let path = test_utils::test_output_path("write_region_reopen").join("0.0.2dr");
+        let new_region = || WriteRegion::new(&path, EntryLocation2d::SECTOR_SIZE, EntryLocation2d::ENTRIES_PER_REGION);
+
+        let mut region = new_region();
+        region.write(&EntryLocation2d::new(0, 0), b"first").unwrap();
+        region.flush().unwrap();
+        // entries already in the file are read back and written again with the new one
+        let mut region = new_region();
+        region.write(&EntryLocation2d::new(0, 1), b"second").unwrap();
+        region.flush().unwrap();
+
+        let entries = stored_entries(&fs::read(&path).unwrap());
+        assert_eq!(entries[0].as_deref(), Some(&b"first"[..]));
+        assert_eq!(entries[1].as_deref(), Some(&b"second"[..]));
+        assert_eq!(entries.iter().flatten().count(), 2);
+    }
+}
@@ -1,26 +1,72 @@
 use std::path::Path;
 
 use convert::{
-    anvil2cc::{conv::Anvil2CCConverter, info::Anvil2CCLevelInfoConverter},
+    anvil2cc::{conv::Anvil2CCConverter, info::Anvil2CCLevelInfoConverter, population::PopulationMap},
     run_conversion,
-    waiter::ConverterWaiter,
-    ConverterCreateCtx,
 };
-use io::{anvil::reader::create_anvil_region_reader, cubic::writer::CubicRegionWriter};
+use io::{
+    anvil::reader::{create_anvil_region_reader, create_selective_anvil_region_reader},
+    cubic::writer::CubicRegionWriter,
+};
+use util::positions::CubePos;
+
+pub use convert::cc2cc::copy::{copy_cubes, CopyCubesConfig, CopyCubesError};
+pub use convert::cc2cc::prune::{prune_cubes, PruneCubesConfig, PruneCubesError};
+pub use convert::selection::Selection;
+pub use convert::waiter::{ConverterError, ConverterWaiter, ErrorCause};
+pub use convert::ConverterCreateCtx;
+pub use util::aabb::{Aabb, BlockAabb, CubeAabb};
+
+#[cfg(feature = "dfu")]
+pub use convert::anvil2cc::upgrade::DATA_VERSION_1_12_2;
+#[cfg(feature = "dfu")]
+pub use dfu;
 
 mod convert;
 mod dimension;
 mod io;
+pub mod log_targets;
 mod util;
 
 pub struct Anvil2CCConfig {
     pub fix_missing_tile_entities: bool,
+    /// Don't write cubes with only air and no entities, except the ones cubic chunks requires
+    pub skip_empty_cubes: bool,
+    /// Horizontal shift of the converted world, in chunks
+    pub x_offset: i32,
+    /// Vertical shift of the converted world, in cubes
+    pub y_offset: i32,
+    /// Horizontal shift of the converted world, in chunks
+    pub z_offset: i32,
+    /// Only convert the selected part of the source world. Offsets are applied after selecting
+    pub selection: Option<Selection>,
+    /// Upgrades chunks, level.dat and player data saved by older versions of the game to this DataVersion before
+    /// converting. Cubic chunks runs on 1.12.2, see [`DATA_VERSION_1_12_2`]. Later versions are refused, upgrading past it
+    /// would flatten the blocks of 1.13.
+    #[cfg(feature = "dfu")]
+    pub upgrade_to: Option<i32>,
     pub ctx: ConverterCreateCtx,
 }
 
 pub fn anvil2cc(src_path: &Path, dst_path: &Path, config: Anvil2CCConfig) -> Result<ConverterWaiter, std::io::Error> {
-    let reader = create_anvil_region_reader(src_path);
-    let converter = Anvil2CCConverter::new(config.fix_missing_tile_entities);
+    #[cfg(feature = "dfu")]
+    if let Some(data_version) = config.upgrade_to.filter(|data_version| *data_version > DATA_VERSION_1_12_2) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("can't upgrade to DataVersion {data_version}, cubic chunks worlds are 1.12.2 ({DATA_VERSION_1_12_2}) at most"),
+        ));
+    }
+    let reader = match &config.selection {
+        Some(selection) => create_selective_anvil_region_reader(src_path, selection.clone()),
+        None => create_anvil_region_reader(src_path),
+    };
+    let converter = Anvil2CCConverter::new(
+        config.fix_missing_tile_entities,
+        config.skip_empty_cubes,
+        CubePos::new(config.x_offset, config.y_offset, config.z_offset),
+        config.selection,
+        PopulationMap::new(src_path),
+    );
     let writer = CubicRegionWriter::new(dst_path, 64)?;
 
     let info_converter = Anvil2CCLevelInfoConverter::new(src_path, dst_path, |base, path| {
@@ -37,6 +83,15 @@ pub fn anvil2cc(src_path: &Path, dst_path: &Path, config: Anvil2CCConfig) -> Res
         false
     });
 
+    #[cfg(feature = "dfu")]
+    let (converter, info_converter) = match config.upgrade_to {
+        Some(data_version) => {
+            let upgrader = std::sync::Arc::new(convert::anvil2cc::upgrade::Upgrader::for_world(src_path, data_version));
+            (converter.with_upgrader(upgrader.clone()), info_converter.with_upgrader(upgrader))
+        }
+        None => (converter, info_converter),
+    };
+
     let waiter = run_conversion(config.ctx, reader, converter, info_converter, writer);
     Ok(waiter)
 }
@@ -57,6 +112,13 @@ mod tests {
             &dst_path,
             crate::Anvil2CCConfig {
                 fix_missing_tile_entities: true,
+                skip_empty_cubes: false,
+                x_offset: 0,
+                y_offset: 0,
+                z_offset: 0,
+                selection: None,
+                #[cfg(feature = "dfu")]
+                upgrade_to: Some(crate::DATA_VERSION_1_12_2),
                 ctx: Default::default(),
             },
         )
@@ -64,4 +126,25 @@ mod tests {
 
         waiter.join_all().unwrap();
     }
+
+    #[cfg(feature = "dfu")]
+    #[test]
+    fn anvil2cc_refuses_upgrades_past_1_12_2() {
+        let src_path = test_utils::test_resources_path().join("anvil2cc/in");
+        let dst_path = test_utils::test_output_path("anvil2cc_refuses_upgrades").join("out");
+
+        let config = crate::Anvil2CCConfig {
+            fix_missing_tile_entities: false,
+            skip_empty_cubes: false,
+            x_offset: 0,
+            y_offset: 0,
+            z_offset: 0,
+            selection: None,
+            upgrade_to: Some(1451),
+            ctx: Default::default(),
+        };
+        let err = anvil2cc(&src_path, &dst_path, config).err().expect("1451 is past 1.12.2");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(!dst_path.exists());
+    }
 }
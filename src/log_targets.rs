@@ -0,0 +1,14 @@
+// Diagnostics are logged through the `log` crate, under a target for each stage of a conversion. Nothing is logged
+// unless a logger is installed, e.g. `env_logger` with `RUST_LOG=lib_cc_converter::reader=debug`.
+
+/// Reading source regions, e.g. regions which are skipped because they can't be read
+pub const READER: &str = "lib_cc_converter::reader";
+/// Converting chunks and cubes
+pub const CONVERTER: &str = "lib_cc_converter::converter";
+/// Writing converted regions
+pub const WRITER: &str = "lib_cc_converter::writer";
+/// Converting level.dat and copying the other files of the world
+pub const INFO: &str = "lib_cc_converter::info";
+/// Upgrading data saved by older versions of the game, logged by the data fixer
+#[cfg(feature = "dfu")]
+pub const DFU: &str = dfu::LOG_TARGET;
@@ -1,8 +1,291 @@
-pub struct AABB {
-    pub min_x: i32,
-    pub max_x: i32,
-    pub min_y: i32,
-    pub max_y: i32,
-    pub min_z: i32,
-    pub max_z: i32,
+use std::fmt::{Debug, Formatter};
+
+use crate::util::positions::{
+    BlockPos, BlockSpace, ChunkPos, CubePos, CubeSpace, MinecraftRegionPos, RegionPos2d, RegionPos3d, RegionSpace3d,
+};
+use crate::util::vec::vec3i::Vec3i;
+use crate::util::vec::CoordinateSpace;
+
+/// An axis aligned box in `SPACE`, with inclusive bounds on every axis
+pub struct Aabb<SPACE: CoordinateSpace> {
+    pub min: Vec3i<SPACE>,
+    pub max: Vec3i<SPACE>,
+}
+
+pub type BlockAabb = Aabb<BlockSpace>;
+pub type CubeAabb = Aabb<CubeSpace>;
+pub type RegionAabb3d = Aabb<RegionSpace3d>;
+
+impl<SPACE: CoordinateSpace> Aabb<SPACE> {
+    /// Creates the box spanning both corners, in any order
+    pub fn new(a: Vec3i<SPACE>, b: Vec3i<SPACE>) -> Self {
+        Self {
+            min: Vec3i::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: Vec3i::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    pub fn from_coords(min_x: i32, min_y: i32, min_z: i32, max_x: i32, max_y: i32, max_z: i32) -> Self {
+        Self::new(Vec3i::new(min_x, min_y, min_z), Vec3i::new(max_x, max_y, max_z))
+    }
+
+    /// A box of all positions in a column, at any height
+    pub fn column(min_x: i32, min_z: i32, max_x: i32, max_z: i32) -> Self {
+        Self::from_coords(min_x, i32::MIN, min_z, max_x, i32::MAX, max_z)
+    }
+
+    pub fn contains(&self, pos: Vec3i<SPACE>) -> bool {
+        pos.x >= self.min.x
+            && pos.x <= self.max.x
+            && pos.y >= self.min.y
+            && pos.y <= self.max.y
+            && pos.z >= self.min.z
+            && pos.z <= self.max.z
+    }
+
+    pub fn contains_aabb(&self, other: &Self) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+            && self.min.z <= other.max.z
+            && self.max.z >= other.min.z
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if !self.intersects(other) {
+            return None;
+        }
+        Some(Self {
+            min: Vec3i::new(
+                self.min.x.max(other.min.x),
+                self.min.y.max(other.min.y),
+                self.min.z.max(other.min.z),
+            ),
+            max: Vec3i::new(
+                self.max.x.min(other.max.x),
+                self.max.y.min(other.max.y),
+                self.max.z.min(other.max.z),
+            ),
+        })
+    }
+
+    /// The smallest box containing both boxes
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: Vec3i::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: Vec3i::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    pub fn offset(&self, offset: Vec3i<SPACE>) -> Self {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// Iterates every position in the box, x outermost
+    pub fn iter(&self) -> impl Iterator<Item = Vec3i<SPACE>> {
+        let (min, max) = (self.min, self.max);
+        (min.x..=max.x).flat_map(move |x| (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| Vec3i::new(x, y, z))))
+    }
+
+    /// Scales every position by `1 << shift`, such that each position of the box is covered by the output box
+    fn scale_up<OUT: CoordinateSpace>(&self, shift: i32) -> Aabb<OUT> {
+        let mask = (1 << shift) - 1;
+        Aabb::from_coords(
+            self.min.x << shift,
+            self.min.y << shift,
+            self.min.z << shift,
+            (self.max.x << shift) | mask,
+            (self.max.y << shift) | mask,
+            (self.max.z << shift) | mask,
+        )
+    }
+
+    /// Divides every position by `1 << shift`, such that the output box covers every position of the box
+    fn scale_down<OUT: CoordinateSpace>(&self, shift: i32) -> Aabb<OUT> {
+        Aabb::from_coords(
+            self.min.x >> shift,
+            self.min.y >> shift,
+            self.min.z >> shift,
+            self.max.x >> shift,
+            self.max.y >> shift,
+            self.max.z >> shift,
+        )
+    }
+}
+
+impl BlockAabb {
+    /// All cubes containing at least one block of the box
+    pub fn to_cube_aabb(&self) -> CubeAabb {
+        self.scale_down(4)
+    }
+
+    pub fn to_3dr_aabb(&self) -> RegionAabb3d {
+        self.scale_down(8)
+    }
+
+    pub fn cubes(&self) -> impl Iterator<Item = CubePos> {
+        self.to_cube_aabb().iter()
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = ChunkPos> {
+        self.to_cube_aabb().chunks()
+    }
+}
+
+impl CubeAabb {
+    pub fn to_block_aabb(&self) -> BlockAabb {
+        self.scale_up(4)
+    }
+
+    pub fn to_3dr_aabb(&self) -> RegionAabb3d {
+        self.scale_down(4)
+    }
+
+    pub fn contains_block(&self, pos: BlockPos) -> bool {
+        self.contains(pos.to_cube_pos())
+    }
+
+    /// Every chunk column intersecting the box
+    pub fn chunks(&self) -> impl Iterator<Item = ChunkPos> {
+        let (min, max) = (self.min, self.max);
+        (min.x..=max.x).flat_map(move |x| (min.z..=max.z).map(move |z| ChunkPos::new(x, z)))
+    }
+
+    /// Every cubic chunks 3d region intersecting the box
+    pub fn regions_3d(&self) -> impl Iterator<Item = RegionPos3d> {
+        self.to_3dr_aabb().iter()
+    }
+
+    /// Every cubic chunks 2d region intersecting the box
+    pub fn regions_2d(&self) -> impl Iterator<Item = RegionPos2d> {
+        let (min, max) = (self.min.x >> 5, self.max.x >> 5);
+        let (min_z, max_z) = (self.min.z >> 5, self.max.z >> 5);
+        (min..=max).flat_map(move |x| (min_z..=max_z).map(move |z| RegionPos2d::new(x, z)))
+    }
+
+    /// Every anvil region intersecting the box
+    pub fn minecraft_regions(&self) -> impl Iterator<Item = MinecraftRegionPos> {
+        self.regions_2d().map(|pos| MinecraftRegionPos::new(pos.x, pos.z))
+    }
+}
+
+impl RegionAabb3d {
+    pub fn to_cube_aabb(&self) -> CubeAabb {
+        self.scale_up(4)
+    }
+
+    pub fn to_block_aabb(&self) -> BlockAabb {
+        self.scale_up(8)
+    }
+}
+
+impl<SPACE: CoordinateSpace> Copy for Aabb<SPACE> {}
+impl<SPACE: CoordinateSpace> Clone for Aabb<SPACE> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<SPACE: CoordinateSpace> Eq for Aabb<SPACE> {}
+impl<SPACE: CoordinateSpace> PartialEq<Self> for Aabb<SPACE> {
+    fn eq(&self, other: &Self) -> bool {
+        self.min == other.min && self.max == other.max
+    }
+}
+
+impl<SPACE: CoordinateSpace> Debug for Aabb<SPACE> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("Aabb[{} -> {}]", self.min, self.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockAabb, CubeAabb};
+    use crate::util::positions::{BlockPos, ChunkPos, CubePos, RegionPos2d, RegionPos3d};
+
+    #[test]
+    fn new_orders_corners() {
+        let aabb = BlockAabb::new(BlockPos::new(5, -3, 2), BlockPos::new(-1, 7, 0));
+        assert_eq!(aabb, BlockAabb::from_coords(-1, -3, 0, 5, 7, 2));
+    }
+
+    #[test]
+    fn contains_and_intersects() {
+        let a = BlockAabb::from_coords(0, 0, 0, 15, 15, 15);
+        let b = BlockAabb::from_coords(15, 15, 15, 20, 20, 20);
+        let c = BlockAabb::from_coords(16, 0, 0, 20, 15, 15);
+
+        assert!(a.contains(BlockPos::new(15, 0, 7)));
+        assert!(!a.contains(BlockPos::new(16, 0, 7)));
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&c));
+        assert_eq!(a.intersection(&b), Some(BlockAabb::from_coords(15, 15, 15, 15, 15, 15)));
+        assert_eq!(a.intersection(&c), None);
+        assert_eq!(a.union(&c), BlockAabb::from_coords(0, 0, 0, 20, 15, 15));
+        assert!(a.union(&c).contains_aabb(&c));
+        assert!(BlockAabb::column(0, 0, 15, 15).intersects(&BlockAabb::from_coords(3, -100_000, 3, 3, -100_000, 3)));
+    }
+
+    #[test]
+    fn space_conversions() {
+        let blocks = BlockAabb::from_coords(-1, 0, 17, 16, 255, 31);
+        let cubes = blocks.to_cube_aabb();
+        assert_eq!(cubes, CubeAabb::from_coords(-1, 0, 1, 1, 15, 1));
+        assert_eq!(cubes.to_block_aabb(), BlockAabb::from_coords(-16, 0, 16, 31, 255, 31));
+        assert!(cubes.to_block_aabb().contains_aabb(&blocks));
+        assert_eq!(cubes.to_3dr_aabb().to_cube_aabb(), CubeAabb::from_coords(-16, 0, 0, 15, 15, 15));
+    }
+
+    #[test]
+    fn iterators() {
+        let cubes = CubeAabb::from_coords(-1, 0, 31, 0, 1, 32);
+        assert_eq!(cubes.iter().count(), 8);
+        assert!(cubes.iter().all(|pos| cubes.contains(pos)));
+
+        let chunks: Vec<_> = cubes.chunks().collect();
+        assert_eq!(
+            chunks,
+            vec![
+                ChunkPos::new(-1, 31),
+                ChunkPos::new(-1, 32),
+                ChunkPos::new(0, 31),
+                ChunkPos::new(0, 32)
+            ]
+        );
+
+        let regions_2d: Vec<_> = cubes.regions_2d().collect();
+        assert_eq!(
+            regions_2d,
+            vec![
+                RegionPos2d::new(-1, 0),
+                RegionPos2d::new(-1, 1),
+                RegionPos2d::new(0, 0),
+                RegionPos2d::new(0, 1)
+            ]
+        );
+
+        let regions_3d: Vec<_> = cubes.regions_3d().collect();
+        assert_eq!(regions_3d.len(), 4);
+        assert!(regions_3d.contains(&RegionPos3d::new(-1, 0, 2)));
+
+        let block_cubes: Vec<_> = BlockAabb::from_coords(0, 0, 0, 16, 0, 0).cubes().collect();
+        assert_eq!(block_cubes, vec![CubePos::new(0, 0, 0), CubePos::new(1, 0, 0)]);
+    }
 }
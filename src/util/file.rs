@@ -1,5 +1,7 @@
 use std::path::Path;
 
+use crate::log_targets;
+
 pub fn copy_everything_except(path: &Path, src: &Path, dst: &Path, exclude: &dyn Fn(&Path, &Path) -> bool) -> Result<(), std::io::Error>
 where
 {
@@ -7,7 +9,7 @@ where
         let src_file = file?.path();
         if let Some(relative) = pathdiff::diff_paths(&src_file, src) {
             if !exclude(src, &src_file) {
-                println!("copying {}", src_file.to_string_lossy());
+                log::debug!(target: log_targets::INFO, "copying {}", src_file.to_string_lossy());
 
                 let dst_file = dst.join(relative);
                 if src_file.is_dir() {
@@ -16,11 +18,16 @@ where
                     }
                     copy_everything_except(&src_file, src, dst, exclude)?;
                 } else {
-                    println!("copying {} to {}", &src_file.to_string_lossy(), &dst_file.to_string_lossy());
+                    log::debug!(
+                        target: log_targets::INFO,
+                        "copying {} to {}",
+                        &src_file.to_string_lossy(),
+                        &dst_file.to_string_lossy()
+                    );
                     std::fs::copy(&src_file, dst_file)?;
                 }
             } else {
-                println!("excluded {}", src_file.to_string_lossy());
+                log::debug!(target: log_targets::INFO, "excluded {}", src_file.to_string_lossy());
             }
         }
     }
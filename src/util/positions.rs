@@ -6,6 +6,7 @@ use crate::util::vec::vec2i::Vec2i;
 use crate::util::vec::vec3i::Vec3i;
 use crate::util::vec::CoordinateSpace;
 
+#[derive(Debug)]
 pub struct BlockSpace {}
 impl CoordinateSpace for BlockSpace {}
 pub type BlockPos = Vec3i<BlockSpace>;
@@ -20,6 +21,7 @@ impl BlockPos {
     }
 }
 
+#[derive(Debug)]
 pub struct CubeSpace {}
 impl CoordinateSpace for CubeSpace {}
 pub type CubePos = Vec3i<CubeSpace>;
@@ -42,6 +44,10 @@ pub struct RegionSpace3d {}
 impl CoordinateSpace for RegionSpace3d {}
 pub type RegionPos3d = Vec3i<RegionSpace3d>;
 
+lazy_static! {
+    static ref FORMAT_REGEX_3DR: Regex = Regex::new(r"^\-?\d+\.\-?\d+\.\-?\d+\.3dr$").unwrap();
+}
+
 impl RegionPos3d {
     pub const DIAMETER_IN_CUBES: usize = 16;
     pub const CUBES_COUNT: usize = Self::DIAMETER_IN_CUBES * Self::DIAMETER_IN_CUBES * Self::DIAMETER_IN_CUBES;
@@ -63,8 +69,21 @@ impl RegionPos3d {
     pub fn region_key(&self) -> RegionKey {
         format!("{}.{}.{}.3dr", self.x, self.y, self.z)
     }
+
+    pub fn from_file_name(filename: &str) -> Option<Self> {
+        if !FORMAT_REGEX_3DR.is_match(filename) {
+            return None;
+        }
+
+        let split: Vec<_> = filename.split('.').collect(); // string is valid, so length is 4
+        match (str::parse(split[0]), str::parse(split[1]), str::parse(split[2])) {
+            (Ok(x), Ok(y), Ok(z)) => Some(RegionPos3d::new(x, y, z)),
+            _ => None,
+        }
+    }
 }
 
+#[derive(Debug)]
 pub struct ChunkSpace {}
 impl CoordinateSpace for ChunkSpace {}
 pub type ChunkPos = Vec2i<ChunkSpace>;
@@ -81,7 +100,7 @@ impl CoordinateSpace for RegionSpace2d {}
 pub type RegionPos2d = Vec2i<RegionSpace2d>;
 
 lazy_static! {
-    static ref FORMAT_REGEX_2DR: Regex = Regex::new(r"^r\.\-?\d*\.\-?\d*\.2dr$").unwrap();
+    static ref FORMAT_REGEX_2DR: Regex = Regex::new(r"^\-?\d+\.\-?\d+\.2dr$").unwrap();
 }
 
 impl RegionPos2d {
@@ -106,10 +125,10 @@ impl RegionPos for RegionPos2d {
             return None;
         }
 
-        let split: Vec<_> = filename.split('.').collect(); // string is valid, so length is 4
+        let split: Vec<_> = filename.split('.').collect(); // string is valid, so length is 3
 
-        let x: Result<i32, _> = str::parse(split[1]);
-        let z: Result<i32, _> = str::parse(split[2]);
+        let x: Result<i32, _> = str::parse(split[0]);
+        let z: Result<i32, _> = str::parse(split[1]);
 
         if let Ok(x) = x {
             if let Ok(z) = z {
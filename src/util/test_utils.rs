@@ -6,3 +6,14 @@ pub fn test_resources_path() -> PathBuf {
     d.push("resources/test");
     d
 }
+
+/// An empty directory for a test to write into
+#[cfg(test)]
+pub fn test_output_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join("cc-converter-test").join(name);
+    if path.exists() {
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}